@@ -2,8 +2,12 @@
 /**
  * Comprehensive Golden Test Generation Script
  *
- * This script loads stardew-predictor and calls its ACTUAL functions directly.
- * NO reimplementation of any game logic.
+ * Loads a stardew-predictor checkout next to this repo. Cart stock comes from
+ * its own functions (getCartItem, getRandomItems, getRandomSeed); 1.6 cart
+ * prices and quantities follow its predictCart_1_6. Night events, daily luck
+ * and the dish of the day are inside its page code, which can't be called
+ * here, so they are ported by hand below from stardew-predictor and the
+ * decompiled game. Those series only check Rust against that port.
  *
  * Run with: node scripts/generate-comprehensive-golden.js
  */
@@ -153,10 +157,10 @@ function getCartItems(version, gameId, day) {
 }
 
 // ============================================================================
-// Night event prediction - exact copy from stardew-predictor
+// Night event prediction - ported by hand from stardew-predictor's predictNight
 // ============================================================================
 
-function getNightEvent(version, gameId, day) {
+function getNightEvent(version, gameId, day, greenhouseRepaired = false) {
     const eventDay = day + 1;
 
     if (eventDay === 30) {
@@ -172,6 +176,9 @@ function getNightEvent(version, gameId, day) {
         for (let i = 0; i < 10; i++) {
             rng.NextDouble();
         }
+        // The windstorm is only rolled once the greenhouse is repaired:
+        // Utility.pickFarmEvent checks random.NextBool(0.1) first
+        if (greenhouseRepaired && rng.NextDouble() < 0.1) return 'windstorm';
         if (rng.NextDouble() < 0.01 && month < 3) return 'fairy';
         if (rng.NextDouble() < 0.01 && eventDay > 20) return 'witch';
         if (rng.NextDouble() < 0.01 && eventDay > 5) return 'meteor';
@@ -332,12 +339,21 @@ function generateComprehensiveTests() {
                 daily_luck: [],
                 dish_of_day: [],
             };
+            if (stardew.compareSemVer(version, "1.6") >= 0) {
+                versionData.night_events_greenhouse = [];
+            }
 
             for (let day = 1; day <= CONFIG.NUM_DAYS; day++) {
                 const nightEvent = getNightEvent(version, seed, day);
                 versionData.night_events.push({ day, event: nightEvent });
                 totalTests++;
 
+                if (versionData.night_events_greenhouse) {
+                    const event = getNightEvent(version, seed, day, true);
+                    versionData.night_events_greenhouse.push({ day, event });
+                    totalTests++;
+                }
+
                 const luck = getDailyLuck(version, seed, day);
                 versionData.daily_luck.push({ day, luck: Math.round(luck * 100000) / 100000 });
                 totalTests++;
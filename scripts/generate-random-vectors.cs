@@ -0,0 +1,37 @@
+// Print SYSTEM_RANDOM_API_VECTORS (src/rng/validation_tests.rs) from the real System.Random.
+//
+// Usage: dotnet run scripts/generate-random-vectors.cs
+//
+// A seeded System.Random still runs the .NET Framework algorithm on modern .NET,
+// so any SDK that can run a single-file program (10+) works. Paste the output over
+// the table and check the draw order below still matches the test's.
+
+using System.Globalization;
+
+int[] seeds = [0, 1, 12345, -638161535, int.MinValue];
+
+string Ints(IEnumerable<int> values) => "[" + string.Join(", ", values) + "]";
+
+foreach (var seed in seeds)
+{
+    var rng = new Random(seed);
+    var next = Enumerable.Range(0, 3).Select(_ => rng.Next()).ToArray();
+    var nextMax = Enumerable.Range(0, 3).Select(_ => rng.Next(100)).ToArray();
+    var small = Enumerable.Range(0, 3).Select(_ => rng.Next(-50, 50)).ToArray();
+    var large = Enumerable.Range(0, 3).Select(_ => rng.Next(int.MinValue, int.MaxValue)).ToArray();
+    var wide = Enumerable.Range(0, 2).Select(_ => rng.Next(-2, int.MaxValue)).ToArray();
+    var doubles = Enumerable.Range(0, 2).Select(_ => rng.NextDouble().ToString("R", CultureInfo.InvariantCulture));
+    var bytes = new byte[8];
+    rng.NextBytes(bytes);
+
+    Console.WriteLine("        (");
+    Console.WriteLine($"            {(seed == int.MinValue ? "i32::MIN" : seed.ToString(CultureInfo.InvariantCulture))},");
+    Console.WriteLine($"            {Ints(next)},");
+    Console.WriteLine($"            {Ints(nextMax)},");
+    Console.WriteLine($"            {Ints(small)},");
+    Console.WriteLine($"            {Ints(large)},");
+    Console.WriteLine($"            {Ints(wide)},");
+    Console.WriteLine($"            [{string.Join(", ", doubles)}],");
+    Console.WriteLine($"            {Ints(bytes.Select(b => (int)b))},");
+    Console.WriteLine("        ),");
+}
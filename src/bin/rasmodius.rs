@@ -0,0 +1,419 @@
+//! Native multithreaded seed search.
+//!
+//! Takes the same filter JSON and version as `search_range`, splits the seed
+//! range into chunks shared out to one worker per CPU, and streams matches to
+//! stdout as NDJSON (`{"seed":123}` per line) in ascending seed order.
+//! Progress goes to stderr. With `--checkpoint`, progress is also saved to a
+//! file every second, and a later run with the same arguments resumes it.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, BufWriter, Read, Write};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rasmodius::search::{validate_filter_json, Checkpoint, FilterNode, FilterPlan};
+use rasmodius::{GameVersion, PlayerContext};
+
+const USAGE: &str = "\
+Usage: rasmodius [OPTIONS] <FILTER>
+
+Search game IDs with a filter and print matches as NDJSON.
+
+Arguments:
+  <FILTER>                 Filter JSON file, or - to read it from stdin
+
+Options:
+  --version <VERSION>      Game version (default 1.6)
+  --start <SEED>           First seed to check (default 0)
+  --end <SEED>             Last seed to check (default 2147483647)
+  --max-results <N>        Stop after N matches (default: no limit)
+  --threads <N>            Worker threads (default: one per CPU)
+  --context <JSON>         PlayerContext object (default: defaults)
+  --chunk-size <N>         Seeds per work unit (default 1000000)
+  --checkpoint <FILE>      Save progress to FILE, resuming from it if it exists;
+                           only new matches are printed
+  --quiet                  Don't report progress on stderr
+  -h, --help               Print this help";
+
+/// Seeds per work unit. A multiple of 100 keeps `seed / 100` runs whole.
+const DEFAULT_CHUNK_SIZE: i64 = 1_000_000;
+
+/// How often progress is reported and the checkpoint saved.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How many chunks per worker can be taken past the next one to print.
+/// Finished chunks wait in memory until the chunks before them print, so
+/// this bounds how many are held back behind a slow chunk.
+const CHUNKS_AHEAD_PER_THREAD: i64 = 4;
+
+struct Options {
+    filter_path: String,
+    version: GameVersion,
+    start: i32,
+    end: i32,
+    max_results: Option<u64>,
+    threads: usize,
+    ctx: PlayerContext,
+    chunk_size: i64,
+    checkpoint_path: Option<String>,
+    progress: bool,
+}
+
+/// What the workers share.
+struct Search<'a> {
+    filter: &'a FilterNode,
+    options: &'a Options,
+    /// Work units, in ascending seed order
+    chunks: Vec<(i32, i32)>,
+    next_chunk: AtomicI64,
+    checked: AtomicU64,
+    stop: AtomicBool,
+    /// Chunks printed so far, so workers can wait rather than run too far ahead
+    printed: Mutex<i64>,
+    printed_changed: Condvar,
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let (filter_json, filter) = match read_filter(&options) {
+        Ok(filter) => filter,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            return ExitCode::from(2);
+        }
+    };
+    let checkpoint = match load_checkpoint(&options, &filter_json) {
+        Ok(checkpoint) => checkpoint,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&options, &filter, checkpoint) {
+        Ok(()) => ExitCode::SUCCESS,
+        // The reader went away (e.g. `| head`); that's a normal way to stop
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Parse command-line arguments. Ok(None) means help was requested.
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        filter_path: String::new(),
+        version: GameVersion::V1_6,
+        start: 0,
+        end: i32::MAX,
+        max_results: None,
+        threads: thread::available_parallelism().map_or(1, |n| n.get()),
+        ctx: PlayerContext::default(),
+        chunk_size: DEFAULT_CHUNK_SIZE,
+        checkpoint_path: None,
+        progress: true,
+    };
+    let mut filter_path = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{} needs a value", name));
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--quiet" => options.progress = false,
+            "--version" => {
+                options.version = GameVersion::try_parse(&value("--version")?).map_err(|e| e.to_string())?
+            }
+            "--start" => options.start = parse_number("--start", &value("--start")?)?,
+            "--end" => options.end = parse_number("--end", &value("--end")?)?,
+            "--max-results" => options.max_results = Some(parse_number("--max-results", &value("--max-results")?)?),
+            "--threads" => options.threads = parse_number("--threads", &value("--threads")?)?,
+            "--chunk-size" => options.chunk_size = parse_number("--chunk-size", &value("--chunk-size")?)?,
+            "--checkpoint" => options.checkpoint_path = Some(value("--checkpoint")?),
+            "--context" => {
+                options.ctx = serde_json::from_str(&value("--context")?)
+                    .map_err(|e| format!("Context parse error: {}", e))?
+            }
+            flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+            path if filter_path.is_none() => filter_path = Some(path.to_string()),
+            extra => return Err(format!("unexpected argument {}", extra)),
+        }
+    }
+
+    options.filter_path = filter_path.ok_or("missing <FILTER>")?;
+    if options.threads == 0 {
+        return Err("--threads must be at least 1".to_string());
+    }
+    if options.chunk_size <= 0 {
+        return Err("--chunk-size must be at least 1".to_string());
+    }
+    Ok(Some(options))
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid {} value: {}", name, value))
+}
+
+/// Read and validate the filter, returning its JSON (for the checkpoint hash)
+/// and the parsed tree.
+fn read_filter(options: &Options) -> Result<(String, FilterNode), String> {
+    let path = options.filter_path.as_str();
+    let json = if path == "-" {
+        let mut json = String::new();
+        io::stdin()
+            .read_to_string(&mut json)
+            .map_err(|e| format!("reading filter from stdin: {}", e))?;
+        json
+    } else {
+        fs::read_to_string(path).map_err(|e| format!("reading {}: {}", path, e))?
+    };
+    let filter = validate_filter_json(&json, options.version, &options.ctx).map_err(|errors| {
+        let errors: Vec<String> = errors.iter().map(|e| format!("\n  {}", e)).collect();
+        format!("invalid filter:{}", errors.concat())
+    })?;
+    Ok((json, filter))
+}
+
+/// Resume the checkpoint file if there is one, otherwise start a new search.
+fn load_checkpoint(options: &Options, filter_json: &str) -> Result<Checkpoint, String> {
+    let existing = match &options.checkpoint_path {
+        Some(path) => match fs::read_to_string(path) {
+            Ok(json) => Some(json),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(format!("reading {}: {}", path, e)),
+        },
+        None => None,
+    };
+
+    let checkpoint = match existing {
+        Some(json) => Checkpoint::resume(&json, filter_json, options.version, &options.ctx),
+        None => Checkpoint::new(filter_json, options.version, &options.ctx, options.start, options.end),
+    }
+    .map_err(|e| e.to_string())?;
+
+    if (checkpoint.start_seed, checkpoint.end_seed) != (options.start, options.end) {
+        return Err(format!(
+            "checkpoint covers seeds {}..={}, not {}..={}",
+            checkpoint.start_seed, checkpoint.end_seed, options.start, options.end
+        ));
+    }
+    Ok(checkpoint)
+}
+
+/// Write the checkpoint next to its file and move it into place, so an
+/// interrupted save never leaves a truncated checkpoint behind.
+fn save_checkpoint(path: &str, checkpoint: &Checkpoint) -> io::Result<()> {
+    let temp = format!("{}.tmp", path);
+    fs::write(&temp, checkpoint.to_json())?;
+    fs::rename(&temp, path)
+}
+
+fn run(options: &Options, filter: &FilterNode, checkpoint: Checkpoint) -> io::Result<()> {
+    let chunks: Vec<(i32, i32)> = checkpoint
+        .remaining()
+        .into_iter()
+        .flat_map(|(start, end)| {
+            (start as i64..=end as i64)
+                .step_by(options.chunk_size as usize)
+                .map(move |chunk_start| {
+                    (chunk_start as i32, (chunk_start + options.chunk_size - 1).min(end as i64) as i32)
+                })
+        })
+        .collect();
+    let total = (options.end as i64 - options.start as i64 + 1).max(0) as u64;
+    // Matches from earlier runs count towards the limit
+    let results_left = options
+        .max_results
+        .map(|max| max.saturating_sub(checkpoint.matches.len() as u64));
+
+    let search = Search {
+        filter,
+        options,
+        chunks,
+        next_chunk: AtomicI64::new(0),
+        checked: AtomicU64::new(checkpoint.seeds_checked()),
+        stop: AtomicBool::new(results_left == Some(0)),
+        printed: Mutex::new(0),
+        printed_changed: Condvar::new(),
+    };
+    let started = Instant::now();
+
+    thread::scope(|scope| {
+        let (tx, rx) = mpsc::channel::<(i64, Vec<i32>)>();
+        for _ in 0..options.threads {
+            let tx = tx.clone();
+            let search = &search;
+            scope.spawn(move || search.work(tx, results_left));
+        }
+        drop(tx);
+
+        let result = write_in_order(&search, rx, checkpoint, results_left, total, started);
+        // Stop the workers whether we finished or failed to write
+        search.halt();
+        result
+    })
+}
+
+impl Search<'_> {
+    /// Take chunks until the range is done or the search is stopped.
+    fn work(&self, tx: mpsc::Sender<(i64, Vec<i32>)>, results_left: Option<u64>) {
+        let options = self.options;
+        let mut plan = FilterPlan::compile(self.filter, options.version);
+
+        while !self.stop.load(Ordering::Relaxed) {
+            let index = self.next_chunk.fetch_add(1, Ordering::Relaxed);
+            let Some(&(chunk_start, chunk_end)) = self.chunks.get(index as usize) else {
+                break;
+            };
+            if !self.wait_for_turn(index) {
+                break;
+            }
+
+            let mut seeds = Vec::new();
+            plan.scan(chunk_start, chunk_end, &options.ctx, |seed| {
+                seeds.push(seed);
+                // Only this chunk's first results_left can ever be printed
+                results_left.is_none_or(|max| (seeds.len() as u64) < max)
+                    && !self.stop.load(Ordering::Relaxed)
+            });
+            self.checked
+                .fetch_add((chunk_end as i64 - chunk_start as i64 + 1) as u64, Ordering::Relaxed);
+
+            if tx.send((index, seeds)).is_err() {
+                break;
+            }
+        }
+    }
+
+    /// Block until chunk `index` is close enough to the printed chunks.
+    /// Returns false if the search was stopped meanwhile.
+    fn wait_for_turn(&self, index: i64) -> bool {
+        let ahead = self.options.threads as i64 * CHUNKS_AHEAD_PER_THREAD;
+        let printed = self.printed.lock().unwrap();
+        let _printed = self
+            .printed_changed
+            .wait_while(printed, |printed| {
+                index >= *printed + ahead && !self.stop.load(Ordering::Relaxed)
+            })
+            .unwrap();
+        !self.stop.load(Ordering::Relaxed)
+    }
+
+    /// Record that the chunks before `next_index` are printed.
+    fn mark_printed(&self, next_index: i64) {
+        *self.printed.lock().unwrap() = next_index;
+        self.printed_changed.notify_all();
+    }
+
+    /// Stop the workers, waking any waiting for their turn.
+    fn halt(&self) {
+        let _printed = self.printed.lock().unwrap();
+        self.stop.store(true, Ordering::Relaxed);
+        self.printed_changed.notify_all();
+    }
+}
+
+/// Print chunks as they complete, holding back any that finish ahead of an
+/// earlier chunk, so output is in ascending seed order. Printed chunks are
+/// recorded in the checkpoint.
+fn write_in_order(
+    search: &Search,
+    rx: mpsc::Receiver<(i64, Vec<i32>)>,
+    mut checkpoint: Checkpoint,
+    results_left: Option<u64>,
+    total: u64,
+    started: Instant,
+) -> io::Result<()> {
+    let options = search.options;
+    let mut out = BufWriter::new(io::stdout().lock());
+    let mut pending: BTreeMap<i64, Vec<i32>> = BTreeMap::new();
+    let mut next_index = 0;
+    let mut written = 0u64;
+    let mut last_report = Instant::now();
+    let limit_reached = |written: u64| results_left.is_some_and(|max| written >= max);
+
+    'receive: loop {
+        match rx.recv_timeout(PROGRESS_INTERVAL) {
+            Ok((index, seeds)) => {
+                pending.insert(index, seeds);
+                while let Some(seeds) = pending.remove(&next_index) {
+                    let (chunk_start, mut chunk_end) = search.chunks[next_index as usize];
+                    next_index += 1;
+                    search.mark_printed(next_index);
+                    let mut printed = 0;
+                    for &seed in &seeds {
+                        if limit_reached(written) {
+                            break;
+                        }
+                        writeln!(out, "{{\"seed\":{}}}", seed)?;
+                        written += 1;
+                        printed += 1;
+                    }
+                    out.flush()?;
+                    if limit_reached(written) {
+                        // The worker may have stopped at its last match
+                        chunk_end = seeds[..printed].last().copied().unwrap_or(chunk_end);
+                    }
+                    checkpoint.record(chunk_start, chunk_end, &seeds[..printed]);
+                    if limit_reached(written) {
+                        break 'receive;
+                    }
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        if last_report.elapsed() >= PROGRESS_INTERVAL {
+            if let Some(path) = &options.checkpoint_path {
+                save_checkpoint(path, &checkpoint)?;
+            }
+            if options.progress {
+                report(search.checked.load(Ordering::Relaxed), total, written, started);
+            }
+            last_report = Instant::now();
+        }
+    }
+
+    if let Some(path) = &options.checkpoint_path {
+        save_checkpoint(path, &checkpoint)?;
+    }
+    if options.progress {
+        report(search.checked.load(Ordering::Relaxed).min(total), total, written, started);
+        eprintln!();
+    }
+    Ok(())
+}
+
+fn report(checked: u64, total: u64, matches: u64, started: Instant) {
+    let elapsed = started.elapsed().as_secs_f64();
+    let percent = if total == 0 { 100.0 } else { checked as f64 * 100.0 / total as f64 };
+    eprint!(
+        "\r{}/{} seeds ({:.1}%), {} matches, {:.0} seeds/s, {:.0}s",
+        checked,
+        total,
+        percent,
+        matches,
+        checked as f64 / elapsed.max(1e-9),
+        elapsed
+    );
+}
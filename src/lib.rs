@@ -7,9 +7,19 @@
 //!    - `predict_geodes()` - Geode sequence prediction
 //!    - `find_monster_floors()`, `find_dark_floors()`, `find_mushroom_floors()` - Mine floor queries
 //!    - `find_item_in_cart()` - Search for item across cart days
+//!    - `plan_steps()` - Step counts that manipulate luck or the dish
 //!
 //! 2. **Search API** - Find seeds matching filter criteria
 //!    - `search_range()` - Evaluate filters across seed range with callbacks
+//!    - `search_range_into()` - Same, writing matches into a caller's `Int32Array`
+//!    - `search_range_checkpointed()` - Resumable search (`new_checkpoint()`, `merge_checkpoints()`)
+//!    - `search_range_into_checkpointed()` - Same, writing matches into a caller's `Int32Array`
+//!    - `validate_filter()` - Filter mistakes with JSON-pointer paths
+//!    - `upgrade_filter()` - Migrate saved filters to the current schema (`filter_json_schema()`)
+//!    - `estimate_filter()` - Sampled match rate and time to first match
+//!    - `explain_filter()` - Trace of why a seed passes or fails a filter
+//!    - `search_top_k()` - Highest-scoring seeds for a weighted score expression
+//!    - `recover_seeds()` - Find game IDs consistent with in-game observations
 //!
 //! Internal mechanics are in the `mechanics` module and can be unit tested directly.
 
@@ -20,9 +30,13 @@ pub mod types;
 mod version;
 
 use wasm_bindgen::prelude::*;
-pub use search::search_range;
+pub use search::{
+    estimate_filter, explain_filter, filter_json_schema, merge_checkpoints, new_checkpoint, recover_seeds,
+    search_range, search_range_checkpointed, search_range_into, search_range_into_checkpointed, search_top_k,
+    upgrade_filter, validate_filter,
+};
 pub use types::*;
-pub use version::GameVersion;
+pub use version::{GameVersion, VersionError};
 
 // Re-export RNG for internal use and testing
 pub use rng::{CSRandom, CSRandomLite};
@@ -46,27 +60,32 @@ pub fn init() {
 /// - weather: Tomorrow's weather
 /// - night_event: Night event (if any)
 /// - cart: Traveling cart stock (only on Fri/Sun)
+///
+/// Every `predict_*` export takes an optional `PlayerContext` as its last
+/// argument; pass `undefined` for the defaults.
 #[wasm_bindgen]
-pub fn predict_day(seed: i32, day: i32, version: &str) -> JsValue {
-    let v = GameVersion::parse(version);
-
-    let (dish_id, dish_qty) = mechanics::dish_of_the_day(seed, day, 0);
-    let luck = mechanics::daily_luck(seed, day, 0, false);
-    let weather_code = mechanics::weather_tomorrow(seed, day, 0, 0, false, v).to_code();
-
-    let night_event = match mechanics::night_event(seed, day, v) {
-        None => NightEventType::None,
-        Some(mechanics::NightEvent::Fairy) => NightEventType::Fairy,
-        Some(mechanics::NightEvent::Witch) => NightEventType::Witch,
-        Some(mechanics::NightEvent::Meteor) => NightEventType::Meteor,
-        Some(mechanics::NightEvent::Ufo) => NightEventType::Ufo,
-        Some(mechanics::NightEvent::Owl) => NightEventType::Owl,
-        Some(mechanics::NightEvent::Earthquake) => NightEventType::Earthquake,
-    };
+pub fn predict_day(seed: i32, day: i32, version: &str, context: JsValue) -> Result<JsValue, JsValue> {
+    let v = search::parse_version(version)?;
+    let ctx = PlayerContext::from_js(context)?;
+
+    let (dish_id, dish_qty) = mechanics::dish_of_the_day(seed, day, ctx.steps);
+    let luck = mechanics::daily_luck(seed, day, ctx.steps, ctx.friendship_rolls());
+    // Chain through today's forecast so a windy day feeds into tomorrow's roll
+    let weather_code =
+        mechanics::forecast_weather(seed, day + 1, day + 1, ctx.steps, ctx.friendship_rolls(), ctx.legacy_random, v)[0]
+            .to_code();
+
+    let night_event = NightEventType::from_event(mechanics::night_event_for_farm(
+        seed,
+        day,
+        v,
+        ctx.greenhouse_repaired,
+        ctx.legacy_random,
+    ));
 
     let cart = if is_cart_day(day) {
         Some(
-            mechanics::get_cart_for_day(seed, day, v)
+            mechanics::get_cart_for_day(seed, day, ctx.legacy_random, v)
                 .into_iter()
                 .map(|item| CartItem {
                     id: item.item_id,
@@ -90,7 +109,7 @@ pub fn predict_day(seed: i32, day: i32, version: &str) -> JsValue {
         cart,
     };
 
-    serde_wasm_bindgen::to_value(&prediction).unwrap()
+    Ok(serde_wasm_bindgen::to_value(&prediction).unwrap())
 }
 
 // ============================================================================
@@ -108,8 +127,10 @@ pub fn predict_geodes(
     count: i32,
     geode_type: &str,
     version: &str,
-) -> JsValue {
-    let v = GameVersion::parse(version);
+    context: JsValue,
+) -> Result<JsValue, JsValue> {
+    let v = search::parse_version(version)?;
+    let ctx = PlayerContext::from_js(context)?;
     let gt = types::GeodeType::parse(geode_type);
 
     let internal_gt = match gt {
@@ -121,16 +142,23 @@ pub fn predict_geodes(
         types::GeodeType::GoldenCoconut => mechanics::GeodeType::GoldenCoconut,
     };
 
-    let results: Vec<GeodeResult> =
-        mechanics::predict_geode_sequence(seed, start, count, internal_gt, 120, v)
-            .into_iter()
-            .map(|r| GeodeResult {
-                item_id: r.item_id,
-                quantity: r.quantity,
-            })
-            .collect();
-
-    serde_wasm_bindgen::to_value(&results).unwrap()
+    let results: Vec<GeodeResult> = mechanics::predict_geode_sequence(
+        seed,
+        start,
+        count,
+        internal_gt,
+        ctx.deepest_mine_level,
+        ctx.has_coconut_hat,
+        v,
+    )
+    .into_iter()
+    .map(|r| GeodeResult {
+        item_id: r.item_id,
+        quantity: r.quantity,
+    })
+    .collect();
+
+    Ok(serde_wasm_bindgen::to_value(&results).unwrap())
 }
 
 // ============================================================================
@@ -145,9 +173,11 @@ pub fn find_monster_floors(
     start_floor: i32,
     end_floor: i32,
     version: &str,
-) -> Vec<i32> {
-    let v = GameVersion::parse(version);
-    mechanics::find_monster_floors(seed, days_played, start_floor, end_floor, v)
+    context: JsValue,
+) -> Result<Vec<i32>, JsValue> {
+    let v = search::parse_version(version)?;
+    PlayerContext::from_js(context)?;
+    Ok(mechanics::find_monster_floors(seed, days_played, start_floor, end_floor, v))
 }
 
 /// Find all unusually dark floors in a range.
@@ -157,8 +187,13 @@ pub fn find_dark_floors(
     days_played: i32,
     start_floor: i32,
     end_floor: i32,
-) -> Vec<i32> {
-    mechanics::find_dark_floors(seed, days_played, start_floor, end_floor)
+    version: &str,
+    context: JsValue,
+) -> Result<Vec<i32>, JsValue> {
+    // Dark floors roll the same way in every version
+    search::parse_version(version)?;
+    PlayerContext::from_js(context)?;
+    Ok(mechanics::find_dark_floors(seed, days_played, start_floor, end_floor))
 }
 
 /// Find all mushroom floors in a range (only valid for floors 81+).
@@ -169,9 +204,11 @@ pub fn find_mushroom_floors(
     start_floor: i32,
     end_floor: i32,
     version: &str,
-) -> Vec<i32> {
-    let v = GameVersion::parse(version);
-    mechanics::find_mushroom_floors(seed, days_played, start_floor, end_floor, v)
+    context: JsValue,
+) -> Result<Vec<i32>, JsValue> {
+    let v = search::parse_version(version)?;
+    PlayerContext::from_js(context)?;
+    Ok(mechanics::find_mushroom_floors(seed, days_played, start_floor, end_floor, v))
 }
 
 // ============================================================================
@@ -187,12 +224,14 @@ pub fn find_item_in_cart(
     target_item: i32,
     max_days: i32,
     version: &str,
-) -> Vec<i32> {
-    let v = GameVersion::parse(version);
-    match mechanics::find_item_in_cart(seed, target_item, max_days, v) {
+    context: JsValue,
+) -> Result<Vec<i32>, JsValue> {
+    let v = search::parse_version(version)?;
+    let ctx = PlayerContext::from_js(context)?;
+    Ok(match mechanics::find_item_in_cart(seed, target_item, max_days, ctx.legacy_random, v) {
         Some((day, price, qty)) => vec![day, price, qty],
         None => vec![],
-    }
+    })
 }
 
 // ============================================================================
@@ -202,33 +241,47 @@ pub fn find_item_in_cart(
 /// Predict daily luck for a range of days.
 /// Returns array of {day, luck} objects.
 #[wasm_bindgen]
-pub fn predict_luck_range(seed: i32, start_day: i32, end_day: i32) -> JsValue {
+pub fn predict_luck_range(
+    seed: i32,
+    start_day: i32,
+    end_day: i32,
+    context: JsValue,
+) -> Result<JsValue, JsValue> {
+    let ctx = PlayerContext::from_js(context)?;
     let results: Vec<DayLuck> = (start_day..=end_day)
         .map(|day| DayLuck {
             day,
-            luck: mechanics::daily_luck(seed, day, 0, false),
+            luck: mechanics::daily_luck(seed, day, ctx.steps, ctx.friendship_rolls()),
         })
         .collect();
 
-    serde_wasm_bindgen::to_value(&results).unwrap()
+    Ok(serde_wasm_bindgen::to_value(&results).unwrap())
 }
 
 /// Predict weather for a range of days.
-/// Returns array of {day, weather} objects.
+/// Returns array of {day, weather} objects with the weather on each day,
+/// chained day to day from the start of the season.
 #[wasm_bindgen]
-pub fn predict_weather_range(seed: i32, start_day: i32, end_day: i32, version: &str) -> JsValue {
-    let v = GameVersion::parse(version);
-    let results: Vec<DayWeather> = (start_day..=end_day)
-        .map(|day| {
-            let code = mechanics::weather_tomorrow(seed, day, 0, 0, false, v).to_code();
-            DayWeather {
-                day,
-                weather: WeatherType::from_code(code),
-            }
+pub fn predict_weather_range(
+    seed: i32,
+    start_day: i32,
+    end_day: i32,
+    version: &str,
+    context: JsValue,
+) -> Result<JsValue, JsValue> {
+    let v = search::parse_version(version)?;
+    let ctx = PlayerContext::from_js(context)?;
+    let results: Vec<DayWeather> =
+        mechanics::forecast_weather(seed, start_day, end_day, ctx.steps, ctx.friendship_rolls(), ctx.legacy_random, v)
+            .into_iter()
+        .zip(start_day..=end_day)
+        .map(|(weather, day)| DayWeather {
+            day,
+            weather: WeatherType::from_code(weather.to_code()),
         })
         .collect();
 
-    serde_wasm_bindgen::to_value(&results).unwrap()
+    Ok(serde_wasm_bindgen::to_value(&results).unwrap())
 }
 
 /// Predict night events for a range of days.
@@ -239,33 +292,39 @@ pub fn predict_night_events_range(
     start_day: i32,
     end_day: i32,
     version: &str,
-) -> JsValue {
-    let v = GameVersion::parse(version);
+    context: JsValue,
+) -> Result<JsValue, JsValue> {
+    let v = search::parse_version(version)?;
+    let ctx = PlayerContext::from_js(context)?;
     let results: Vec<DayNightEvent> = (start_day..=end_day)
         .map(|day| {
-            let event = match mechanics::night_event(seed, day, v) {
-                None => NightEventType::None,
-                Some(mechanics::NightEvent::Fairy) => NightEventType::Fairy,
-                Some(mechanics::NightEvent::Witch) => NightEventType::Witch,
-                Some(mechanics::NightEvent::Meteor) => NightEventType::Meteor,
-                Some(mechanics::NightEvent::Ufo) => NightEventType::Ufo,
-                Some(mechanics::NightEvent::Owl) => NightEventType::Owl,
-                Some(mechanics::NightEvent::Earthquake) => NightEventType::Earthquake,
-            };
+            let event = NightEventType::from_event(mechanics::night_event_for_farm(
+                seed,
+                day,
+                v,
+                ctx.greenhouse_repaired,
+                ctx.legacy_random,
+            ));
             DayNightEvent { day, event }
         })
         .collect();
 
-    serde_wasm_bindgen::to_value(&results).unwrap()
+    Ok(serde_wasm_bindgen::to_value(&results).unwrap())
 }
 
 /// Predict dish of the day for a range of days.
 /// Returns array of {day, dish} objects.
 #[wasm_bindgen]
-pub fn predict_dish_range(seed: i32, start_day: i32, end_day: i32) -> JsValue {
+pub fn predict_dish_range(
+    seed: i32,
+    start_day: i32,
+    end_day: i32,
+    context: JsValue,
+) -> Result<JsValue, JsValue> {
+    let ctx = PlayerContext::from_js(context)?;
     let results: Vec<DayDish> = (start_day..=end_day)
         .map(|day| {
-            let (id, quantity) = mechanics::dish_of_the_day(seed, day, 0);
+            let (id, quantity) = mechanics::dish_of_the_day(seed, day, ctx.steps);
             DayDish {
                 day,
                 dish: DishOfDay { id, quantity },
@@ -273,18 +332,25 @@ pub fn predict_dish_range(seed: i32, start_day: i32, end_day: i32) -> JsValue {
         })
         .collect();
 
-    serde_wasm_bindgen::to_value(&results).unwrap()
+    Ok(serde_wasm_bindgen::to_value(&results).unwrap())
 }
 
 /// Predict cart contents for a range of days.
 /// Returns array of {day, items} objects. Only cart days (Fri/Sun) are included.
 #[wasm_bindgen]
-pub fn predict_cart_range(seed: i32, start_day: i32, end_day: i32, version: &str) -> JsValue {
-    let v = GameVersion::parse(version);
+pub fn predict_cart_range(
+    seed: i32,
+    start_day: i32,
+    end_day: i32,
+    version: &str,
+    context: JsValue,
+) -> Result<JsValue, JsValue> {
+    let v = search::parse_version(version)?;
+    let ctx = PlayerContext::from_js(context)?;
     let results: Vec<DayCart> = (start_day..=end_day)
         .filter(|&day| is_cart_day(day))
         .map(|day| {
-            let items = mechanics::get_cart_for_day(seed, day, v)
+            let items = mechanics::get_cart_for_day(seed, day, ctx.legacy_random, v)
                 .into_iter()
                 .map(|item| CartItem {
                     id: item.item_id,
@@ -296,7 +362,7 @@ pub fn predict_cart_range(seed: i32, start_day: i32, end_day: i32, version: &str
         })
         .collect();
 
-    serde_wasm_bindgen::to_value(&results).unwrap()
+    Ok(serde_wasm_bindgen::to_value(&results).unwrap())
 }
 
 /// Predict mine floors for a range.
@@ -308,8 +374,10 @@ pub fn predict_mine_floors(
     start_floor: i32,
     end_floor: i32,
     version: &str,
-) -> JsValue {
-    let v = GameVersion::parse(version);
+    context: JsValue,
+) -> Result<JsValue, JsValue> {
+    let v = search::parse_version(version)?;
+    PlayerContext::from_js(context)?;
 
     // Get all special floors in range
     let monster_floors: std::collections::HashSet<i32> =
@@ -335,6 +403,62 @@ pub fn predict_mine_floors(
         })
         .collect();
 
-    serde_wasm_bindgen::to_value(&results).unwrap()
+    Ok(serde_wasm_bindgen::to_value(&results).unwrap())
 }
 
+
+// ============================================================================
+// Explore API - Step Planner
+// ============================================================================
+
+/// Find total step counts in [min_steps, max_steps] that hit a luck or dish target on `day`.
+///
+/// target: `{"type": "luck", "min_luck", "max_luck"}` or `{"type": "dish", "dish_id", "min_quantity"}`
+/// Returns array of StepPlanResult objects. `context.steps` is ignored; the
+/// planner varies it. Windows wider than `MAX_STEP_WINDOW` (100,000) step
+/// counts are an error.
+#[wasm_bindgen]
+pub fn plan_steps(
+    seed: i32,
+    day: i32,
+    target: JsValue,
+    min_steps: i32,
+    max_steps: i32,
+    version: &str,
+    context: JsValue,
+) -> Result<JsValue, JsValue> {
+    let v = search::parse_version(version)?;
+    let ctx = PlayerContext::from_js(context)?;
+    let target: StepTargetSpec = serde_wasm_bindgen::from_value(target)
+        .map_err(|e| JsValue::from_str(&format!("Target parse error: {}", e)))?;
+    let target = match target {
+        StepTargetSpec::Luck { min_luck, max_luck } => mechanics::StepTarget::Luck {
+            min: min_luck,
+            max: max_luck,
+        },
+        StepTargetSpec::Dish {
+            dish_id,
+            min_quantity,
+        } => mechanics::StepTarget::Dish {
+            id: dish_id,
+            min_quantity,
+        },
+    };
+
+    let results: Vec<StepPlanResult> =
+        mechanics::plan_steps(seed, day, target, min_steps, max_steps, ctx.friendship_rolls(), ctx.legacy_random, v)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?
+            .into_iter()
+            .map(|plan| StepPlanResult {
+                steps: plan.steps,
+                luck: plan.luck,
+                dish: DishOfDay {
+                    id: plan.dish_id,
+                    quantity: plan.dish_quantity,
+                },
+                weather: WeatherType::from_code(plan.weather.to_code()),
+            })
+            .collect();
+
+    Ok(serde_wasm_bindgen::to_value(&results).unwrap())
+}
@@ -1,5 +1,14 @@
 use crate::rng::CSRandomLite;
 
+/// Seed of the day-start RNG: `seed/100 + (daysPlayed - 1)*10 + 1 + steps`.
+/// The game adds in unchecked C# ints, so large step counts wrap.
+pub(crate) fn day_start_seed(seed: i32, days_played: i32, steps: i32) -> i32 {
+    (seed / 100)
+        .wrapping_add((days_played - 1).wrapping_mul(10))
+        .wrapping_add(1)
+        .wrapping_add(steps)
+}
+
 /// Returns (dish_id, quantity) for the Saloon's dish of the day.
 /// This is called internally to advance the RNG state before daily luck calculation.
 ///
@@ -7,8 +16,7 @@ use crate::rng::CSRandomLite;
 /// so we use (daysPlayed - 1) for the seed calculation.
 pub fn dish_of_the_day(seed: i32, days_played: i32, steps: i32) -> (i32, i32) {
     let local_days_played = days_played - 1;
-    let rng_seed = (seed / 100) + local_days_played * 10 + 1 + steps;
-    let mut rng = CSRandomLite::new(rng_seed);
+    let mut rng = CSRandomLite::new(day_start_seed(seed, days_played, steps));
     dish_of_the_day_with_rng(&mut rng, local_days_played)
 }
 
@@ -59,8 +67,7 @@ fn dish_of_the_day_with_rng(rng: &mut CSRandomLite, local_days_played: i32) -> (
 /// Daily luck value in range [-0.1, 0.1]
 pub fn daily_luck(seed: i32, days_played: i32, steps: i32, has_friends: bool) -> f64 {
     let local_days_played = days_played - 1;
-    let rng_seed = (seed / 100) + local_days_played * 10 + 1 + steps;
-    let mut rng = CSRandomLite::new(rng_seed);
+    let mut rng = CSRandomLite::new(day_start_seed(seed, days_played, steps));
 
     // Dish of the day is calculated first, advancing the RNG
     dish_of_the_day_with_rng(&mut rng, local_days_played);
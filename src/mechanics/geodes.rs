@@ -52,11 +52,15 @@ pub struct GeodeResult {
 }
 
 /// Predict what item will come from a geode.
+///
+/// `has_coconut_hat` only matters for golden coconuts: once the hat is owned
+/// its 5% slot falls through to the regular drop table.
 pub fn next_geode_item(
     seed: i32,
     geodes_cracked: i32,
     geode_type: GeodeType,
     deepest_mine_level: i32,
+    has_coconut_hat: bool,
     version: GameVersion,
 ) -> GeodeResult {
     let rng_seed = geodes_cracked + (seed / 2);
@@ -81,7 +85,7 @@ pub fn next_geode_item(
 
     // Golden Coconut special handling
     if geode_type == GeodeType::GoldenCoconut {
-        return get_coconut_result(&mut rng, false);
+        return get_coconut_result(&mut rng, has_coconut_hat);
     }
 
     // Artifact Trove goes straight to mineral list
@@ -132,7 +136,7 @@ pub fn next_geode_item(
         GeodeType::MagmaGeode => &MAGMA_ITEMS[..],
         GeodeType::OmniGeode => &OMNI_ITEMS[..],
         GeodeType::ArtifactTrove => &TROVE_ITEMS[..],
-        GeodeType::GoldenCoconut => return get_coconut_result(&mut rng, false),
+        GeodeType::GoldenCoconut => return get_coconut_result(&mut rng, has_coconut_hat),
     };
 
     // 1.6 checks prismatic shard differently
@@ -246,6 +250,7 @@ pub fn predict_geode_sequence(
     count: i32,
     geode_type: GeodeType,
     deepest_mine_level: i32,
+    has_coconut_hat: bool,
     version: GameVersion,
 ) -> Vec<GeodeResult> {
     (0..count)
@@ -255,6 +260,7 @@ pub fn predict_geode_sequence(
                 start_geode + i,
                 geode_type,
                 deepest_mine_level,
+                has_coconut_hat,
                 version,
             )
         })
@@ -267,14 +273,14 @@ mod tests {
 
     #[test]
     fn test_geode_deterministic() {
-        let result1 = next_geode_item(12345, 1, GeodeType::Geode, 50, GameVersion::V1_5);
-        let result2 = next_geode_item(12345, 1, GeodeType::Geode, 50, GameVersion::V1_5);
+        let result1 = next_geode_item(12345, 1, GeodeType::Geode, 50, false, GameVersion::V1_5);
+        let result2 = next_geode_item(12345, 1, GeodeType::Geode, 50, false, GameVersion::V1_5);
         assert_eq!(result1, result2);
     }
 
     #[test]
     fn test_geode_sequence_unique_items() {
-        let results = predict_geode_sequence(12345, 1, 100, GeodeType::OmniGeode, 120, GameVersion::V1_5);
+        let results = predict_geode_sequence(12345, 1, 100, GeodeType::OmniGeode, 120, false, GameVersion::V1_5);
         let unique: std::collections::HashSet<_> = results.iter().map(|r| r.item_id).collect();
         assert!(unique.len() > 5, "Should have variety in 100 geodes");
     }
@@ -282,7 +288,7 @@ mod tests {
     #[test]
     fn test_artifact_trove_items() {
         for i in 1..=50 {
-            let result = next_geode_item(12345, i, GeodeType::ArtifactTrove, 0, GameVersion::V1_5);
+            let result = next_geode_item(12345, i, GeodeType::ArtifactTrove, 0, false, GameVersion::V1_5);
             assert!(
                 TROVE_ITEMS.contains(&result.item_id),
                 "Trove gave invalid item {}",
@@ -296,8 +302,8 @@ mod tests {
         // 1.5 and 1.6 should give different results due to reversed geode check
         let mut found_diff = false;
         for geode_num in 1..100 {
-            let v15 = next_geode_item(12345, geode_num, GeodeType::OmniGeode, 120, GameVersion::V1_5);
-            let v16 = next_geode_item(12345, geode_num, GeodeType::OmniGeode, 120, GameVersion::V1_6);
+            let v15 = next_geode_item(12345, geode_num, GeodeType::OmniGeode, 120, false, GameVersion::V1_5);
+            let v16 = next_geode_item(12345, geode_num, GeodeType::OmniGeode, 120, false, GameVersion::V1_6);
             if v15 != v16 {
                 found_diff = true;
                 break;
@@ -305,4 +311,21 @@ mod tests {
         }
         assert!(found_diff, "1.5 and 1.6 should give different geode results");
     }
+
+    #[test]
+    fn test_coconut_hat_only_drops_once() {
+        // Every hat drop without the hat becomes a regular coconut item with it
+        let mut hats = 0;
+        for i in 1..=200 {
+            let without = next_geode_item(12345, i, GeodeType::GoldenCoconut, 120, false, GameVersion::V1_5);
+            let with = next_geode_item(12345, i, GeodeType::GoldenCoconut, 120, true, GameVersion::V1_5);
+            assert_ne!(with.item_id, -1);
+            if without.item_id == -1 {
+                hats += 1;
+            } else {
+                assert_eq!(without, with);
+            }
+        }
+        assert!(hats > 0, "Expected at least one hat in 200 coconuts");
+    }
 }
@@ -3,6 +3,7 @@ pub mod daily_luck;
 pub mod geodes;
 pub mod mine;
 pub mod night_events;
+pub mod step_planner;
 pub mod traveling_cart;
 pub mod weather;
 
@@ -10,5 +11,6 @@ pub use daily_luck::*;
 pub use geodes::*;
 pub use mine::*;
 pub use night_events::*;
+pub use step_planner::*;
 pub use traveling_cart::*;
 pub use weather::*;
@@ -2,9 +2,9 @@
 //!
 //! Night events (fairy, witch, meteor, etc.) have version-specific RNG.
 
+use crate::rng::seeding::create_day_save_random;
 use crate::rng::CSRandomLite;
 use crate::version::GameVersion;
-use xxhash_rust::xxh32::xxh32;
 
 /// Types of night events that can occur in Stardew Valley.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -15,8 +15,13 @@ pub enum NightEvent {
     Ufo,        // Strange Capsule
     Owl,        // Stone Owl
     Earthquake, // Opens railroad area (day 3 of Summer Year 1)
+    Windstorm,  // 1.6+: fells the tree that becomes the raccoon's Giant Stump
 }
 
+/// Chance of the 1.6 windstorm once it is eligible (`random.NextBool(0.1)`
+/// in `Utility.pickFarmEvent`).
+const WINDSTORM_CHANCE: f64 = 0.1;
+
 /// Determine what night event (if any) occurs for a given seed and day.
 ///
 /// # Arguments
@@ -27,6 +32,23 @@ pub enum NightEvent {
 /// # Returns
 /// The night event that occurs, or None if no event
 pub fn night_event(seed: i32, days_played: i32, version: GameVersion) -> Option<NightEvent> {
+    night_event_for_farm(seed, days_played, version, false, false)
+}
+
+/// Determine the night event for a specific farm's state and save options.
+///
+/// In 1.6 the windstorm is only rolled once the greenhouse is repaired (and
+/// until it has happened once), so `greenhouse_repaired` adds one roll ahead
+/// of the fairy and shifts every later check. `legacy_random` is the 1.6
+/// "use legacy randomization" option, which seeds with `day + seed/2`
+/// instead of a hash. Neither has an effect before 1.6.
+pub fn night_event_for_farm(
+    seed: i32,
+    days_played: i32,
+    version: GameVersion,
+    greenhouse_repaired: bool,
+    legacy_random: bool,
+) -> Option<NightEvent> {
     // The event is rolled at 6am for what happened "overnight".
     // The game actually uses days_played+1 for the seed calculation.
     let event_day = days_played + 1;
@@ -38,7 +60,8 @@ pub fn night_event(seed: i32, days_played: i32, version: GameVersion) -> Option<
     }
 
     match version {
-        GameVersion::V1_6 => night_event_v16(seed, event_day),
+        GameVersion::V1_6 => night_event_v16(seed, event_day, greenhouse_repaired, legacy_random),
+        GameVersion::V1_5_3 => night_event_v153(seed, event_day),
         GameVersion::V1_5 => night_event_v15(seed, event_day),
         GameVersion::V1_4 => night_event_v14(seed, event_day),
         GameVersion::V1_3 => night_event_v13(seed, event_day),
@@ -47,30 +70,35 @@ pub fn night_event(seed: i32, days_played: i32, version: GameVersion) -> Option<
 
 /// Night event logic for v1.6.
 /// Uses hash-based seeding, 10 prime calls, different probabilities.
+/// Roll order: windstorm (greenhouse only), fairy, witch, meteor, owl, capsule.
 #[inline]
-fn night_event_v16(seed: i32, event_day: i32) -> Option<NightEvent> {
-    // 1.6 uses getRandomSeed(day, gameId/2) with hash-based seeding
-    let rng_seed = hash_seed(event_day, seed / 2);
-    let mut rng = CSRandomLite::new(rng_seed);
+fn night_event_v16(
+    seed: i32,
+    event_day: i32,
+    greenhouse_repaired: bool,
+    legacy_random: bool,
+) -> Option<NightEvent> {
+    // 1.6 uses CreateDaySaveRandom(): hash of (day, gameId/2)
+    let mut rng = create_day_save_random(event_day, seed, 0.0, 0.0, 0.0, legacy_random);
 
     // Prime RNG with 10 calls
     for _ in 0..10 {
         rng.sample();
     }
 
-    // Greenhouse windstorm check (skipped - assume no greenhouse)
-    // The windstorm check consumes one Sample() call
-
-    // For saves without greenhouse, the next roll is reused for both
-    // windstorm check and fairy check
-    let roll = rng.sample();
+    // Windstorm: only checked once the greenhouse is repaired. The check is
+    // short-circuited otherwise, so saves without a greenhouse don't consume
+    // a roll here and the fairy uses the next one.
+    if greenhouse_repaired && rng.sample() < WINDSTORM_CHANCE {
+        return Some(NightEvent::Windstorm);
+    }
 
     let month = ((event_day - 1) / 28) % 4;
     let year = 1 + (event_day - 1) / 112;
 
     // Fairy: 1% chance, but not in winter
     // (Fairy rose bonus +0.7% ignored - too volatile)
-    if roll < 0.01 && month < 3 {
+    if rng.sample() < 0.01 && month < 3 {
         return Some(NightEvent::Fairy);
     }
 
@@ -97,9 +125,48 @@ fn night_event_v16(seed: i32, event_day: i32) -> Option<NightEvent> {
     None
 }
 
+/// Night event logic for v1.5.3-1.5.6.
+/// Same seeding as 1.5.0, but the owl (now 0.5%) is checked before the capsule.
+#[inline]
+fn night_event_v153(seed: i32, event_day: i32) -> Option<NightEvent> {
+    let rng_seed = seed / 2 + event_day;
+    let mut rng = CSRandomLite::new(rng_seed);
+
+    let month = ((event_day - 1) / 28) % 4;
+    let year = 1 + (event_day - 1) / 112;
+
+    // Fairy: 1% chance, but not in winter
+    if rng.sample() < 0.01 && month < 3 {
+        return Some(NightEvent::Fairy);
+    }
+
+    // Witch: 1% chance
+    if rng.sample() < 0.01 {
+        return Some(NightEvent::Witch);
+    }
+
+    // Meteor: 1% chance
+    if rng.sample() < 0.01 {
+        return Some(NightEvent::Meteor);
+    }
+
+    // v1.5.3+: Owl checked before Capsule
+    // Stone Owl: 0.5% chance
+    if rng.sample() < 0.005 {
+        return Some(NightEvent::Owl);
+    }
+
+    // Strange Capsule: 0.8% chance, requires Year 2+
+    if rng.sample() < 0.008 && year > 1 {
+        return Some(NightEvent::Ufo);
+    }
+
+    None
+}
+
 /// Night event logic for v1.5 (v1.5.0-1.5.2).
 /// Uses simple seeding, capsule before owl, both 0.8% probability.
-/// Note: 1.5.3+ changed the order and probabilities, but we treat "1.5" as 1.5.0-1.5.2.
+/// 1.5.3+ is `night_event_v153`.
 #[inline]
 fn night_event_v15(seed: i32, event_day: i32) -> Option<NightEvent> {
     let rng_seed = seed / 2 + event_day;
@@ -184,14 +251,34 @@ fn night_event_v13(seed: i32, event_day: i32) -> Option<NightEvent> {
     night_event_v14(seed, event_day)
 }
 
-/// Hash-based seed generation for 1.6 versions.
-/// Mimics StardewValley.Utility.CreateRandomSeed() using XXHash32.
-#[inline]
-fn hash_seed(a: i32, b: i32) -> i32 {
-    // XXHash32 of 5 integers (a, b, 0, 0, 0) as little-endian bytes
-    let values = [a, b, 0, 0, 0];
-    let bytes: Vec<u8> = values.iter().flat_map(|&v| v.to_le_bytes()).collect();
-    xxh32(&bytes, 0) as i32
+/// Whether `event` can happen overnight after `days_played` for any seed.
+///
+/// Mirrors the day and season gates of the version's roll order: no fairy
+/// in winter, no capsule in year 1, the 1.6 witch and meteor wait for days
+/// 21 and 6, and the windstorm needs 1.6 and a repaired greenhouse.
+pub fn night_event_possible(
+    event: NightEvent,
+    days_played: i32,
+    version: GameVersion,
+    greenhouse_repaired: bool,
+) -> bool {
+    let event_day = days_played + 1;
+    if event_day == 30 {
+        return event == NightEvent::Earthquake;
+    }
+
+    let month = ((event_day - 1) / 28) % 4;
+    let year = 1 + (event_day - 1) / 112;
+    let v16 = matches!(version, GameVersion::V1_6);
+    match event {
+        NightEvent::Fairy => month < 3,
+        NightEvent::Witch => !v16 || event_day > 20,
+        NightEvent::Meteor => !v16 || event_day > 5,
+        NightEvent::Owl => true,
+        NightEvent::Ufo => year > 1,
+        NightEvent::Earthquake => false,
+        NightEvent::Windstorm => version.has_windstorm_event() && greenhouse_repaired,
+    }
 }
 
 /// Check all days in a range for night events.
@@ -214,6 +301,34 @@ pub fn find_night_events(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_night_event_possible_agrees_with_rolls() {
+        for version in [GameVersion::V1_3, GameVersion::V1_5, GameVersion::V1_6] {
+            for greenhouse in [false, true] {
+                for seed in 0..300 {
+                    for day in 1..=240 {
+                        if let Some(event) = night_event_for_farm(seed, day, version, greenhouse, false) {
+                            assert!(
+                                night_event_possible(event, day, version, greenhouse),
+                                "{:?} on day {} in {:?}",
+                                event,
+                                day,
+                                version
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        // Winter has no fairies, year 1 no capsules
+        assert!(!(85..=111).any(|day| night_event_possible(NightEvent::Fairy, day, GameVersion::V1_6, false)));
+        assert!(!(1..=111).any(|day| night_event_possible(NightEvent::Ufo, day, GameVersion::V1_6, false)));
+        assert!(night_event_possible(NightEvent::Ufo, 112, GameVersion::V1_6, false));
+        assert!(!night_event_possible(NightEvent::Windstorm, 10, GameVersion::V1_6, false));
+        assert!(!night_event_possible(NightEvent::Windstorm, 10, GameVersion::V1_5, true));
+    }
+
     #[test]
     fn test_day_29_earthquake() {
         // Day 29 leads to event_day 30, which is always earthquake (Summer 3 Y1)
@@ -244,7 +359,7 @@ mod tests {
         // UFO should not occur in Year 1 (days 1-112)
         for seed in 1..1000 {
             for day in 1..=111 {
-                for version in [GameVersion::V1_4, GameVersion::V1_5, GameVersion::V1_6] {
+                for version in [GameVersion::V1_4, GameVersion::V1_5, GameVersion::V1_5_3, GameVersion::V1_6] {
                     if let Some(event) = night_event(seed, day, version) {
                         assert_ne!(
                             event,
@@ -281,4 +396,84 @@ mod tests {
             "Should find at least one seed/day where versions differ"
         );
     }
+
+    #[test]
+    fn test_windstorm_requires_greenhouse() {
+        for seed in 1..500 {
+            for day in 1..=224 {
+                assert_ne!(night_event(seed, day, GameVersion::V1_6), Some(NightEvent::Windstorm));
+            }
+        }
+    }
+
+    #[test]
+    fn test_windstorm_only_v16() {
+        for seed in 1..500 {
+            for day in 1..=112 {
+                for version in [GameVersion::V1_3, GameVersion::V1_4, GameVersion::V1_5, GameVersion::V1_5_3] {
+                    assert_eq!(
+                        night_event_for_farm(seed, day, version, true, false),
+                        night_event(seed, day, version)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_greenhouse_shifts_v16_rolls() {
+        // The extra windstorm roll shifts every later check, so the
+        // non-windstorm events can differ with a repaired greenhouse.
+        let mut found_difference = false;
+        'outer: for seed in 1..2000 {
+            for day in 1..=112 {
+                let without = night_event(seed, day, GameVersion::V1_6);
+                let with = night_event_for_farm(seed, day, GameVersion::V1_6, true, false);
+                if with != Some(NightEvent::Windstorm) && with != without {
+                    found_difference = true;
+                    break 'outer;
+                }
+            }
+        }
+        assert!(found_difference);
+    }
+
+    #[test]
+    fn test_legacy_random_changes_v16_seeding() {
+        let differs = (1..2000).any(|seed| {
+            (1..=112).any(|day| {
+                night_event_for_farm(seed, day, GameVersion::V1_6, false, true)
+                    != night_event(seed, day, GameVersion::V1_6)
+            })
+        });
+        assert!(differs);
+
+        // The option only exists in 1.6
+        for seed in 1..200 {
+            for day in 1..=112 {
+                assert_eq!(
+                    night_event_for_farm(seed, day, GameVersion::V1_5, false, true),
+                    night_event(seed, day, GameVersion::V1_5)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_v153_reorders_owl_and_capsule() {
+        // Same seed and first three rolls as 1.5.0; only the owl/capsule
+        // checks differ
+        let mut found_difference = false;
+        for seed in 1..2000 {
+            for day in 113..=224 {
+                let v15 = night_event(seed, day, GameVersion::V1_5);
+                let v153 = night_event(seed, day, GameVersion::V1_5_3);
+                if matches!(v15, Some(NightEvent::Fairy | NightEvent::Witch | NightEvent::Meteor)) {
+                    assert_eq!(v153, v15);
+                }
+                found_difference |= v15 != v153;
+            }
+        }
+        assert!(found_difference);
+    }
 }
@@ -0,0 +1,170 @@
+//! Step-count manipulation planning.
+//!
+//! The day-start RNG is seeded with `seed/100 + day*10 + 1 + steps`, where
+//! `steps` is the save's cumulative step count (`Game1.stats.StepsTaken`) when
+//! the day starts. Walking until the total reaches a chosen count therefore
+//! picks which dish, luck and weather roll happens.
+
+use super::daily_luck::{daily_luck, dish_of_the_day};
+use super::weather::{forecast_weather, scripted_weather, weather_tomorrow, Weather};
+use crate::version::GameVersion;
+
+/// Most step counts one `plan_steps` call checks.
+pub const MAX_STEP_WINDOW: i32 = 100_000;
+
+/// A `plan_steps` window holding more than `MAX_STEP_WINDOW` step counts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StepWindowTooWide;
+
+impl std::fmt::Display for StepWindowTooWide {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Step window too wide: at most {} step counts per call", MAX_STEP_WINDOW)
+    }
+}
+
+impl std::error::Error for StepWindowTooWide {}
+
+/// What a step count has to produce.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StepTarget {
+    /// Daily luck within [min, max]
+    Luck { min: f64, max: f64 },
+    /// A specific dish of the day, with at least `min_quantity` servings
+    Dish { id: i32, min_quantity: i32 },
+}
+
+/// Outcome of walking a given number of steps.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StepPlan {
+    pub steps: i32,
+    pub luck: f64,
+    pub dish_id: i32,
+    pub dish_quantity: i32,
+    /// Tomorrow's weather, rolled from the same seed
+    pub weather: Weather,
+}
+
+/// Find the total step counts in `[min_steps, max_steps]` that hit `target` on `days_played`.
+///
+/// Negative step counts are skipped. Windows with more than `MAX_STEP_WINDOW`
+/// step counts left are an error.
+///
+/// Only `days_played` is affected by the step count. Today's weather (which
+/// decides whether debris samples are consumed) is forecast with zero steps
+/// on earlier days.
+#[allow(clippy::too_many_arguments)]
+pub fn plan_steps(
+    seed: i32,
+    days_played: i32,
+    target: StepTarget,
+    min_steps: i32,
+    max_steps: i32,
+    has_friends: bool,
+    legacy_random: bool,
+    version: GameVersion,
+) -> Result<Vec<StepPlan>, StepWindowTooWide> {
+    let first = min_steps.max(0);
+    if max_steps as i64 - first as i64 >= MAX_STEP_WINDOW as i64 {
+        return Err(StepWindowTooWide);
+    }
+    let weather_today =
+        forecast_weather(seed, days_played, days_played, 0, has_friends, legacy_random, version)[0];
+    let scripted = scripted_weather(seed, days_played + 1, legacy_random, version);
+
+    let plans = (first..=max_steps)
+        .filter_map(|steps| {
+            let (dish_id, dish_quantity) = dish_of_the_day(seed, days_played, steps);
+            let luck = daily_luck(seed, days_played, steps, has_friends);
+
+            let hit = match target {
+                StepTarget::Luck { min, max } => luck >= min && luck <= max,
+                StepTarget::Dish { id, min_quantity } => dish_id == id && dish_quantity >= min_quantity,
+            };
+            if !hit {
+                return None;
+            }
+
+            // Weather rolls on past the dish and luck, so only do it for hits
+            let weather = scripted.unwrap_or_else(|| {
+                weather_tomorrow(
+                    seed,
+                    days_played,
+                    steps,
+                    weather_today.to_code(),
+                    has_friends,
+                    legacy_random,
+                    version,
+                )
+            });
+
+            Some(StepPlan {
+                steps,
+                luck,
+                dish_id,
+                dish_quantity,
+                weather,
+            })
+        })
+        .collect();
+    Ok(plans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plans_match_direct_predictions() {
+        let target = StepTarget::Luck { min: 0.07, max: 0.1 };
+        let plans = plan_steps(12345, 5, target, 0, 2000, false, false, GameVersion::V1_6).unwrap();
+        assert!(!plans.is_empty(), "Expected some lucky step counts in 2000");
+
+        for plan in &plans {
+            assert!(plan.luck >= 0.07);
+            assert_eq!(plan.luck, daily_luck(12345, 5, plan.steps, false));
+            assert_eq!(
+                (plan.dish_id, plan.dish_quantity),
+                dish_of_the_day(12345, 5, plan.steps)
+            );
+        }
+    }
+
+    #[test]
+    fn test_zero_steps_matches_forecast() {
+        // With zero steps the plan's weather is the regular forecast for tomorrow
+        let any = StepTarget::Luck { min: -1.0, max: 1.0 };
+        for day in 1..=56 {
+            let plan = plan_steps(12345, day, any, 0, 0, false, false, GameVersion::V1_6).unwrap()[0];
+            let forecast = forecast_weather(12345, day + 1, day + 1, 0, false, false, GameVersion::V1_6)[0];
+            assert_eq!(plan.weather, forecast, "day {}", day);
+        }
+    }
+
+    #[test]
+    fn test_wide_windows_are_refused() {
+        let any = StepTarget::Luck { min: -1.0, max: 1.0 };
+        let plans = plan_steps(12345, 5, any, i32::MAX - 10, i32::MAX, false, false, GameVersion::V1_6).unwrap();
+        assert_eq!(plans.len(), 11);
+        assert_eq!(plans[10].luck, daily_luck(12345, 5, i32::MAX, false));
+
+        let plans = plan_steps(12345, 5, any, -5, MAX_STEP_WINDOW - 1, false, false, GameVersion::V1_6).unwrap();
+        assert_eq!(plans.len(), MAX_STEP_WINDOW as usize);
+        assert_eq!(
+            plan_steps(12345, 5, any, 0, MAX_STEP_WINDOW, false, false, GameVersion::V1_6),
+            Err(StepWindowTooWide)
+        );
+        assert_eq!(
+            plan_steps(12345, 5, any, 0, i32::MAX, false, false, GameVersion::V1_6),
+            Err(StepWindowTooWide)
+        );
+    }
+
+    #[test]
+    fn test_dish_target() {
+        let (dish, _) = dish_of_the_day(12345, 3, 42);
+        let target = StepTarget::Dish { id: dish, min_quantity: 1 };
+        let plans = plan_steps(12345, 3, target, 0, 500, false, false, GameVersion::V1_5).unwrap();
+        assert!(plans.iter().any(|p| p.steps == 42));
+        assert!(plans.iter().all(|p| p.dish_id == dish));
+    }
+}
@@ -1,7 +1,7 @@
-use crate::rng::CSRandom;
+use crate::rng::seeding::create_day_save_random;
+use crate::rng::CSRandomLite;
 use crate::GameVersion;
 use std::collections::HashMap;
-use xxhash_rust::xxh32::xxh32;
 
 // Use the 1.6 object data from parent module
 use super::cart_objects_1_6::CART_OBJECTS_1_6;
@@ -90,20 +90,6 @@ fn get_item_base_price(item_id: i32) -> i32 {
         .unwrap_or(0)
 }
 
-/// Hash-based seed generation for 1.6 (XXHash32)
-/// Mimics StardewValley.Utility.CreateRandomSeed() / getHashFromArray()
-fn get_random_seed_1_6(a: i32, b: i32) -> i32 {
-    // Create Int32Array with the values (like JavaScript's Int32Array)
-    let values = [a, b, 0, 0, 0];
-    let bytes: Vec<u8> = values
-        .iter()
-        .flat_map(|&v| v.to_le_bytes())
-        .collect();
-
-    // XXHash32 with seed 0
-    xxh32(&bytes, 0) as i32
-}
-
 /// A cart item with its ID, price, and quantity
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct CartItem {
@@ -115,7 +101,7 @@ pub struct CartItem {
 /// Generate the traveling cart stock for pre-1.4 (1.3)
 /// Pre-1.4 uses a direct lookup table, no duplicate prevention
 fn get_cart_stock_pre14(seed: i32) -> Vec<CartItem> {
-    let mut rng = CSRandom::new(seed);
+    let mut rng = CSRandomLite::new(seed);
     let mut stock = Vec::with_capacity(10);
 
     for _ in 0..10 {
@@ -149,7 +135,7 @@ fn get_cart_stock_pre14(seed: i32) -> Vec<CartItem> {
 /// IMPORTANT: Price/quantity RNG calls happen for EVERY valid item tested,
 /// even if that item is already seen and gets skipped!
 fn get_cart_stock_1_4_plus(seed: i32) -> Vec<CartItem> {
-    let mut rng = CSRandom::new(seed);
+    let mut rng = CSRandomLite::new(seed);
     let mut stock = Vec::with_capacity(10);
     let mut seen_names: std::collections::HashSet<i32> = std::collections::HashSet::new();
 
@@ -198,10 +184,9 @@ fn get_cart_stock_1_4_plus(seed: i32) -> Vec<CartItem> {
 
 /// Generate the traveling cart stock for version 1.6
 /// 1.6 uses shuffle-based selection with getRandomItems()
-fn get_cart_stock_v16(game_id: i32, day: i32) -> Vec<CartItem> {
-    // Seed: getRandomSeed(day, gameId/2) - hash-based
-    let seed = get_random_seed_1_6(day, game_id / 2);
-    let mut rng = CSRandom::new(seed);
+fn get_cart_stock_v16(game_id: i32, day: i32, legacy_random: bool) -> Vec<CartItem> {
+    // Seed: CreateDaySaveRandom() - hash of (day, gameId/2)
+    let mut rng = create_day_save_random(day, game_id, 0.0, 0.0, 0.0, legacy_random);
 
     // ============================================================================
     // COLLISION BEHAVIOR - SEE COLLISION_ERRATA.md
@@ -307,7 +292,7 @@ fn get_cart_stock_v16(game_id: i32, day: i32) -> Vec<CartItem> {
 pub fn get_traveling_cart_stock(seed: i32, version: GameVersion) -> Vec<CartItem> {
     match version {
         GameVersion::V1_3 => get_cart_stock_pre14(seed),
-        GameVersion::V1_4 | GameVersion::V1_5 => get_cart_stock_1_4_plus(seed),
+        GameVersion::V1_4 | GameVersion::V1_5 | GameVersion::V1_5_3 => get_cart_stock_1_4_plus(seed),
         GameVersion::V1_6 => {
             // 1.6 uses different seeding and algorithm, handled by get_cart_for_day_v16
             // This function is kept for compatibility but 1.6 should use get_cart_for_day
@@ -317,18 +302,31 @@ pub fn get_traveling_cart_stock(seed: i32, version: GameVersion) -> Vec<CartItem
 }
 
 /// Get traveling cart stock for a specific game and day
-pub fn get_cart_for_day(game_id: i32, day_number: i32, version: GameVersion) -> Vec<CartItem> {
+///
+/// `legacy_random` is the 1.6 "use legacy randomization" save option.
+pub fn get_cart_for_day(
+    game_id: i32,
+    day_number: i32,
+    legacy_random: bool,
+    version: GameVersion,
+) -> Vec<CartItem> {
     match version {
-        GameVersion::V1_6 => get_cart_stock_v16(game_id, day_number),
+        GameVersion::V1_6 => get_cart_stock_v16(game_id, day_number, legacy_random),
         _ => get_traveling_cart_stock(game_id.wrapping_add(day_number), version),
     }
 }
 
 /// Check if the traveling cart has a specific item on a given day
-pub fn cart_has_item(game_id: i32, day_number: i32, target_item: i32, version: GameVersion) -> bool {
+pub fn cart_has_item(
+    game_id: i32,
+    day_number: i32,
+    target_item: i32,
+    legacy_random: bool,
+    version: GameVersion,
+) -> bool {
     match version {
-        GameVersion::V1_6 => cart_has_item_v16_fast(game_id, day_number, target_item),
-        GameVersion::V1_4 | GameVersion::V1_5 => {
+        GameVersion::V1_6 => cart_has_item_v16_fast(game_id, day_number, target_item, legacy_random),
+        GameVersion::V1_4 | GameVersion::V1_5 | GameVersion::V1_5_3 => {
             cart_has_item_1_4_fast(game_id.wrapping_add(day_number), target_item)
         }
         GameVersion::V1_3 => cart_has_item_pre14_fast(game_id.wrapping_add(day_number), target_item),
@@ -337,7 +335,7 @@ pub fn cart_has_item(game_id: i32, day_number: i32, target_item: i32, version: G
 
 /// Fast cart item check for pre-1.4 - no allocations
 fn cart_has_item_pre14_fast(seed: i32, target_item: i32) -> bool {
-    let mut rng = CSRandom::new(seed);
+    let mut rng = CSRandomLite::new(seed);
 
     for _ in 0..10 {
         let roll = rng.next_range(2, 790);
@@ -358,7 +356,7 @@ fn cart_has_item_pre14_fast(seed: i32, target_item: i32) -> bool {
 
 /// Fast cart item check for 1.4/1.5 - uses fixed-size array instead of HashSet
 fn cart_has_item_1_4_fast(seed: i32, target_item: i32) -> bool {
-    let mut rng = CSRandom::new(seed);
+    let mut rng = CSRandomLite::new(seed);
     // Fixed-size array for seen items (max 10 items, but we might check more due to duplicates)
     let mut seen: [i32; 10] = [0; 10];
     let mut seen_count = 0;
@@ -415,9 +413,8 @@ fn cart_has_item_1_4_fast(seed: i32, target_item: i32) -> bool {
 /// 10 comparisons, so total is O(10n) ≈ 3000 ops vs sorting's ~2400 comparisons,
 /// but with much lower constant factors (no recursion, cache-friendly).
 ///
-fn cart_has_item_v16_fast(game_id: i32, day: i32, target_item: i32) -> bool {
-    let seed = get_random_seed_1_6(day, game_id / 2);
-    let mut rng = CSRandom::new(seed);
+fn cart_has_item_v16_fast(game_id: i32, day: i32, target_item: i32, legacy_random: bool) -> bool {
+    let mut rng = create_day_save_random(day, game_id, 0.0, 0.0, 0.0, legacy_random);
 
     // Top 10 candidates: (shuffle_key, iteration_index, item_id)
     // Initialized with MAX keys so any real item will be better
@@ -482,6 +479,7 @@ pub fn find_item_in_cart(
     game_id: i32,
     target_item: i32,
     max_days: i32,
+    legacy_random: bool,
     version: GameVersion,
 ) -> Option<(i32, i32, i32)> {
     let mut day = 5; // First Friday
@@ -489,7 +487,7 @@ pub fn find_item_in_cart(
     while day <= max_days {
         for cart_day in [day, day + 2].iter() {
             if *cart_day <= max_days {
-                let stock = get_cart_for_day(game_id, *cart_day, version);
+                let stock = get_cart_for_day(game_id, *cart_day, legacy_random, version);
                 if let Some(item) = stock.iter().find(|i| i.item_id == target_item) {
                     return Some((*cart_day, item.price, item.quantity));
                 }
@@ -562,13 +560,13 @@ mod tests {
 
     #[test]
     fn test_find_item_red_cabbage() {
-        let result = find_item_in_cart(12345, 266, 224, GameVersion::V1_5);
+        let result = find_item_in_cart(12345, 266, 224, false, GameVersion::V1_5);
         assert!(result.is_some(), "Should find Red Cabbage within 2 years");
     }
 
     #[test]
     fn test_overflow_handling() {
-        let stock = get_cart_for_day(i32::MAX, 5, GameVersion::V1_5);
+        let stock = get_cart_for_day(i32::MAX, 5, false, GameVersion::V1_5);
         assert_eq!(stock.len(), 10);
     }
 
@@ -584,7 +582,7 @@ mod tests {
 
     #[test]
     fn test_debug_cart_v16() {
-        let cart = get_cart_for_day(1, 5, GameVersion::V1_6);
+        let cart = get_cart_for_day(1, 5, false, GameVersion::V1_6);
         println!("Cart for seed=1, day=5, v1.6:");
         for (i, item) in cart.iter().enumerate() {
             println!("[{}] id={} price={} qty={}", i, item.item_id, item.price, item.quantity);
@@ -595,4 +593,21 @@ mod tests {
         // etc.
         assert_eq!(cart.len(), 10);
     }
+
+    #[test]
+    fn test_legacy_random_v16_cart() {
+        // Legacy seeding sums day and gameId/2, so the fast check must agree
+        // with the full stock under either option
+        for game_id in [1, 12345, 25831481] {
+            for day in [5, 7, 12] {
+                let hashed = get_cart_for_day(game_id, day, false, GameVersion::V1_6);
+                let legacy = get_cart_for_day(game_id, day, true, GameVersion::V1_6);
+                assert_eq!(legacy.len(), 10);
+                assert_ne!(hashed, legacy);
+                for item in &legacy {
+                    assert!(cart_has_item(game_id, day, item.item_id, true, GameVersion::V1_6));
+                }
+            }
+        }
+    }
 }
@@ -2,9 +2,13 @@
 //!
 //! Predicts tomorrow's weather based on game seed and current day.
 
-use crate::rng::CSRandom;
+use crate::rng::seeding::create_random;
+use crate::rng::CSRandomLite;
 use crate::version::GameVersion;
-use super::daily_luck::{dish_of_the_day, daily_luck};
+use super::daily_luck::{day_start_seed, dish_of_the_day, daily_luck};
+
+/// Summer days that can be picked for green rain (1.6+), one per year.
+const GREEN_RAIN_DAYS: [i32; 8] = [5, 6, 7, 14, 15, 16, 18, 23];
 
 /// Weather types.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -14,6 +18,7 @@ pub enum Weather {
     Debris = 2,  // Windy/leaves
     Lightning = 3,
     Snow = 5,
+    GreenRain = 6, // 1.6+ summer only
 }
 
 impl Weather {
@@ -24,6 +29,7 @@ impl Weather {
             2 => Weather::Debris,
             3 => Weather::Lightning,
             5 => Weather::Snow,
+            6 => Weather::GreenRain,
             _ => Weather::Sunny,
         }
     }
@@ -35,12 +41,16 @@ impl Weather {
 
 /// Predict tomorrow's weather.
 ///
+/// Fixed days (season starts, festivals, etc.) are applied via
+/// `scripted_weather`. Use `forecast_weather` to chain days together.
+///
 /// # Arguments
 /// * `seed` - Game seed
 /// * `days_played` - Current day (1 = Spring 1 Year 1)
 /// * `steps` - Step count modifier (usually 0)
 /// * `weather_today` - Today's weather code (affects RNG consumption)
 /// * `has_friends` - Whether player has multiplayer friends
+/// * `legacy_random` - 1.6 "legacy randomization" option (green rain day)
 /// * `version` - Game version
 #[allow(clippy::if_same_then_else)] // Intentional: different RNG calls for spring vs fall
 pub fn weather_tomorrow(
@@ -49,10 +59,11 @@ pub fn weather_tomorrow(
     steps: i32,
     weather_today: u8,
     has_friends: bool,
+    legacy_random: bool,
     version: GameVersion,
 ) -> Weather {
     // Initialize RNG - same formula as daily luck
-    let mut rng = CSRandom::new(seed / 100 + (days_played - 1) * 10 + 1 + steps);
+    let mut rng = CSRandomLite::new(day_start_seed(seed, days_played, steps));
 
     // Consume RNG calls for dish of day and daily luck
     dish_of_the_day(seed, days_played, steps);
@@ -107,7 +118,7 @@ pub fn weather_tomorrow(
     };
 
     // Determine weather
-    if rng.sample() < chance_to_rain {
+    let rolled = if rng.sample() < chance_to_rain {
         // Rainy conditions
         if winter {
             Weather::Snow
@@ -126,30 +137,177 @@ pub fn weather_tomorrow(
         Weather::Debris
     } else {
         Weather::Sunny
+    };
+
+    // Fixed days are applied on top of the roll, so the RNG consumption
+    // above is the same either way.
+    scripted_weather(seed, days_played + 1, legacy_random, version).unwrap_or(rolled)
+}
+
+/// Weather the game forces for a day regardless of the RNG roll.
+///
+/// Applied in the same order as the game, so later rules win:
+/// - Day 1 of every season is sunny (1.6 also keeps days 1-4 of Year 1 sunny)
+/// - Day 3 of Year 1 always rains
+/// - 1.6 green rain day in summer
+/// - 1.6 storms on Summer 13 and 26
+/// - Festival days are sunny
+pub fn scripted_weather(seed: i32, day: i32, legacy_random: bool, version: GameVersion) -> Option<Weather> {
+    let season = ((day - 1) / 28) % 4;
+    let day_of_month = ((day - 1) % 28) + 1;
+    let mut weather = None;
+
+    if day_of_month == 1 || (version.has_green_rain() && day <= 4) {
+        weather = Some(Weather::Sunny);
+    }
+    if day == 3 {
+        weather = Some(Weather::Rain);
     }
+    if version.has_green_rain() {
+        // 1.6 replaces one summer day per year with green rain
+        if is_green_rain_day(seed, day, legacy_random) {
+            weather = Some(Weather::GreenRain);
+        }
+        if season == 1 && day_of_month % 13 == 0 {
+            weather = Some(Weather::Lightning);
+        }
+    }
+    if is_festival_day(day) {
+        weather = Some(Weather::Sunny);
+    }
+
+    weather
 }
 
-/// Find days with specific weather in a range.
-pub fn find_weather_days(
+/// Whether `weather` can fall on `day` for any seed.
+///
+/// Scripted days allow only their weather; otherwise snow is winter-only,
+/// rain and storms never happen in winter, windy days are spring and fall
+/// only, and green rain needs 1.6 and one of its candidate summer days.
+pub fn weather_possible(weather: Weather, day: i32, version: GameVersion) -> bool {
+    let season = ((day - 1) / 28) % 4;
+    let day_of_month = ((day - 1) % 28) + 1;
+
+    if is_festival_day(day) || day_of_month == 1 || (version.has_green_rain() && day <= 4 && day != 3) {
+        return weather == Weather::Sunny;
+    }
+    if day == 3 {
+        return weather == Weather::Rain;
+    }
+    if version.has_green_rain() && season == 1 && day_of_month % 13 == 0 {
+        return weather == Weather::Lightning;
+    }
+
+    match weather {
+        Weather::Sunny => true,
+        Weather::Rain | Weather::Lightning => season != 3,
+        Weather::Debris => season == 0 || season == 2,
+        Weather::Snow => season == 3,
+        Weather::GreenRain => {
+            version.has_green_rain() && season == 1 && GREEN_RAIN_DAYS.contains(&day_of_month)
+        }
+    }
+}
+
+/// Check if a day (1 = Spring 1 Year 1) is a festival day.
+/// Festivals always have sunny weather.
+pub fn is_festival_day(day: i32) -> bool {
+    let season = ((day - 1) / 28) % 4;
+    let day_of_month = ((day - 1) % 28) + 1;
+    matches!(
+        (season, day_of_month),
+        (0, 13) | (0, 24) | (1, 11) | (1, 28) | (2, 16) | (2, 27) | (3, 8) | (3, 25)
+    )
+}
+
+/// Forecast the weather on each day in a range.
+///
+/// Walks day by day from the start of `start_day`'s season and feeds each
+/// day's weather into the next `weather_tomorrow` call, since a windy day
+/// changes how much RNG the next roll consumes. Day 1 of every season is
+/// always sunny, so nothing before the season start matters.
+///
+/// Returns one entry per day in `start_day..=end_day`.
+pub fn forecast_weather(
     seed: i32,
     start_day: i32,
     end_day: i32,
-    target_weather: Weather,
+    steps: i32,
+    has_friends: bool,
+    legacy_random: bool,
     version: GameVersion,
-) -> Vec<i32> {
-    let mut results = Vec::new();
+) -> Vec<Weather> {
+    // rem_euclid keeps the season start at or before start_day for days < 1
+    let season_start = start_day - (start_day - 1).rem_euclid(28);
+    let mut results = Vec::with_capacity((end_day - start_day + 1).max(0) as usize);
+    let mut today = Weather::Sunny;
 
-    for day in start_day..=end_day {
-        // For simplicity, assume sunny today (weather_today = 0)
-        let weather = weather_tomorrow(seed, day, 0, 0, false, version);
-        if weather == target_weather {
-            results.push(day);
+    for day in season_start..=end_day {
+        if day > season_start {
+            today = weather_tomorrow(seed, day - 1, steps, today.to_code(), has_friends, legacy_random, version);
+        }
+        if day >= start_day {
+            results.push(today);
         }
     }
 
     results
 }
 
+/// Forecast a whole season (28 days) of a given year.
+///
+/// `season` is 0-3 (Spring, Summer, Fall, Winter) and `year` starts at 1.
+pub fn forecast_season(
+    seed: i32,
+    year: i32,
+    season: i32,
+    steps: i32,
+    has_friends: bool,
+    legacy_random: bool,
+    version: GameVersion,
+) -> Vec<Weather> {
+    let first_day = (year - 1) * 112 + season * 28 + 1;
+    forecast_weather(seed, first_day, first_day + 27, steps, has_friends, legacy_random, version)
+}
+
+/// Day of summer (1-28) that gets green rain in the given year (1.6+).
+///
+/// Mimics `Utility.isGreenRainDay()`: the game seeds a fresh RNG with
+/// `CreateRandom(uniqueIDForThisGame + year * 777)` and picks one of the
+/// candidate days with a single `Next(8)`. With legacy randomization the
+/// seed is summed instead of hashed.
+pub fn green_rain_day(seed: i32, year: i32, legacy_random: bool) -> i32 {
+    let mut rng = create_random(seed as f64 + (year * 777) as f64, 0.0, 0.0, 0.0, 0.0, legacy_random);
+    GREEN_RAIN_DAYS[rng.next_max(GREEN_RAIN_DAYS.len() as i32) as usize]
+}
+
+/// Check if a day (1 = Spring 1 Year 1) is the green rain day for its year.
+pub fn is_green_rain_day(seed: i32, day: i32, legacy_random: bool) -> bool {
+    let season = ((day - 1) / 28) % 4;
+    if season != 1 {
+        return false;
+    }
+    let year = 1 + (day - 1) / 112;
+    let day_of_month = ((day - 1) % 28) + 1;
+    day_of_month == green_rain_day(seed, year, legacy_random)
+}
+
+/// Find days with specific weather in a range (weather on the day itself).
+pub fn find_weather_days(
+    seed: i32,
+    start_day: i32,
+    end_day: i32,
+    target_weather: Weather,
+    version: GameVersion,
+) -> Vec<i32> {
+    forecast_weather(seed, start_day, end_day, 0, false, false, version)
+        .into_iter()
+        .zip(start_day..=end_day)
+        .filter(|&(weather, _)| weather == target_weather)
+        .map(|(_, day)| day)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -160,13 +318,15 @@ mod tests {
         assert_eq!(Weather::Rain.to_code(), 1);
         assert_eq!(Weather::Lightning.to_code(), 3);
         assert_eq!(Weather::Snow.to_code(), 5);
+        assert_eq!(Weather::GreenRain.to_code(), 6);
+        assert_eq!(Weather::from_code(6), Weather::GreenRain);
     }
 
     #[test]
     fn test_weather_prediction() {
         // Weather should be deterministic
-        let w1 = weather_tomorrow(12345, 5, 0, 0, false, GameVersion::V1_5);
-        let w2 = weather_tomorrow(12345, 5, 0, 0, false, GameVersion::V1_5);
+        let w1 = weather_tomorrow(12345, 5, 0, 0, false, false, GameVersion::V1_5);
+        let w2 = weather_tomorrow(12345, 5, 0, 0, false, false, GameVersion::V1_5);
         assert_eq!(w1, w2);
     }
 
@@ -174,7 +334,7 @@ mod tests {
     fn test_winter_snow() {
         // In winter, rainy weather becomes snow
         for seed in 0..1000 {
-            let weather = weather_tomorrow(seed, 85, 0, 0, false, GameVersion::V1_5);
+            let weather = weather_tomorrow(seed, 85, 0, 0, false, false, GameVersion::V1_5);
             if weather == Weather::Snow {
                 return; // Found snow in winter
             }
@@ -186,8 +346,8 @@ mod tests {
         // 1.5+ has extra RNG call for Ginger Island, so results may differ
         let mut found_difference = false;
         for seed in 1..1000 {
-            let v14 = weather_tomorrow(seed, 50, 0, 0, false, GameVersion::V1_4);
-            let v15 = weather_tomorrow(seed, 50, 0, 0, false, GameVersion::V1_5);
+            let v14 = weather_tomorrow(seed, 50, 0, 0, false, false, GameVersion::V1_4);
+            let v15 = weather_tomorrow(seed, 50, 0, 0, false, false, GameVersion::V1_5);
             if v14 != v15 {
                 found_difference = true;
                 break;
@@ -195,4 +355,150 @@ mod tests {
         }
         assert!(found_difference, "1.4 and 1.5 should sometimes give different weather");
     }
+
+    #[test]
+    fn test_green_rain_day_is_candidate() {
+        for seed in [1, 100, 12345, 999999, i32::MAX] {
+            for year in 1..=5 {
+                assert!(GREEN_RAIN_DAYS.contains(&green_rain_day(seed, year, false)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_green_rain_once_per_summer_v16() {
+        // Summer Year 1 is days 29-56; weather_tomorrow(day) is the weather for day + 1
+        for seed in [1, 100, 12345, 999999] {
+            let green_days: Vec<i32> = (28..=55)
+                .filter(|&day| {
+                    weather_tomorrow(seed, day, 0, 0, false, false, GameVersion::V1_6) == Weather::GreenRain
+                })
+                .map(|day| day + 1)
+                .collect();
+            assert_eq!(green_days, vec![28 + green_rain_day(seed, 1, false)]);
+        }
+    }
+
+    #[test]
+    fn test_no_green_rain_before_v16() {
+        for seed in 0..200 {
+            for day in 1..=112 {
+                assert_ne!(
+                    weather_tomorrow(seed, day, 0, 0, false, false, GameVersion::V1_5),
+                    Weather::GreenRain
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_green_rain_only_in_summer() {
+        for seed in 0..50 {
+            for day in 1..=224 {
+                if is_green_rain_day(seed, day, false) {
+                    assert_eq!(((day - 1) / 28) % 4, 1, "Green rain outside summer on day {}", day);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_weather_possible_agrees_with_forecast() {
+        for version in [GameVersion::V1_3, GameVersion::V1_5, GameVersion::V1_6] {
+            for seed in 0..100 {
+                for (day, weather) in (1..).zip(forecast_weather(seed, 1, 224, 0, false, false, version)) {
+                    assert!(
+                        weather_possible(weather, day, version),
+                        "{:?} on day {} in {:?}",
+                        weather,
+                        day,
+                        version
+                    );
+                }
+            }
+        }
+
+        assert!(!(29..=56).any(|day| weather_possible(Weather::Snow, day, GameVersion::V1_6)));
+        assert!(!(85..=112).any(|day| weather_possible(Weather::Rain, day, GameVersion::V1_6)));
+        assert!(!(1..=224).any(|day| weather_possible(Weather::GreenRain, day, GameVersion::V1_5)));
+        assert!(weather_possible(Weather::GreenRain, 33, GameVersion::V1_6));
+    }
+
+    #[test]
+    fn test_forecast_scripted_days() {
+        for version in [GameVersion::V1_5, GameVersion::V1_6] {
+            for seed in [1, 100, 12345, 999999] {
+                let year1 = forecast_weather(seed, 1, 112, 0, false, false, version);
+                assert_eq!(year1[0], Weather::Sunny, "Spring 1");
+                assert_eq!(year1[2], Weather::Rain, "Spring 3 Year 1");
+                for festival in [13, 24, 28 + 11, 28 + 28, 56 + 16, 56 + 27, 84 + 8, 84 + 25] {
+                    assert_eq!(year1[festival - 1], Weather::Sunny, "Festival day {}", festival);
+                }
+                for season_start in [29, 57, 85] {
+                    assert_eq!(year1[season_start - 1], Weather::Sunny, "Day 1 of season");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_forecast_summer_storms_v16() {
+        for seed in [1, 100, 12345] {
+            let summer = forecast_season(seed, 1, 1, 0, false, false, GameVersion::V1_6);
+            assert_eq!(summer[12], Weather::Lightning);
+            assert_eq!(summer[25], Weather::Lightning);
+            let green_days: Vec<usize> = (0..28).filter(|&i| summer[i] == Weather::GreenRain).collect();
+            assert_eq!(green_days, vec![green_rain_day(seed, 1, false) as usize - 1]);
+        }
+    }
+
+    #[test]
+    fn test_forecast_matches_season_walk() {
+        // A forecast starting mid-season must agree with the full season walk
+        let season = forecast_season(12345, 1, 2, 0, false, false, GameVersion::V1_5);
+        let partial = forecast_weather(12345, 56 + 10, 56 + 20, 0, false, false, GameVersion::V1_5);
+        assert_eq!(&season[9..20], &partial[..]);
+    }
+
+    #[test]
+    fn test_forecast_before_day_one() {
+        // One entry per day even when the range starts before Spring 1
+        let forecast = forecast_weather(12345, -30, 5, 0, false, false, GameVersion::V1_6);
+        assert_eq!(forecast.len(), 36);
+        assert_eq!(&forecast[31..], &forecast_weather(12345, 1, 5, 0, false, false, GameVersion::V1_6)[..]);
+    }
+
+    #[test]
+    fn test_forecast_feeds_debris_into_tomorrow() {
+        // After a windy day the chained forecast must use the debris RNG path,
+        // which can differ from assuming a sunny today.
+        let mut found_difference = false;
+        'outer: for seed in 0..500 {
+            let fall = forecast_season(seed, 1, 2, 0, false, false, GameVersion::V1_5);
+            for i in 0..27 {
+                let day = 57 + i as i32;
+                if fall[i] == Weather::Debris {
+                    let chained = weather_tomorrow(seed, day, 0, 2, false, false, GameVersion::V1_5);
+                    assert_eq!(fall[i + 1], chained);
+                    if chained != weather_tomorrow(seed, day, 0, 0, false, false, GameVersion::V1_5) {
+                        found_difference = true;
+                        break 'outer;
+                    }
+                }
+            }
+        }
+        assert!(found_difference, "Debris days should change the next forecast");
+    }
+
+    #[test]
+    fn test_green_rain_day_honors_legacy_random() {
+        // Legacy randomization sums the seed, so some games move their green rain
+        let moved = (0..200).filter(|&seed| green_rain_day(seed, 1, true) != green_rain_day(seed, 1, false));
+        assert!(moved.count() > 0);
+        for seed in 0..50 {
+            let summer = forecast_season(seed, 1, 1, 0, false, true, GameVersion::V1_6);
+            let green_days: Vec<usize> = (0..28).filter(|&i| summer[i] == Weather::GreenRain).collect();
+            assert_eq!(green_days, vec![green_rain_day(seed, 1, true) as usize - 1]);
+        }
+    }
 }
@@ -43,12 +43,13 @@ impl CSRandom {
             mj = seed_array[ii];
         }
 
-        // 4-pass shuffle
+        // 4-pass shuffle. .NET corrects a negative value only once, so a
+        // wrapped i32::MIN stays at -1 (reachable for |seed| = 1235545220)
         for _ in 0..4 {
             for i in 1..56 {
                 let idx = 1 + (i + 30) % 55;
                 seed_array[i] = seed_array[i].wrapping_sub(seed_array[idx]);
-                while seed_array[i] < 0 {
+                if seed_array[i] < 0 {
                     seed_array[i] = seed_array[i].wrapping_add(MAX_INT);
                 }
             }
@@ -111,6 +112,12 @@ impl CSRandom {
         self.sample_raw() as f64 * (1.0 / MAX_INT as f64)
     }
 
+    /// Returns a random float in [0, 1) (C# NextDouble)
+    #[wasm_bindgen]
+    pub fn next_double(&mut self) -> f64 {
+        self.sample()
+    }
+
     /// Returns a random integer based on parameters:
     /// - next() -> raw integer [0, MAX_INT)
     /// - next(max) -> integer in [0, max)
@@ -122,19 +129,8 @@ impl CSRandom {
                 // Return raw sample
                 self.sample_raw()
             }
-            (Some(max), None) => {
-                // [0, max)
-                (self.sample() * max as f64) as i32
-            }
-            (Some(min), Some(max)) => {
-                // [min, max)
-                let range = (max - min) as i64;
-                if range <= MAX_INT as i64 {
-                    (range as f64 * self.sample()) as i32 + min
-                } else {
-                    (range as f64 * self.sample_lr()) as i32 + min
-                }
-            }
+            (Some(max), None) => self.next_max(max),
+            (Some(min), Some(max)) => self.next_range(min, max),
             (None, Some(_)) => {
                 // Invalid - treat as raw sample
                 self.sample_raw()
@@ -143,19 +139,43 @@ impl CSRandom {
     }
 
     /// Convenience method: next with single max parameter
+    /// .NET throws for a negative max; here it is not checked and the result
+    /// falls in (max, 0] instead.
     #[wasm_bindgen]
     pub fn next_max(&mut self, max: i32) -> i32 {
         (self.sample() * max as f64) as i32
     }
 
-    /// Convenience method: next with min and max parameters
+    /// Convenience method: next with min and max parameters.
+    /// Ranges wider than MAX_INT use GetSampleForLargeRange (two raw calls).
+    /// .NET throws when min > max; here it is not checked and the result
+    /// falls in (max, min] instead.
     #[wasm_bindgen]
     pub fn next_range(&mut self, min: i32, max: i32) -> i32 {
-        let range = (max - min) as i64;
+        let range = max as i64 - min as i64;
         if range <= MAX_INT as i64 {
-            (range as f64 * self.sample()) as i32 + min
+            (self.sample() * range as f64) as i32 + min
         } else {
-            (range as f64 * self.sample_lr()) as i32 + min
+            ((self.sample_lr() * range as f64) as i64 + min as i64) as i32
+        }
+    }
+
+    /// Fill a buffer with random bytes (C# NextBytes)
+    #[wasm_bindgen]
+    pub fn next_bytes(&mut self, buffer: &mut [u8]) {
+        for byte in buffer.iter_mut() {
+            *byte = (self.sample_raw() % 256) as u8;
+        }
+    }
+}
+
+impl CSRandom {
+    /// Resume a generator from a known state (used by CSRandomLite's fallback)
+    pub(crate) fn from_state(seed_array: [i32; 56], inext: usize, inextp: usize) -> Self {
+        Self {
+            seed_array,
+            inext,
+            inextp,
         }
     }
 }
@@ -227,4 +247,16 @@ mod tests {
             assert!(val >= 5 && val < 15, "Value {} out of range [5, 15)", val);
         }
     }
+
+    #[test]
+    fn test_csrandom_reversed_bounds_do_not_panic() {
+        let mut rng = CSRandom::new(12345);
+        for _ in 0..100 {
+            let val = rng.next_max(-10);
+            assert!((-9..=0).contains(&val), "next_max(-10) gave {}", val);
+            let val = rng.next_range(15, 5);
+            assert!((6..=15).contains(&val), "next_range(15, 5) gave {}", val);
+            rng.next_range(i32::MAX, i32::MIN);
+        }
+    }
 }
@@ -1,5 +1,6 @@
 mod cs_random;
 mod cs_random_lite;
+pub mod seeding;
 #[cfg(test)]
 mod validation_tests;
 
@@ -13,6 +14,7 @@ pub const MSEED: i32 = 0x09A4EC86; // 161803398
 
 /// Simulates 32-bit signed integer overflow behavior (matching C#)
 #[inline]
+#[allow(dead_code)] // No longer needed by CSRandomLite's seeding
 pub fn int_overflow(val: i64) -> i32 {
     // Wrap to 32-bit signed range [-2147483648, 2147483647]
     let max = MAX_INT as i64;
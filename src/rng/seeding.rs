@@ -0,0 +1,92 @@
+//! 1.6 seed derivation, mirroring `StardewValley.Utility`.
+//!
+//! 1.6 seeds almost every Random through `Utility.CreateRandomSeed`: up to
+//! five doubles, each reduced modulo `int.MaxValue`, hashed with XXHash32.
+//! With the "use legacy randomization" save option the values are summed
+//! instead, like pre-1.4 seeding.
+
+use xxhash_rust::xxh32::xxh32;
+
+use super::{CSRandomLite, MAX_INT};
+
+const M: f64 = MAX_INT as f64;
+
+/// `Utility.CreateRandomSeed(seedA, seedB, seedC, seedD, seedE)`.
+///
+/// Hashes the five values as little-endian i32s on the stack, so it is safe
+/// to call in search hot loops.
+#[inline]
+pub fn create_random_seed(a: f64, b: f64, c: f64, d: f64, e: f64, legacy_random: bool) -> i32 {
+    if legacy_random {
+        return ((a % M + b % M + c % M + d % M + e % M) % M) as i32;
+    }
+
+    let mut bytes = [0u8; 20];
+    for (chunk, value) in bytes.chunks_exact_mut(4).zip([a, b, c, d, e]) {
+        chunk.copy_from_slice(&((value % M) as i32).to_le_bytes());
+    }
+    xxh32(&bytes, 0) as i32
+}
+
+/// `Utility.CreateRandom(seedA, seedB, seedC, seedD, seedE)`.
+#[inline]
+pub fn create_random(a: f64, b: f64, c: f64, d: f64, e: f64, legacy_random: bool) -> CSRandomLite {
+    CSRandomLite::new(create_random_seed(a, b, c, d, e, legacy_random))
+}
+
+/// `Utility.CreateDaySaveRandom(seedA, seedB, seedC)`: seeded with the days
+/// played and half the game ID ahead of the three extra values.
+#[inline]
+pub fn create_day_save_random(
+    days_played: i32,
+    game_id: i32,
+    a: f64,
+    b: f64,
+    c: f64,
+    legacy_random: bool,
+) -> CSRandomLite {
+    create_random(days_played as f64, (game_id / 2) as f64, a, b, c, legacy_random)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash_ints(values: [i32; 5]) -> i32 {
+        let bytes: Vec<u8> = values.iter().flat_map(|&v| v.to_le_bytes()).collect();
+        xxh32(&bytes, 0) as i32
+    }
+
+    #[test]
+    fn test_hash_matches_int_array() {
+        assert_eq!(
+            create_random_seed(21.0, 6172839.0, 0.0, 0.0, 0.0, false),
+            hash_ints([21, 6172839, 0, 0, 0])
+        );
+        assert_eq!(
+            create_random_seed(-5.0, 1.0, 2.0, 3.0, 4.0, false),
+            hash_ints([-5, 1, 2, 3, 4])
+        );
+        // Each value is reduced modulo int.MaxValue before hashing
+        assert_eq!(
+            create_random_seed(MAX_INT as f64 + 7.0, 0.0, 0.0, 0.0, 0.0, false),
+            hash_ints([7, 0, 0, 0, 0])
+        );
+    }
+
+    #[test]
+    fn test_legacy_sums_values() {
+        assert_eq!(create_random_seed(21.0, 6172839.0, 0.0, 0.0, 0.0, true), 6172860);
+        assert_eq!(
+            create_random_seed(MAX_INT as f64 - 1.0, 5.0, 0.0, 0.0, 0.0, true),
+            4
+        );
+    }
+
+    #[test]
+    fn test_day_save_random_uses_half_game_id() {
+        let mut a = create_day_save_random(10, 12345, 0.0, 0.0, 0.0, false);
+        let mut b = CSRandomLite::new(hash_ints([10, 6172, 0, 0, 0]));
+        assert_eq!(a.sample(), b.sample());
+    }
+}
@@ -182,8 +182,7 @@ mod tests {
 
     #[test]
     fn validate_csrandom_lite_sample_accuracy() {
-        // CSRandomLite should produce values very close to CSRandom
-        // Small differences are expected due to different computation methods
+        // CSRandomLite is exact, so it meets the same tolerance as CSRandom
         for (seed, expected_samples) in CSRANDOM_TEST_VECTORS {
             let mut rng = CSRandomLite::new(*seed);
 
@@ -191,9 +190,8 @@ mod tests {
                 let actual = rng.sample();
                 let diff = (actual - expected).abs();
 
-                // Lite version has slightly lower precision due to precomputed coefficients
                 assert!(
-                    diff < 1e-4,
+                    diff < 1e-8,
                     "CSRandomLite({}): sample {} too far from CSRandom\n  expected: {}\n  actual:   {}\n  diff:     {}",
                     seed,
                     i,
@@ -294,7 +292,7 @@ mod tests {
 
     #[test]
     fn validate_lite_vs_full_many_seeds() {
-        // Test many seeds to ensure CSRandomLite approximation is acceptable
+        // CSRandomLite is exact, so every sample must be identical
         for seed in (-1000..1000).step_by(17) {
             let mut lite = CSRandomLite::new(seed);
             let mut full = CSRandom::new(seed);
@@ -302,19 +300,111 @@ mod tests {
             for call in 0..20 {
                 let lite_val = lite.sample();
                 let full_val = full.sample();
-                let diff = (lite_val - full_val).abs();
-
-                // Allow small precision differences
-                assert!(
-                    diff < 1e-4,
-                    "Seed {}, call {}: diff {} too large\n  lite: {}\n  full: {}",
-                    seed,
-                    call,
-                    diff,
-                    lite_val,
-                    full_val
-                );
+                assert_eq!(lite_val, full_val, "Seed {}, call {}", seed, call);
             }
         }
     }
+
+    /// Vectors for the full System.Random API. NOT yet checked against real .NET:
+    /// they come from a port of the .NET Framework reference source, so they pin
+    /// CSRandom's current behavior and would not catch a bug shared with that port.
+    /// Replace the table with the output of
+    /// `dotnet run scripts/generate-random-vectors.cs` before trusting it as a
+    /// reference. Each seed is drawn in this order:
+    /// Next() x3, Next(100) x3, Next(-50, 50) x3, Next(MIN_INT, MAX_INT) x3,
+    /// Next(-2, MAX_INT) x2, NextDouble() x2, NextBytes(8).
+    #[allow(clippy::type_complexity)]
+    const SYSTEM_RANDOM_API_VECTORS: &[(i32, [i32; 3], [i32; 3], [i32; 3], [i32; 3], [i32; 2], [f64; 2], [u8; 8])] = &[
+        (
+            0,
+            [1559595546, 1755192844, 1649316166],
+            [55, 20, 55],
+            [40, -6, 47],
+            [587775846, 1003550676, 1008269080],
+            [1106348130, 4996031],
+            [0.3145917930242567, 0.8169079086822029],
+            [248, 133, 0, 236, 94, 100, 0, 83],
+        ),
+        (
+            1,
+            [534011718, 237820880, 1002897798],
+            [77, 65, 43],
+            [-15, 44, -40],
+            [1379662798, 532638533, 2125508763],
+            [1776922337, 413018646],
+            [0.7017966754277222, 0.9497784469042805],
+            [91, 78, 163, 111, 132, 165, 74, 223],
+        ),
+        (
+            12345,
+            [143337951, 150666398, 1663795458],
+            [51, 79, 82],
+            [-34, 23, -24],
+            [1086637142, 831377770, -44691037],
+            [278279804, 121290340],
+            [0.3588650638046046, 0.10370226022959791],
+            [159, 52, 35, 209, 159, 26, 34, 209],
+        ),
+        (
+            -638161535,
+            [326498284, 464234277, 1541382334],
+            [77, 92, 30],
+            [30, -48, -9],
+            [-1670759565, 429154253, 1485814538],
+            [162585208, 1339227002],
+            [0.42868175656939006, 0.4449850909621385],
+            [114, 142, 181, 94, 58, 25, 102, 165],
+        ),
+        (
+            i32::MIN,
+            [1559595546, 1755192844, 1649316172],
+            [55, 20, 55],
+            [40, -6, 47],
+            [587775834, 1003550676, 1008269080],
+            [1106348130, 4996031],
+            [0.3145917790544181, 0.8169079086822029],
+            [248, 133, 248, 236, 94, 82, 0, 83],
+        ),
+    ];
+
+    /// Run the golden draw order against any generator exposing the API.
+    macro_rules! check_system_random_api {
+        ($rng_type:ty) => {
+            for &(seed, next, next_max, small, large, wide, doubles, bytes) in SYSTEM_RANDOM_API_VECTORS {
+                let mut rng = <$rng_type>::new(seed);
+                let name = stringify!($rng_type);
+                for expected in next {
+                    assert_eq!(rng.next(None, None), expected, "{}({}) Next()", name, seed);
+                }
+                for expected in next_max {
+                    assert_eq!(rng.next(Some(100), None), expected, "{}({}) Next(100)", name, seed);
+                }
+                for expected in small {
+                    assert_eq!(rng.next_range(-50, 50), expected, "{}({}) Next(-50, 50)", name, seed);
+                }
+                for expected in large {
+                    assert_eq!(rng.next_range(i32::MIN, i32::MAX), expected, "{}({}) large range", name, seed);
+                }
+                for expected in wide {
+                    assert_eq!(rng.next(Some(-2), Some(i32::MAX)), expected, "{}({}) Next(-2, MAX)", name, seed);
+                }
+                for expected in doubles {
+                    assert_eq!(rng.next_double(), expected, "{}({}) NextDouble()", name, seed);
+                }
+                let mut buffer = [0u8; 8];
+                rng.next_bytes(&mut buffer);
+                assert_eq!(buffer, bytes, "{}({}) NextBytes", name, seed);
+            }
+        };
+    }
+
+    #[test]
+    fn validate_csrandom_full_api() {
+        check_system_random_api!(CSRandom);
+    }
+
+    #[test]
+    fn validate_csrandom_lite_full_api() {
+        check_system_random_api!(CSRandomLite);
+    }
 }
@@ -0,0 +1,69 @@
+//! Quotient blocks for seed-range scans.
+//!
+//! Many mechanics only see `seed / 100` or `seed / 2`, so every seed in a
+//! run sharing the same quotient gives the same result. Scans can test one
+//! representative per run and skip or accept the whole run at once.
+
+/// Split `[start, end]` into maximal inclusive runs where `seed / divisor`
+/// is constant.
+///
+/// Rust division truncates toward zero, so the run around zero is
+/// `[-(divisor - 1), divisor - 1]` and negative runs end at a multiple of
+/// `divisor`.
+pub(crate) fn quotient_blocks(start: i32, end: i32, divisor: i32) -> impl Iterator<Item = (i32, i32)> {
+    let mut next = start as i64;
+    let end = end as i64;
+    let divisor = divisor as i64;
+
+    std::iter::from_fn(move || {
+        if next > end {
+            return None;
+        }
+        let q = next / divisor;
+        let block_end = if q >= 0 && next >= 0 {
+            q * divisor + divisor - 1
+        } else if q == 0 {
+            divisor - 1
+        } else {
+            q * divisor
+        };
+        let block = (next as i32, block_end.min(end) as i32);
+        next = block_end + 1;
+        Some(block)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocks_share_quotient() {
+        for divisor in [2, 100] {
+            let blocks: Vec<_> = quotient_blocks(-1000, 1000, divisor).collect();
+            let mut expected_start = -1000;
+            for &(start, end) in &blocks {
+                assert_eq!(start, expected_start, "blocks must be contiguous");
+                assert!(start <= end);
+                assert!((start..=end).all(|s| s / divisor == start / divisor));
+                // Maximal: the next seed has a different quotient
+                if end < 1000 {
+                    assert_ne!((end + 1) / divisor, end / divisor);
+                }
+                expected_start = end + 1;
+            }
+            assert_eq!(expected_start, 1001);
+        }
+        // The zero run is wider than the others
+        assert!(quotient_blocks(-1000, 1000, 100).any(|b| b == (-99, 99)));
+    }
+
+    #[test]
+    fn test_blocks_at_i32_limits() {
+        let last: Vec<_> = quotient_blocks(i32::MAX - 150, i32::MAX, 100).collect();
+        assert_eq!(last.last().unwrap().1, i32::MAX);
+        let first: Vec<_> = quotient_blocks(i32::MIN, i32::MIN + 150, 100).collect();
+        assert_eq!(first[0].0, i32::MIN);
+        assert_eq!(first[0].1, i32::MIN / 100 * 100);
+    }
+}
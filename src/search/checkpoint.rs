@@ -0,0 +1,418 @@
+//! Resumable searches.
+//!
+//! A checkpoint records which seeds of a search range have been checked and
+//! which of them matched, for one filter, version and player context. It
+//! serializes to compact JSON: finished seeds are kept as merged inclusive
+//! ranges, so a mostly finished search stays small.
+
+use serde::{Deserialize, Serialize};
+use xxhash_rust::xxh64::xxh64;
+
+use super::plan::FilterPlan;
+use crate::types::PlayerContext;
+use crate::version::GameVersion;
+
+/// Current checkpoint file format.
+pub const CHECKPOINT_FORMAT: u32 = 1;
+
+/// Progress of a search over `[start_seed, end_seed]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub format: u32,
+    /// Hash of the filter JSON, ignoring whitespace and key order
+    pub filter_hash: String,
+    /// Game version the search runs against (e.g. "1.6")
+    pub version: String,
+    pub context: PlayerContext,
+    pub start_seed: i32,
+    pub end_seed: i32,
+    /// Checked seeds as sorted, non-adjacent inclusive ranges
+    pub completed: Vec<(i32, i32)>,
+    /// Matching seeds found so far, ascending
+    pub matches: Vec<i32>,
+}
+
+/// What `Checkpoint::scan_into` wrote.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CheckpointBatch {
+    /// Matches written to the front of the buffer
+    pub count: u32,
+    /// Whether the buffer filled up before the unchecked seeds ran out
+    pub stopped_early: bool,
+}
+
+/// Why a checkpoint can't be used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckpointError {
+    /// Not valid checkpoint JSON (or the filter isn't valid JSON)
+    Malformed(String),
+    /// Written by a newer or older format
+    UnsupportedFormat(u32),
+    /// Made with a different filter
+    FilterMismatch,
+    /// Made for a different game version
+    VersionMismatch { checkpoint: String, requested: String },
+    /// Made with a different player context
+    ContextMismatch,
+    /// Covers a different seed range
+    RangeMismatch,
+}
+
+impl std::fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Malformed(e) => write!(f, "Checkpoint parse error: {}", e),
+            Self::UnsupportedFormat(format) => write!(
+                f,
+                "Unsupported checkpoint format {} (expected {})",
+                format, CHECKPOINT_FORMAT
+            ),
+            Self::FilterMismatch => write!(f, "Checkpoint was made with a different filter"),
+            Self::VersionMismatch {
+                checkpoint,
+                requested,
+            } => write!(
+                f,
+                "Checkpoint is for game version {}, not {}",
+                checkpoint, requested
+            ),
+            Self::ContextMismatch => write!(f, "Checkpoint was made with a different player context"),
+            Self::RangeMismatch => write!(f, "Checkpoint covers a different seed range"),
+        }
+    }
+}
+
+impl std::error::Error for CheckpointError {}
+
+/// Hash a filter's JSON so formatting and key order don't matter.
+pub fn filter_hash(filter_json: &str) -> Result<String, CheckpointError> {
+    // serde_json's map is sorted, so re-serializing gives a canonical form
+    let value: serde_json::Value =
+        serde_json::from_str(filter_json).map_err(|e| CheckpointError::Malformed(e.to_string()))?;
+    Ok(format!("{:016x}", xxh64(value.to_string().as_bytes(), 0)))
+}
+
+impl Checkpoint {
+    /// Start a search with nothing checked yet.
+    pub fn new(
+        filter_json: &str,
+        version: GameVersion,
+        ctx: &PlayerContext,
+        start_seed: i32,
+        end_seed: i32,
+    ) -> Result<Self, CheckpointError> {
+        Ok(Self {
+            format: CHECKPOINT_FORMAT,
+            filter_hash: filter_hash(filter_json)?,
+            version: version.to_string(),
+            context: *ctx,
+            start_seed,
+            end_seed,
+            completed: Vec::new(),
+            matches: Vec::new(),
+        })
+    }
+
+    /// Parse a checkpoint and check it belongs to this search.
+    pub fn resume(
+        checkpoint_json: &str,
+        filter_json: &str,
+        version: GameVersion,
+        ctx: &PlayerContext,
+    ) -> Result<Self, CheckpointError> {
+        let checkpoint: Self = serde_json::from_str(checkpoint_json)
+            .map_err(|e| CheckpointError::Malformed(e.to_string()))?;
+        checkpoint.verify(filter_json, version, ctx)?;
+        Ok(checkpoint)
+    }
+
+    /// Refuse a checkpoint made for a different search.
+    pub fn verify(
+        &self,
+        filter_json: &str,
+        version: GameVersion,
+        ctx: &PlayerContext,
+    ) -> Result<(), CheckpointError> {
+        if self.format != CHECKPOINT_FORMAT {
+            return Err(CheckpointError::UnsupportedFormat(self.format));
+        }
+        if self.filter_hash != filter_hash(filter_json)? {
+            return Err(CheckpointError::FilterMismatch);
+        }
+        if self.version != version.to_string() {
+            return Err(CheckpointError::VersionMismatch {
+                checkpoint: self.version.clone(),
+                requested: version.to_string(),
+            });
+        }
+        if self.context != *ctx {
+            return Err(CheckpointError::ContextMismatch);
+        }
+        Ok(())
+    }
+
+    /// Scan the unchecked seeds of `[start, end]` into `out` and record them.
+    ///
+    /// The buffer length is the match limit. If it fills up, only seeds up to
+    /// the last match are recorded, so calling again with the same range
+    /// carries on from there.
+    pub fn scan_into(
+        &mut self,
+        plan: &mut FilterPlan,
+        start: i32,
+        end: i32,
+        ctx: &PlayerContext,
+        out: &mut [i32],
+    ) -> CheckpointBatch {
+        let mut count = 0;
+        for (start, end) in self.remaining_within(start, end) {
+            let batch = plan.scan_into(start, end, ctx, &mut out[count..]);
+            let found = count..count + batch.count as usize;
+            self.record(start, (batch.next_seed - 1) as i32, &out[found.clone()]);
+            count = found.end;
+            if batch.stopped_early {
+                return CheckpointBatch {
+                    count: count as u32,
+                    stopped_early: true,
+                };
+            }
+        }
+        CheckpointBatch {
+            count: count as u32,
+            stopped_early: false,
+        }
+    }
+
+    /// Mark `[start, end]` as checked, with the matches found in it.
+    /// Seeds outside the checkpoint's range are ignored.
+    pub fn record(&mut self, start: i32, end: i32, matches: &[i32]) {
+        let (start, end) = (start.max(self.start_seed), end.min(self.end_seed));
+        if start > end {
+            return;
+        }
+        self.completed.push((start, end));
+        self.completed = merge_ranges(std::mem::take(&mut self.completed));
+
+        self.matches
+            .extend(matches.iter().filter(|&&seed| (start..=end).contains(&seed)));
+        self.matches.sort_unstable();
+        self.matches.dedup();
+    }
+
+    /// Combine progress from another worker on the same search.
+    pub fn merge(&mut self, other: &Checkpoint) -> Result<(), CheckpointError> {
+        if other.format != self.format {
+            return Err(CheckpointError::UnsupportedFormat(other.format));
+        }
+        if other.filter_hash != self.filter_hash {
+            return Err(CheckpointError::FilterMismatch);
+        }
+        if other.version != self.version {
+            return Err(CheckpointError::VersionMismatch {
+                checkpoint: other.version.clone(),
+                requested: self.version.clone(),
+            });
+        }
+        if other.context != self.context {
+            return Err(CheckpointError::ContextMismatch);
+        }
+        if (other.start_seed, other.end_seed) != (self.start_seed, self.end_seed) {
+            return Err(CheckpointError::RangeMismatch);
+        }
+
+        self.completed.extend_from_slice(&other.completed);
+        self.completed = merge_ranges(std::mem::take(&mut self.completed));
+        self.matches.extend_from_slice(&other.matches);
+        self.matches.sort_unstable();
+        self.matches.dedup();
+        Ok(())
+    }
+
+    /// Unchecked parts of the range, in order.
+    pub fn remaining(&self) -> Vec<(i32, i32)> {
+        let mut gaps = Vec::new();
+        let mut next = self.start_seed as i64;
+        for &(start, end) in &self.completed {
+            if (start as i64) > next {
+                gaps.push((next as i32, start - 1));
+            }
+            next = next.max(end as i64 + 1);
+        }
+        if next <= self.end_seed as i64 {
+            gaps.push((next as i32, self.end_seed));
+        }
+        gaps
+    }
+
+    /// Refuse a search range that reaches outside the checkpoint's own.
+    pub fn verify_range(&self, start: i32, end: i32) -> Result<(), CheckpointError> {
+        if start <= end && (start < self.start_seed || end > self.end_seed) {
+            return Err(CheckpointError::RangeMismatch);
+        }
+        Ok(())
+    }
+
+    /// Unchecked parts of `[start, end]`, in order.
+    pub fn remaining_within(&self, start: i32, end: i32) -> Vec<(i32, i32)> {
+        self.remaining()
+            .into_iter()
+            .filter_map(|(a, b)| {
+                let (a, b) = (a.max(start), b.min(end));
+                (a <= b).then_some((a, b))
+            })
+            .collect()
+    }
+
+    /// Whether every seed in the range has been checked.
+    pub fn is_complete(&self) -> bool {
+        self.remaining().is_empty()
+    }
+
+    /// Number of seeds checked so far.
+    pub fn seeds_checked(&self) -> u64 {
+        self.completed
+            .iter()
+            .map(|&(start, end)| (end as i64 - start as i64 + 1) as u64)
+            .sum()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("checkpoints always serialize")
+    }
+}
+
+/// Sort ranges and join overlapping or adjacent ones.
+fn merge_ranges(mut ranges: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
+    ranges.sort_unstable();
+    let mut merged: Vec<(i32, i32)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start as i64 <= last.1 as i64 + 1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILTER: &str = r#"{"logic": "condition", "type": "dish_of_day", "days": {"type": "exact", "day": 1}, "dish_id": 200}"#;
+
+    fn checkpoint() -> Checkpoint {
+        Checkpoint::new(FILTER, GameVersion::V1_6, &PlayerContext::default(), 0, 999).unwrap()
+    }
+
+    #[test]
+    fn test_record_and_remaining() {
+        let mut cp = checkpoint();
+        cp.record(500, 599, &[510, 550]);
+        cp.record(0, 99, &[7]);
+        cp.record(100, 199, &[]);
+        assert_eq!(cp.completed, vec![(0, 199), (500, 599)]);
+        assert_eq!(cp.remaining(), vec![(200, 499), (600, 999)]);
+        assert_eq!(cp.remaining_within(150, 650), vec![(200, 499), (600, 650)]);
+        assert_eq!(cp.matches, vec![7, 510, 550]);
+        assert_eq!(cp.seeds_checked(), 300);
+
+        cp.record(-50, 2000, &[]);
+        assert!(cp.is_complete());
+        assert_eq!(cp.completed, vec![(0, 999)]);
+    }
+
+    #[test]
+    fn test_scan_into_skips_and_records() {
+        let json = r#"{"logic": "condition", "type": "night_event", "days": {"type": "range", "start": 1, "end": 28}, "event_type": "any"}"#;
+        let filter: crate::search::FilterNode = serde_json::from_str(json).unwrap();
+        let mut plan = FilterPlan::compile(&filter, GameVersion::V1_6);
+        let ctx = PlayerContext::default();
+        let expected: Vec<i32> = (0..=999)
+            .filter(|&seed| crate::search::evaluate_filter(seed, &filter, GameVersion::V1_6, &ctx))
+            .collect();
+        assert!(expected.len() > 4, "{:?}", expected);
+
+        // Resume a checkpoint that already covers the middle of the range
+        let mut cp = Checkpoint::new(json, GameVersion::V1_6, &ctx, 0, 999).unwrap();
+        let skipped: Vec<i32> = expected.iter().copied().filter(|seed| (300..=599).contains(seed)).collect();
+        cp.record(300, 599, &skipped);
+
+        let mut out = [0; 2];
+        let mut found = Vec::new();
+        loop {
+            let batch = cp.scan_into(&mut plan, 0, 999, &ctx, &mut out);
+            found.extend_from_slice(&out[..batch.count as usize]);
+            if !batch.stopped_early {
+                break;
+            }
+            assert_eq!(batch.count, 2);
+        }
+        let fresh: Vec<i32> = expected.iter().copied().filter(|seed| !(300..=599).contains(seed)).collect();
+        assert_eq!(found, fresh);
+        assert!(cp.is_complete());
+        assert_eq!(cp.matches, expected);
+    }
+
+    #[test]
+    fn test_round_trip_and_resume() {
+        let mut cp = checkpoint();
+        cp.record(0, 99, &[42]);
+        let json = cp.to_json();
+
+        // Whitespace and key order in the filter don't matter
+        let reformatted = r#"{"type":"dish_of_day","dish_id":200,"logic":"condition","days":{"day":1,"type":"exact"}}"#;
+        let ctx = PlayerContext::default();
+        let resumed = Checkpoint::resume(&json, reformatted, GameVersion::V1_6, &ctx).unwrap();
+        assert_eq!(resumed, cp);
+    }
+
+    #[test]
+    fn test_refuses_other_searches() {
+        let json = checkpoint().to_json();
+        let ctx = PlayerContext::default();
+        let other_filter = FILTER.replace("200", "201");
+        assert_eq!(
+            Checkpoint::resume(&json, &other_filter, GameVersion::V1_6, &ctx),
+            Err(CheckpointError::FilterMismatch)
+        );
+        assert!(matches!(
+            Checkpoint::resume(&json, FILTER, GameVersion::V1_5, &ctx),
+            Err(CheckpointError::VersionMismatch { .. })
+        ));
+        let stepped = PlayerContext {
+            steps: 10,
+            ..PlayerContext::default()
+        };
+        assert_eq!(
+            Checkpoint::resume(&json, FILTER, GameVersion::V1_6, &stepped),
+            Err(CheckpointError::ContextMismatch)
+        );
+        assert!(matches!(
+            Checkpoint::resume("{}", FILTER, GameVersion::V1_6, &ctx),
+            Err(CheckpointError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_merge_worker_progress() {
+        let (mut a, mut b) = (checkpoint(), checkpoint());
+        a.record(0, 499, &[3]);
+        b.record(500, 999, &[700]);
+        a.merge(&b).unwrap();
+        assert!(a.is_complete());
+        assert_eq!(a.matches, vec![3, 700]);
+
+        let wider = Checkpoint::new(FILTER, GameVersion::V1_6, &PlayerContext::default(), 0, 5000).unwrap();
+        assert_eq!(a.merge(&wider), Err(CheckpointError::RangeMismatch));
+    }
+
+    #[test]
+    fn test_verify_range() {
+        let cp = checkpoint();
+        assert_eq!(cp.verify_range(0, 999), Ok(()));
+        assert_eq!(cp.verify_range(200, 300), Ok(()));
+        assert_eq!(cp.verify_range(-1, 500), Err(CheckpointError::RangeMismatch));
+        assert_eq!(cp.verify_range(900, 1000), Err(CheckpointError::RangeMismatch));
+        assert_eq!(cp.verify_range(5000, 6000), Err(CheckpointError::RangeMismatch));
+    }
+}
@@ -0,0 +1,237 @@
+//! Match-rate estimation.
+//!
+//! Before a long search it helps to know how rare a filter is. This evaluates
+//! the filter on a random sample of seeds, drawn from a fixed-seed PRNG so the
+//! same request always gives the same answer, and reports the match rate with
+//! a confidence interval, the expected wait for a first match at the measured
+//! speed, and how often each condition passes on its own.
+
+use instant::Instant;
+use serde::Serialize;
+
+use super::evaluate::evaluate_filter;
+use super::filter::FilterNode;
+use super::plan::FilterPlan;
+use crate::types::PlayerContext;
+use crate::version::GameVersion;
+
+/// State the sample PRNG starts from.
+const SAMPLE_SEED: u64 = 0x5EED_5A3B_1E0F_CAFE;
+
+/// Most seeds one estimate evaluates; the sample is held in memory.
+pub const MAX_SAMPLES: u32 = 1_000_000;
+
+/// z for a two-sided 95% interval.
+const Z_95: f64 = 1.959_963_984_540_054;
+
+/// How rare a filter is, from a sample of seeds.
+#[derive(Debug, Clone, Serialize)]
+pub struct Estimate {
+    pub samples: u32,
+    pub matches: u32,
+    pub match_rate: f64,
+    /// 95% Wilson score interval for the match rate. It stays informative
+    /// when nothing matched: the high end is then z² / (samples + z²),
+    /// about 3.84 / samples.
+    pub match_rate_low: f64,
+    pub match_rate_high: f64,
+    /// Seeds per second the compiled filter checked on this machine
+    /// (None if the sample was too quick to time)
+    pub seeds_per_second: Option<f64>,
+    /// Seeds a search checks before its first match, on average (None if nothing matched)
+    pub expected_seeds_to_match: Option<f64>,
+    /// Seconds until the first match at the measured speed
+    pub expected_seconds_to_match: Option<f64>,
+    /// Matches expected in the whole range
+    pub expected_matches_in_range: f64,
+    /// Pass rate of every condition, in filter order
+    pub conditions: Vec<ConditionRate>,
+}
+
+/// How often one condition passes on the sampled seeds, ignoring the rest of the filter.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConditionRate {
+    /// JSON pointer to the condition in the filter (e.g. "/conditions/1")
+    pub path: String,
+    /// Condition type, or "count_days"
+    pub node: &'static str,
+    pub passed: u32,
+    pub pass_rate: f64,
+}
+
+/// Estimate how often `filter` matches seeds in `[start_seed, end_seed]`
+/// from `samples` seeds drawn uniformly (with replacement) from the range.
+///
+/// # Panics
+/// If `samples` is zero or above `MAX_SAMPLES`, or the range is empty.
+pub fn estimate_in_range(
+    filter: &FilterNode,
+    start_seed: i32,
+    end_seed: i32,
+    samples: u32,
+    version: GameVersion,
+    ctx: &PlayerContext,
+) -> Estimate {
+    assert!(samples > 0, "need at least one sample");
+    assert!(samples <= MAX_SAMPLES, "too many samples");
+    assert!(start_seed <= end_seed, "empty seed range");
+
+    let width = end_seed as i64 - start_seed as i64 + 1;
+    let mut state = SAMPLE_SEED;
+    let seeds: Vec<i32> = (0..samples)
+        .map(|_| {
+            // Multiply-shift maps the top 32 bits onto the range without modulo bias
+            let offset = ((splitmix64(&mut state) >> 32) * width as u64) >> 32;
+            (start_seed as i64 + offset as i64) as i32
+        })
+        .collect();
+
+    // Time the compiled plan, which is what searches run
+    let mut plan = FilterPlan::compile(filter, version);
+    // `instant` falls back to performance.now() on wasm, where std's Instant panics
+    let started = Instant::now();
+    let matches = seeds.iter().filter(|&&seed| plan.matches(seed, ctx)).count() as u32;
+    let elapsed = started.elapsed().as_secs_f64();
+
+    let mut leaves = Vec::new();
+    collect_leaves(filter, String::new(), &mut leaves);
+    let conditions = leaves
+        .into_iter()
+        .map(|(path, node)| {
+            let passed = seeds
+                .iter()
+                .filter(|&&seed| evaluate_filter(seed, node, version, ctx))
+                .count() as u32;
+            ConditionRate {
+                path,
+                node: match node {
+                    FilterNode::Condition(cond) => cond.type_name(),
+                    _ => "count_days",
+                },
+                passed,
+                pass_rate: passed as f64 / samples as f64,
+            }
+        })
+        .collect();
+
+    let n = samples as f64;
+    let match_rate = matches as f64 / n;
+    let (match_rate_low, match_rate_high) = wilson_interval(matches as f64, n);
+    let seeds_per_second = (elapsed > 0.0).then(|| n / elapsed);
+    let expected_seeds_to_match = (matches > 0).then(|| 1.0 / match_rate);
+    let expected_seconds_to_match = expected_seeds_to_match
+        .zip(seeds_per_second)
+        .map(|(seeds, speed)| seeds / speed);
+
+    Estimate {
+        samples,
+        matches,
+        match_rate,
+        match_rate_low,
+        match_rate_high,
+        seeds_per_second,
+        expected_seeds_to_match,
+        expected_seconds_to_match,
+        expected_matches_in_range: match_rate * width as f64,
+        conditions,
+    }
+}
+
+/// Conditions (and `count_days` nodes) of a filter with their JSON pointers.
+fn collect_leaves<'a>(node: &'a FilterNode, path: String, out: &mut Vec<(String, &'a FilterNode)>) {
+    match node {
+        FilterNode::And { conditions }
+        | FilterNode::Or { conditions }
+        | FilterNode::Xor { conditions }
+        | FilterNode::AtLeast { conditions, .. } => {
+            for (i, child) in conditions.iter().enumerate() {
+                collect_leaves(child, format!("{}/conditions/{}", path, i), out);
+            }
+        }
+        FilterNode::Not { condition } => collect_leaves(condition, format!("{}/condition", path), out),
+        FilterNode::CountDays { .. } | FilterNode::Condition(_) => out.push((path, node)),
+    }
+}
+
+/// Wilson score interval for `successes` out of `n` trials.
+fn wilson_interval(successes: f64, n: f64) -> (f64, f64) {
+    let p = successes / n;
+    let z2 = Z_95 * Z_95;
+    let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let half = Z_95 / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    ((center - half).max(0.0), (center + half).min(1.0))
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> FilterNode {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_estimate_brackets_true_rate() {
+        let filter = parse(
+            r#"{"logic": "condition", "type": "daily_luck", "days": {"type": "range", "start": 1, "end": 3},
+                "min_luck": 0.07, "max_luck": 0.1}"#,
+        );
+        let ctx = PlayerContext::default();
+        let truth = (0..5000)
+            .filter(|&seed| evaluate_filter(seed, &filter, GameVersion::V1_6, &ctx))
+            .count() as f64
+            / 5000.0;
+
+        let estimate = estimate_in_range(&filter, 0, 4999, 20_000, GameVersion::V1_6, &ctx);
+        assert!(estimate.matches > 0);
+        assert!(estimate.match_rate_low <= truth && truth <= estimate.match_rate_high);
+        assert!((estimate.expected_matches_in_range - truth * 5000.0).abs() < 5000.0 * 0.02);
+        assert_eq!(estimate.expected_seeds_to_match, Some(1.0 / estimate.match_rate));
+
+        // Same request, same sample
+        let again = estimate_in_range(&filter, 0, 4999, 20_000, GameVersion::V1_6, &ctx);
+        assert_eq!(again.matches, estimate.matches);
+    }
+
+    #[test]
+    fn test_condition_rates_find_impossible_conditions() {
+        let filter = parse(
+            r#"{"logic": "and", "conditions": [
+                {"logic": "condition", "type": "dish_of_day", "days": {"type": "range", "start": 1, "end": 7}, "dish_id": 200},
+                {"logic": "not", "condition": {"logic": "count_days",
+                    "condition": {"type": "weather", "days": {"type": "range", "start": 1, "end": 28}, "weather_type": "rain"},
+                    "at_least": 1}},
+                {"logic": "condition", "type": "daily_luck", "days": {"type": "exact", "day": 1}, "min_luck": 0.5, "max_luck": 1.0}
+            ]}"#,
+        );
+        let ctx = PlayerContext::default();
+        let estimate = estimate_in_range(&filter, i32::MIN, i32::MAX, 2000, GameVersion::V1_6, &ctx);
+        assert_eq!(estimate.matches, 0);
+        assert_eq!(estimate.expected_seeds_to_match, None);
+        assert_eq!(estimate.expected_seconds_to_match, None);
+        // With no matches the high end is z² / (n + z²), about 3.84 / n
+        let z2 = Z_95 * Z_95;
+        assert!((estimate.match_rate_high - z2 / (2000.0 + z2)).abs() < 1e-12);
+        assert!((estimate.match_rate_high * 2000.0 - 3.84).abs() < 0.01);
+
+        let summary: Vec<_> = estimate.conditions.iter().map(|c| (c.path.as_str(), c.node)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("/conditions/0", "dish_of_day"),
+                ("/conditions/1/condition", "count_days"),
+                ("/conditions/2", "daily_luck"),
+            ]
+        );
+        assert!(estimate.conditions[0].pass_rate > 0.0);
+        assert_eq!(estimate.conditions[2].passed, 0);
+    }
+}
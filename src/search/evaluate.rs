@@ -3,72 +3,150 @@
 //! All filter conditions are evaluated entirely in Rust for performance.
 
 use super::filter::{FilterCondition, FilterNode};
+use super::sequence::Timeline;
 use crate::mechanics;
+use crate::types::PlayerContext;
 use crate::version::GameVersion;
 
 /// Evaluate a filter tree against a seed.
-pub fn evaluate_filter(seed: i32, filter: &FilterNode, version: GameVersion) -> bool {
+pub fn evaluate_filter(
+    seed: i32,
+    filter: &FilterNode,
+    version: GameVersion,
+    ctx: &PlayerContext,
+) -> bool {
     match filter {
         FilterNode::And { conditions } => {
-            conditions.iter().all(|c| evaluate_filter(seed, c, version))
+            conditions.iter().all(|c| evaluate_filter(seed, c, version, ctx))
         }
         FilterNode::Or { conditions } => {
-            conditions.iter().any(|c| evaluate_filter(seed, c, version))
+            conditions.iter().any(|c| evaluate_filter(seed, c, version, ctx))
+        }
+        FilterNode::Not { condition } => !evaluate_filter(seed, condition, version, ctx),
+        FilterNode::Xor { conditions } => {
+            // Exactly one: stop as soon as a second child matches
+            let mut matched = conditions
+                .iter()
+                .filter(|c| evaluate_filter(seed, c, version, ctx));
+            matched.next().is_some() && matched.next().is_none()
         }
-        FilterNode::Condition(cond) => evaluate_condition(seed, cond, version),
+        FilterNode::AtLeast { count, conditions } => {
+            *count == 0
+                || conditions
+                    .iter()
+                    .filter(|c| evaluate_filter(seed, c, version, ctx))
+                    .nth(*count as usize - 1)
+                    .is_some()
+        }
+        FilterNode::CountDays {
+            condition,
+            at_least,
+            at_most,
+        } => count_days_in_bounds(seed, condition, *at_least, *at_most, version, ctx),
+        FilterNode::Condition(cond) => evaluate_condition(seed, cond, version, ctx),
+    }
+}
+
+/// Evaluate a single condition against a seed: true if any of its days match.
+pub(super) fn evaluate_condition(
+    seed: i32,
+    cond: &FilterCondition,
+    version: GameVersion,
+    ctx: &PlayerContext,
+) -> bool {
+    let mut found = false;
+    visit_days(seed, cond, version, ctx, |hit| {
+        found = hit;
+        !hit
+    });
+    found
+}
+
+/// Whether the number of matching days is within [at_least, at_most].
+/// With neither bound set, every day has to match.
+pub(super) fn count_days_in_bounds(
+    seed: i32,
+    cond: &FilterCondition,
+    at_least: Option<u32>,
+    at_most: Option<u32>,
+    version: GameVersion,
+    ctx: &PlayerContext,
+) -> bool {
+    if at_least.is_none() && at_most.is_none() {
+        let mut all = true;
+        visit_days(seed, cond, version, ctx, |hit| {
+            all = hit;
+            hit
+        });
+        return all;
     }
+
+    let at_least = at_least.unwrap_or(0);
+    let mut hits = 0u32;
+    visit_days(seed, cond, version, ctx, |hit| {
+        hits += hit as u32;
+        // Stop once more days can't change the answer
+        match at_most {
+            Some(max) => hits <= max,
+            None => hits < at_least,
+        }
+    });
+    hits >= at_least && at_most.is_none_or(|max| hits <= max)
 }
 
-/// Evaluate a single condition against a seed.
-fn evaluate_condition(seed: i32, cond: &FilterCondition, version: GameVersion) -> bool {
+/// Call `visit` with whether the condition holds on each of its days, in
+/// order, until it returns false. Cart conditions only visit cart days, and
+/// conditions without days (geodes) are visited once.
+pub(super) fn visit_days(
+    seed: i32,
+    cond: &FilterCondition,
+    version: GameVersion,
+    ctx: &PlayerContext,
+    mut visit: impl FnMut(bool) -> bool,
+) {
     match cond {
         FilterCondition::DailyLuck {
-            day_start,
-            day_end,
+            days,
             min_luck,
             max_luck,
         } => {
-            for day in *day_start..=*day_end {
-                let luck = mechanics::daily_luck(seed, day, 0, false);
-                if luck >= *min_luck && luck <= *max_luck {
-                    return true;
+            for day in days.days() {
+                let luck = mechanics::daily_luck(seed, day, ctx.steps, ctx.friendship_rolls());
+                if !visit(luck >= *min_luck && luck <= *max_luck) {
+                    return;
                 }
             }
-            false
         }
 
         FilterCondition::CartItem {
-            day_start,
-            day_end,
+            days,
             item_id,
             max_price,
         } => {
-            for day in *day_start..=*day_end {
-                // Only check cart days (Friday = 5, Sunday = 7)
-                if !is_cart_day(day) {
-                    continue;
-                }
-                if check_cart_has_item(seed, day, *item_id, *max_price, version) {
-                    return true;
+            // Only cart days (Friday = 5, Sunday = 7) count as the condition's days
+            for day in days.days().filter(|&day| is_cart_day(day)) {
+                let hit = check_cart_has_item(seed, day, *item_id, *max_price, ctx.legacy_random, version);
+                if !visit(hit) {
+                    return;
                 }
             }
-            false
         }
 
-        FilterCondition::NightEvent {
-            day_start,
-            day_end,
-            event_type,
-        } => {
+        FilterCondition::NightEvent { days, event_type } => {
             let target_event = parse_night_event(event_type);
-            for day in *day_start..=*day_end {
-                if let Some(event) = mechanics::night_event(seed, day, version) {
-                    if target_event == Some(event) || (event_type == "any") {
-                        return true;
-                    }
+            for day in days.days() {
+                let event = mechanics::night_event_for_farm(
+                    seed,
+                    day,
+                    version,
+                    ctx.greenhouse_repaired,
+                    ctx.legacy_random,
+                );
+                let hit = event.is_some() && (target_event == event || event_type == "any");
+                if !visit(hit) {
+                    return;
                 }
             }
-            false
         }
 
         FilterCondition::Geode {
@@ -77,50 +155,64 @@ fn evaluate_condition(seed: i32, cond: &FilterCondition, version: GameVersion) -
             target_items,
         } => {
             let gt = parse_geode_type(geode_type);
-            let result = mechanics::next_geode_item(seed, *geode_number, gt, 120, version);
-            target_items.contains(&result.item_id)
+            let result = mechanics::next_geode_item(
+                seed,
+                *geode_number,
+                gt,
+                ctx.deepest_mine_level,
+                ctx.has_coconut_hat,
+                version,
+            );
+            visit(target_items.contains(&result.item_id));
         }
 
-        FilterCondition::DishOfDay {
-            day_start,
-            day_end,
-            dish_id,
-        } => {
-            for day in *day_start..=*day_end {
-                let (dish, _qty) = mechanics::dish_of_the_day(seed, day, 0);
-                if dish == *dish_id {
-                    return true;
+        FilterCondition::DishOfDay { days, dish_id } => {
+            for day in days.days() {
+                let (dish, _qty) = mechanics::dish_of_the_day(seed, day, ctx.steps);
+                if !visit(dish == *dish_id) {
+                    return;
                 }
             }
-            false
         }
 
         FilterCondition::Weather {
-            day_start,
-            day_end,
+            days,
             weather_type,
         } => {
             let target = parse_weather(weather_type);
-            for day in *day_start..=*day_end {
-                let weather = mechanics::weather_tomorrow(seed, day, 0, 0, false, version);
-                if weather == target || (weather_type == "any" && weather != mechanics::Weather::Sunny) {
-                    return true;
+            // Chained forecast: weather on each day, with windy days feeding the next roll
+            let (first, last) = days.bounds();
+            let forecast = mechanics::forecast_weather(
+                seed,
+                first,
+                last,
+                ctx.steps,
+                ctx.friendship_rolls(),
+                ctx.legacy_random,
+                version,
+            );
+            for (weather, day) in forecast.into_iter().zip(first..=last) {
+                if !days.contains(day) {
+                    continue;
+                }
+                let hit = weather == target
+                    || (weather_type == "any" && weather != mechanics::Weather::Sunny);
+                if !visit(hit) {
+                    return;
                 }
             }
-            false
         }
 
         FilterCondition::MineFloor {
-            day_start,
-            day_end,
+            days,
             floor_start,
             floor_end,
             no_monsters,
             no_dark,
             has_mushroom,
         } => {
-            for day in *day_start..=*day_end {
-                if check_mine_floors(
+            for day in days.days() {
+                let hit = check_mine_floors(
                     seed,
                     day,
                     *floor_start,
@@ -129,36 +221,46 @@ fn evaluate_condition(seed: i32, cond: &FilterCondition, version: GameVersion) -
                     *no_dark,
                     *has_mushroom,
                     version,
-                ) {
-                    return true;
+                );
+                if !visit(hit) {
+                    return;
+                }
+            }
+        }
+
+        FilterCondition::Sequence { days, steps } => {
+            let mut timeline = Timeline::new(seed, days, steps, version, ctx);
+            for day in days.days() {
+                if !visit(timeline.starts_on(day)) {
+                    return;
                 }
             }
-            false
         }
     }
 }
 
 /// Check if a day is a cart day (Friday or Sunday).
-fn is_cart_day(day: i32) -> bool {
+pub(super) fn is_cart_day(day: i32) -> bool {
     let day_of_week = ((day - 1) % 7) + 1;
     day_of_week == 5 || day_of_week == 7
 }
 
 /// Check if cart has item with optional price constraint.
-fn check_cart_has_item(
+pub(super) fn check_cart_has_item(
     seed: i32,
     day: i32,
     item_id: i32,
     max_price: Option<i32>,
+    legacy_random: bool,
     version: GameVersion,
 ) -> bool {
     // Fast path: no price constraint, use optimized cart_has_item
     if max_price.is_none() {
-        return mechanics::cart_has_item(seed, day, item_id, version);
+        return mechanics::cart_has_item(seed, day, item_id, legacy_random, version);
     }
 
     // Slow path: need to check price, must get full cart
-    let cart = mechanics::get_cart_for_day(seed, day, version);
+    let cart = mechanics::get_cart_for_day(seed, day, legacy_random, version);
     for item in cart {
         if item.item_id == item_id {
             if let Some(max) = max_price {
@@ -217,7 +319,7 @@ fn check_mine_floors(
 }
 
 /// Parse night event type from string.
-fn parse_night_event(s: &str) -> Option<mechanics::NightEvent> {
+pub(super) fn parse_night_event(s: &str) -> Option<mechanics::NightEvent> {
     match s.to_lowercase().as_str() {
         "fairy" => Some(mechanics::NightEvent::Fairy),
         "witch" => Some(mechanics::NightEvent::Witch),
@@ -225,32 +327,44 @@ fn parse_night_event(s: &str) -> Option<mechanics::NightEvent> {
         "ufo" | "capsule" => Some(mechanics::NightEvent::Ufo),
         "owl" => Some(mechanics::NightEvent::Owl),
         "earthquake" => Some(mechanics::NightEvent::Earthquake),
+        "windstorm" => Some(mechanics::NightEvent::Windstorm),
         _ => None,
     }
 }
 
-/// Parse geode type from string.
-fn parse_geode_type(s: &str) -> mechanics::GeodeType {
+/// Parse geode type from string, falling back to a regular geode.
+pub(super) fn parse_geode_type(s: &str) -> mechanics::GeodeType {
+    try_parse_geode_type(s).unwrap_or(mechanics::GeodeType::Geode)
+}
+
+/// Parse geode type from string, or None if it isn't one.
+pub(super) fn try_parse_geode_type(s: &str) -> Option<mechanics::GeodeType> {
     match s.to_lowercase().as_str() {
-        "geode" => mechanics::GeodeType::Geode,
-        "frozen" | "frozen_geode" => mechanics::GeodeType::FrozenGeode,
-        "magma" | "magma_geode" => mechanics::GeodeType::MagmaGeode,
-        "omni" | "omni_geode" => mechanics::GeodeType::OmniGeode,
-        "trove" | "artifact_trove" => mechanics::GeodeType::ArtifactTrove,
-        "coconut" | "golden_coconut" => mechanics::GeodeType::GoldenCoconut,
-        _ => mechanics::GeodeType::Geode,
+        "geode" => Some(mechanics::GeodeType::Geode),
+        "frozen" | "frozen_geode" => Some(mechanics::GeodeType::FrozenGeode),
+        "magma" | "magma_geode" => Some(mechanics::GeodeType::MagmaGeode),
+        "omni" | "omni_geode" => Some(mechanics::GeodeType::OmniGeode),
+        "trove" | "artifact_trove" => Some(mechanics::GeodeType::ArtifactTrove),
+        "coconut" | "golden_coconut" => Some(mechanics::GeodeType::GoldenCoconut),
+        _ => None,
     }
 }
 
-/// Parse weather type from string.
-fn parse_weather(s: &str) -> mechanics::Weather {
+/// Parse weather type from string, falling back to sunny.
+pub(super) fn parse_weather(s: &str) -> mechanics::Weather {
+    try_parse_weather(s).unwrap_or(mechanics::Weather::Sunny)
+}
+
+/// Parse weather type from string, or None if it isn't one.
+pub(super) fn try_parse_weather(s: &str) -> Option<mechanics::Weather> {
     match s.to_lowercase().as_str() {
-        "sunny" | "sun" => mechanics::Weather::Sunny,
-        "rain" | "rainy" => mechanics::Weather::Rain,
-        "debris" | "windy" | "wind" => mechanics::Weather::Debris,
-        "lightning" | "storm" | "stormy" => mechanics::Weather::Lightning,
-        "snow" | "snowy" => mechanics::Weather::Snow,
-        _ => mechanics::Weather::Sunny,
+        "sunny" | "sun" => Some(mechanics::Weather::Sunny),
+        "rain" | "rainy" => Some(mechanics::Weather::Rain),
+        "debris" | "windy" | "wind" => Some(mechanics::Weather::Debris),
+        "lightning" | "storm" | "stormy" => Some(mechanics::Weather::Lightning),
+        "snow" | "snowy" => Some(mechanics::Weather::Snow),
+        "green_rain" | "greenrain" => Some(mechanics::Weather::GreenRain),
+        _ => None,
     }
 }
 
@@ -320,4 +434,232 @@ mod tests {
             _ => panic!("Expected Condition node"),
         }
     }
+
+    #[test]
+    fn test_windstorm_filter() {
+        let json = r#"{
+            "logic": "condition",
+            "type": "night_event",
+            "day_start": 1,
+            "day_end": 7,
+            "event_type": "windstorm"
+        }"#;
+        let filter: FilterNode = serde_json::from_str(json).unwrap();
+        let ctx = PlayerContext {
+            greenhouse_repaired: true,
+            ..Default::default()
+        };
+        assert!(evaluate_filter(12345, &filter, GameVersion::V1_6, &ctx));
+        assert!(!evaluate_filter(12345, &filter, GameVersion::V1_5, &ctx));
+
+        // Without the greenhouse the windstorm can never roll
+        let ctx = PlayerContext::default();
+        assert!(!evaluate_filter(12345, &filter, GameVersion::V1_6, &ctx));
+    }
+
+    #[test]
+    fn test_context_steps_change_dish() {
+        // The dish is seeded by yesterday's steps, so some seed/step pair must differ
+        let json = r#"{
+            "logic": "condition",
+            "type": "dish_of_day",
+            "day_start": 5,
+            "day_end": 5,
+            "dish_id": 0
+        }"#;
+        let (dish, _) = mechanics::dish_of_the_day(12345, 5, 0);
+        let json = json.replace("\"dish_id\": 0", &format!("\"dish_id\": {}", dish));
+        let filter: FilterNode = serde_json::from_str(&json).unwrap();
+
+        let walked = (1..100)
+            .map(|steps| PlayerContext {
+                steps,
+                ..Default::default()
+            })
+            .find(|ctx| !evaluate_filter(12345, &filter, GameVersion::V1_6, ctx));
+        assert!(evaluate_filter(12345, &filter, GameVersion::V1_6, &PlayerContext::default()));
+        assert!(walked.is_some(), "Steps never changed the dish");
+    }
+
+    #[test]
+    fn test_context_legacy_random() {
+        let json = r#"{
+            "logic": "condition",
+            "type": "cart_item",
+            "days": {"type": "exact", "day": 5},
+            "item_id": 0
+        }"#;
+        let stock = |legacy_random| -> Vec<i32> {
+            mechanics::get_cart_for_day(12345, 5, legacy_random, GameVersion::V1_6)
+                .iter()
+                .map(|item| item.item_id)
+                .collect()
+        };
+        let (hashed, legacy) = (stock(false), stock(true));
+        let json = json.replace("\"item_id\": 0", &format!("\"item_id\": {}", legacy[0]));
+        let filter: FilterNode = serde_json::from_str(&json).unwrap();
+
+        let ctx = PlayerContext {
+            legacy_random: true,
+            ..Default::default()
+        };
+        assert!(evaluate_filter(12345, &filter, GameVersion::V1_6, &ctx));
+        assert_eq!(
+            evaluate_filter(12345, &filter, GameVersion::V1_6, &PlayerContext::default()),
+            hashed.contains(&legacy[0])
+        );
+    }
+
+    #[test]
+    fn test_day_spec_list_matches_any_day() {
+        let dish_on = |days: &str| -> FilterNode {
+            let (dish, _) = mechanics::dish_of_the_day(12345, 9, 0);
+            let json = format!(
+                r#"{{"logic": "condition", "type": "dish_of_day", "days": {}, "dish_id": {}}}"#,
+                days, dish
+            );
+            serde_json::from_str(&json).unwrap()
+        };
+        let ctx = PlayerContext::default();
+        let hits = |filter: &FilterNode| evaluate_filter(12345, filter, GameVersion::V1_6, &ctx);
+
+        let list = dish_on(r#"{"type": "list", "days": [2, 9, 40]}"#);
+        let any_exact = [2, 9, 40]
+            .iter()
+            .any(|day| hits(&dish_on(&format!(r#"{{"type": "exact", "day": {}}}"#, day))));
+        assert!(hits(&list));
+        assert_eq!(hits(&list), any_exact);
+    }
+
+    /// A dish condition on day 1 that is true for seed 12345 when `hit`.
+    fn dish_node(hit: bool) -> String {
+        let (dish, _) = mechanics::dish_of_the_day(12345, 1, 0);
+        let dish_id = if hit { dish } else { -1 };
+        format!(
+            r#"{{"logic": "condition", "type": "dish_of_day", "days": {{"type": "exact", "day": 1}}, "dish_id": {}}}"#,
+            dish_id
+        )
+    }
+
+    fn eval_json(json: &str) -> bool {
+        let filter: FilterNode = serde_json::from_str(json).unwrap();
+        evaluate_filter(12345, &filter, GameVersion::V1_6, &PlayerContext::default())
+    }
+
+    #[test]
+    fn test_not_xor_at_least() {
+        let (t, f) = (dish_node(true), dish_node(false));
+        assert!(eval_json(&format!(r#"{{"logic": "not", "condition": {}}}"#, f)));
+        assert!(!eval_json(&format!(r#"{{"logic": "not", "condition": {}}}"#, t)));
+
+        let xor = |a: &str, b: &str, c: &str| {
+            eval_json(&format!(r#"{{"logic": "xor", "conditions": [{}, {}, {}]}}"#, a, b, c))
+        };
+        assert!(xor(&t, &f, &f));
+        assert!(!xor(&t, &t, &f));
+        assert!(!xor(&f, &f, &f));
+
+        let at_least = |count: u32| {
+            eval_json(&format!(
+                r#"{{"logic": "at_least", "count": {}, "conditions": [{}, {}, {}]}}"#,
+                count, t, f, t
+            ))
+        };
+        assert!(at_least(0));
+        assert!(at_least(2));
+        assert!(!at_least(3));
+    }
+
+    #[test]
+    fn test_count_days() {
+        let lucky_days = (1..=28)
+            .filter(|&day| mechanics::daily_luck(12345, day, 0, false) > 0.07)
+            .count() as u32;
+        assert!(lucky_days > 0);
+
+        let count = |bounds: &str| {
+            eval_json(&format!(
+                r#"{{"logic": "count_days", {}
+                    "condition": {{"type": "daily_luck", "days": {{"type": "season", "season": 0}},
+                                   "min_luck": 0.07, "max_luck": 1.0}}}}"#,
+                bounds
+            ))
+        };
+        assert!(count(&format!(r#""at_least": {},"#, lucky_days)));
+        assert!(!count(&format!(r#""at_least": {},"#, lucky_days + 1)));
+        assert!(count(&format!(r#""at_most": {},"#, lucky_days)));
+        assert!(!count(&format!(r#""at_most": {},"#, lucky_days - 1)));
+        assert!(count(&format!(r#""at_least": {0}, "at_most": {0},"#, lucky_days)));
+        // No bounds: every day in spring would have to be lucky
+        assert!(!count(""));
+    }
+
+    #[test]
+    fn test_count_days_every_day() {
+        // "No monster floors on any of days 5-10"
+        let json = r#"{
+            "logic": "count_days",
+            "condition": {
+                "type": "mine_floor",
+                "days": {"type": "range", "start": 5, "end": 10},
+                "floor_start": 1, "floor_end": 40,
+                "no_monsters": true, "no_dark": false, "has_mushroom": false
+            }
+        }"#;
+        let expected = (5..=10).all(|day| {
+            mechanics::find_monster_floors(12345, day, 1, 40, GameVersion::V1_6).is_empty()
+        });
+        assert_eq!(eval_json(json), expected);
+    }
+
+    #[test]
+    fn test_count_days_cart_week() {
+        // A week holds two cart days (Friday 5 and Sunday 7); the other days
+        // are not the condition's days, so "every day" means both cart days
+        let stock = |seed: i32, day: i32| -> Vec<i32> {
+            mechanics::get_cart_for_day(seed, day, false, GameVersion::V1_6)
+                .iter()
+                .map(|item| item.item_id)
+                .collect()
+        };
+        let (seed, item) = (0..100)
+            .find_map(|seed| {
+                let sunday = stock(seed, 7);
+                stock(seed, 5).into_iter().find(|id| sunday.contains(id)).map(|id| (seed, id))
+            })
+            .expect("Some seed stocks an item on both cart days");
+
+        let count = |seed: i32, bounds: &str| {
+            let json = format!(
+                r#"{{"logic": "count_days", {}
+                    "condition": {{"type": "cart_item", "days": {{"type": "range", "start": 1, "end": 7}},
+                                   "item_id": {}}}}}"#,
+                bounds, item
+            );
+            let filter: FilterNode = serde_json::from_str(&json).unwrap();
+            evaluate_filter(seed, &filter, GameVersion::V1_6, &PlayerContext::default())
+        };
+        assert!(count(seed, ""));
+        assert!(count(seed, r#""at_least": 2,"#));
+        assert!(!count(seed, r#""at_least": 3,"#));
+        assert!(count(seed, r#""at_most": 2,"#));
+
+        let once = (0..10_000)
+            .find(|&seed| stock(seed, 5).contains(&item) != stock(seed, 7).contains(&item))
+            .expect("Some seed stocks the item on one cart day");
+        assert!(!count(once, ""));
+        assert!(count(once, r#""at_least": 1, "at_most": 1,"#));
+    }
+
+    #[test]
+    fn test_context_defaults() {
+        // Missing fields fall back to the pre-context values
+        let ctx: PlayerContext = serde_json::from_str(r#"{"steps": 1234}"#).unwrap();
+        assert_eq!(ctx.steps, 1234);
+        assert_eq!(ctx.deepest_mine_level, 120);
+        assert!(!ctx.friendship_rolls());
+
+        let ctx: PlayerContext = serde_json::from_str(r#"{"multiplayer": true}"#).unwrap();
+        assert!(ctx.friendship_rolls());
+    }
 }
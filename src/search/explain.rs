@@ -0,0 +1,464 @@
+//! Explain-mode evaluation.
+//!
+//! Mirrors `evaluate_filter`, but instead of a bool it returns a trace tree
+//! saying which branches passed, which days (or geodes) satisfied each
+//! condition, and the values the mechanics produced. Nothing short-circuits,
+//! so every child and day appears in the trace.
+
+use serde::Serialize;
+
+use super::evaluate::{count_days_in_bounds, is_cart_day, parse_geode_type, parse_night_event, parse_weather};
+use super::filter::{DayEvent, FilterCondition, FilterNode};
+use super::sequence::Timeline;
+use crate::mechanics;
+use crate::types::{NightEventType, PlayerContext, WeatherType};
+use crate::version::GameVersion;
+
+/// One node of an explained filter.
+#[derive(Debug, Clone, Serialize)]
+pub struct Trace {
+    /// Logic node (`and`, `or`, `not`, `xor`, `at_least`, `count_days`) or condition type
+    pub node: &'static str,
+    pub passed: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Trace>,
+    /// Every day (or geode) the condition looked at
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub checks: Vec<Check>,
+    /// Days that satisfied the condition (geode numbers for geodes)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub witnesses: Vec<i32>,
+}
+
+/// What a condition saw on one day.
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    /// Day played, or the geode number for geodes
+    pub at: i32,
+    pub matched: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seen: Option<Seen>,
+}
+
+/// The concrete value behind a check.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Seen {
+    Luck { luck: f64 },
+    /// Cheapest price of the item in the cart, if stocked
+    Cart { price: Option<i32> },
+    NightEvent { event: NightEventType },
+    Dish { dish_id: i32, quantity: i32 },
+    Weather { weather: WeatherType },
+    Geode { item_id: i32, quantity: i32 },
+    /// Floors breaking each requested rule (or, for mushrooms, satisfying it)
+    MineFloors {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        monster_floors: Option<Vec<i32>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        dark_floors: Option<Vec<i32>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mushroom_floors: Option<Vec<i32>>,
+    },
+    /// The day and value of each step of a sequence starting on the check's day,
+    /// or just the first step's when the sequence doesn't match from there
+    Sequence { steps: Vec<Check> },
+}
+
+/// Explain how a filter tree evaluates for a seed. `passed` on the root is
+/// always what `evaluate_filter` returns.
+pub fn trace_filter(seed: i32, filter: &FilterNode, version: GameVersion, ctx: &PlayerContext) -> Trace {
+    let node = |node, passed, children| Trace {
+        node,
+        passed,
+        children,
+        checks: Vec::new(),
+        witnesses: Vec::new(),
+    };
+    let explain_all = |conditions: &[FilterNode]| -> Vec<Trace> {
+        conditions
+            .iter()
+            .map(|c| trace_filter(seed, c, version, ctx))
+            .collect()
+    };
+    let passed_count = |children: &[Trace]| children.iter().filter(|c| c.passed).count();
+
+    match filter {
+        FilterNode::And { conditions } => {
+            let children = explain_all(conditions);
+            node("and", children.iter().all(|c| c.passed), children)
+        }
+        FilterNode::Or { conditions } => {
+            let children = explain_all(conditions);
+            node("or", children.iter().any(|c| c.passed), children)
+        }
+        FilterNode::Not { condition } => {
+            let child = trace_filter(seed, condition, version, ctx);
+            node("not", !child.passed, vec![child])
+        }
+        FilterNode::Xor { conditions } => {
+            let children = explain_all(conditions);
+            node("xor", passed_count(&children) == 1, children)
+        }
+        FilterNode::AtLeast { count, conditions } => {
+            let children = explain_all(conditions);
+            node("at_least", passed_count(&children) >= *count as usize, children)
+        }
+        FilterNode::CountDays {
+            condition,
+            at_least,
+            at_most,
+        } => {
+            let child = explain_condition(seed, condition, version, ctx);
+            let passed = count_days_in_bounds(seed, condition, *at_least, *at_most, version, ctx);
+            node("count_days", passed, vec![child])
+        }
+        FilterNode::Condition(cond) => explain_condition(seed, cond, version, ctx),
+    }
+}
+
+/// Explain a single condition: passes if any check matched.
+fn explain_condition(seed: i32, cond: &FilterCondition, version: GameVersion, ctx: &PlayerContext) -> Trace {
+    let check = |at, matched, seen| Check {
+        at,
+        matched,
+        seen: Some(seen),
+    };
+
+    let (node, checks): (&'static str, Vec<Check>) = match cond {
+        FilterCondition::DailyLuck {
+            days,
+            min_luck,
+            max_luck,
+        } => (
+            "daily_luck",
+            days.days()
+                .map(|day| {
+                    let luck = mechanics::daily_luck(seed, day, ctx.steps, ctx.friendship_rolls());
+                    check(day, luck >= *min_luck && luck <= *max_luck, Seen::Luck { luck })
+                })
+                .collect(),
+        ),
+
+        FilterCondition::CartItem {
+            days,
+            item_id,
+            max_price,
+        } => (
+            "cart_item",
+            days.days()
+                .filter(|&day| is_cart_day(day))
+                .map(|day| {
+                    let price = cheapest_cart_price(seed, day, *item_id, version, ctx);
+                    let matched = price.is_some_and(|p| max_price.is_none_or(|max| p <= max));
+                    check(day, matched, Seen::Cart { price })
+                })
+                .collect(),
+        ),
+
+        FilterCondition::NightEvent { days, event_type } => {
+            let target = parse_night_event(event_type);
+            (
+                "night_event",
+                days.days()
+                    .map(|day| {
+                        let event = farm_night_event(seed, day, version, ctx);
+                        let matched = event.is_some() && (event == target || event_type == "any");
+                        check(day, matched, Seen::NightEvent { event: NightEventType::from_event(event) })
+                    })
+                    .collect(),
+            )
+        }
+
+        FilterCondition::Geode {
+            geode_number,
+            geode_type,
+            target_items,
+        } => {
+            let result = mechanics::next_geode_item(
+                seed,
+                *geode_number,
+                parse_geode_type(geode_type),
+                ctx.deepest_mine_level,
+                ctx.has_coconut_hat,
+                version,
+            );
+            let seen = Seen::Geode {
+                item_id: result.item_id,
+                quantity: result.quantity,
+            };
+            ("geode", vec![check(*geode_number, target_items.contains(&result.item_id), seen)])
+        }
+
+        FilterCondition::DishOfDay { days, dish_id } => (
+            "dish_of_day",
+            days.days()
+                .map(|day| {
+                    let (dish, quantity) = mechanics::dish_of_the_day(seed, day, ctx.steps);
+                    check(day, dish == *dish_id, Seen::Dish { dish_id: dish, quantity })
+                })
+                .collect(),
+        ),
+
+        FilterCondition::Weather { days, weather_type } => {
+            let target = parse_weather(weather_type);
+            let (first, last) = days.bounds();
+            let forecast = mechanics::forecast_weather(
+                seed,
+                first,
+                last,
+                ctx.steps,
+                ctx.friendship_rolls(),
+                ctx.legacy_random,
+                version,
+            );
+            (
+                "weather",
+                forecast
+                    .into_iter()
+                    .zip(first..=last)
+                    .filter(|&(_, day)| days.contains(day))
+                    .map(|(weather, day)| {
+                        let matched = weather == target
+                            || (weather_type == "any" && weather != mechanics::Weather::Sunny);
+                        let seen = Seen::Weather {
+                            weather: WeatherType::from_code(weather.to_code()),
+                        };
+                        check(day, matched, seen)
+                    })
+                    .collect(),
+            )
+        }
+
+        FilterCondition::MineFloor {
+            days,
+            floor_start,
+            floor_end,
+            no_monsters,
+            no_dark,
+            has_mushroom,
+        } => (
+            "mine_floor",
+            days.days()
+                .map(|day| {
+                    let (start, end) = (*floor_start, *floor_end);
+                    let monster_floors =
+                        no_monsters.then(|| mechanics::find_monster_floors(seed, day, start, end, version));
+                    let dark_floors = no_dark.then(|| mechanics::find_dark_floors(seed, day, start, end));
+                    // Mushrooms only grow from floor 81
+                    let mushroom_floors = has_mushroom.then(|| {
+                        let start = start.max(81);
+                        if start <= end {
+                            mechanics::find_mushroom_floors(seed, day, start, end, version)
+                        } else {
+                            Vec::new()
+                        }
+                    });
+                    let matched = monster_floors.as_ref().is_none_or(|f| f.is_empty())
+                        && dark_floors.as_ref().is_none_or(|f| f.is_empty())
+                        && mushroom_floors.as_ref().is_none_or(|f| !f.is_empty());
+                    let seen = Seen::MineFloors {
+                        monster_floors,
+                        dark_floors,
+                        mushroom_floors,
+                    };
+                    check(day, matched, seen)
+                })
+                .collect(),
+        ),
+
+        FilterCondition::Sequence { days, steps } => {
+            let mut timeline = Timeline::new(seed, days, steps, version, ctx);
+            (
+                "sequence",
+                days.days()
+                    .map(|day| {
+                        let chain = timeline.chain_days(day);
+                        let matched = chain.is_some();
+                        // Without a match, show why the first step did or didn't hold
+                        let step_checks = match chain {
+                            Some(chain) => steps
+                                .iter()
+                                .zip(chain)
+                                .map(|(step, at)| check(at, true, step_seen(seed, &step.event, at, version, ctx, &mut timeline)))
+                                .collect(),
+                            None => match steps.first() {
+                                Some(first) => vec![check(
+                                    day,
+                                    timeline.step_on(0, day),
+                                    step_seen(seed, &first.event, day, version, ctx, &mut timeline),
+                                )],
+                                None => Vec::new(),
+                            },
+                        };
+                        check(day, matched, Seen::Sequence { steps: step_checks })
+                    })
+                    .collect(),
+            )
+        }
+    };
+
+    let witnesses: Vec<i32> = checks.iter().filter(|c| c.matched).map(|c| c.at).collect();
+    Trace {
+        node,
+        passed: !witnesses.is_empty(),
+        children: Vec::new(),
+        checks,
+        witnesses,
+    }
+}
+
+/// Cheapest price of `item_id` in the cart on `day`, if stocked.
+fn cheapest_cart_price(seed: i32, day: i32, item_id: i32, version: GameVersion, ctx: &PlayerContext) -> Option<i32> {
+    mechanics::get_cart_for_day(seed, day, ctx.legacy_random, version)
+        .iter()
+        .filter(|item| item.item_id == item_id)
+        .map(|item| item.price)
+        .min()
+}
+
+fn farm_night_event(seed: i32, day: i32, version: GameVersion, ctx: &PlayerContext) -> Option<mechanics::NightEvent> {
+    mechanics::night_event_for_farm(seed, day, version, ctx.greenhouse_repaired, ctx.legacy_random)
+}
+
+/// The value a sequence step's event looks at on `day`.
+fn step_seen(
+    seed: i32,
+    event: &DayEvent,
+    day: i32,
+    version: GameVersion,
+    ctx: &PlayerContext,
+    timeline: &mut Timeline,
+) -> Seen {
+    match event {
+        DayEvent::DailyLuck { .. } => Seen::Luck {
+            luck: mechanics::daily_luck(seed, day, ctx.steps, ctx.friendship_rolls()),
+        },
+        DayEvent::CartItem { item_id, .. } => Seen::Cart {
+            price: is_cart_day(day)
+                .then(|| cheapest_cart_price(seed, day, *item_id, version, ctx))
+                .flatten(),
+        },
+        DayEvent::NightEvent { .. } => Seen::NightEvent {
+            event: NightEventType::from_event(farm_night_event(seed, day, version, ctx)),
+        },
+        DayEvent::DishOfDay { .. } => {
+            let (dish_id, quantity) = mechanics::dish_of_the_day(seed, day, ctx.steps);
+            Seen::Dish { dish_id, quantity }
+        }
+        DayEvent::Weather { .. } => Seen::Weather {
+            weather: WeatherType::from_code(timeline.weather_on(day).to_code()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::evaluate_filter;
+
+    fn parse(json: &str) -> FilterNode {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_explain_agrees_with_evaluate() {
+        let filter = parse(
+            r#"{
+            "logic": "or",
+            "conditions": [
+                {"logic": "and", "conditions": [
+                    {"logic": "condition", "type": "daily_luck", "days": {"type": "range", "start": 1, "end": 5}, "min_luck": 0.05, "max_luck": 0.1},
+                    {"logic": "not", "condition": {"logic": "condition", "type": "weather", "days": {"type": "range", "start": 2, "end": 6}, "weather_type": "rain"}}
+                ]},
+                {"logic": "xor", "conditions": [
+                    {"logic": "condition", "type": "geode", "geode_number": 1, "geode_type": "geode", "target_items": [535, 378]},
+                    {"logic": "condition", "type": "cart_item", "days": {"type": "range", "start": 1, "end": 7}, "item_id": 266, "max_price": 500}
+                ]},
+                {"logic": "count_days", "condition": {"type": "night_event", "days": {"type": "range", "start": 1, "end": 28}, "event_type": "any"}, "at_least": 2},
+                {"logic": "condition", "type": "mine_floor", "days": {"type": "exact", "day": 5}, "floor_start": 75, "floor_end": 90,
+                 "no_monsters": false, "no_dark": true, "has_mushroom": true}
+            ]
+        }"#,
+        );
+        let ctx = PlayerContext::default();
+        for version in [GameVersion::V1_5, GameVersion::V1_6] {
+            for seed in 0..300 {
+                let trace = trace_filter(seed, &filter, version, &ctx);
+                assert_eq!(trace.passed, evaluate_filter(seed, &filter, version, &ctx), "seed {}", seed);
+                // Every node's verdict matches evaluating that subtree alone
+                let FilterNode::Or { conditions } = &filter else { unreachable!() };
+                for (child, node) in trace.children.iter().zip(conditions) {
+                    assert_eq!(child.passed, evaluate_filter(seed, node, version, &ctx));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_witnesses_and_values() {
+        let filter = parse(
+            r#"{"logic": "condition", "type": "daily_luck", "days": {"type": "range", "start": 1, "end": 10},
+                "min_luck": 0.0, "max_luck": 0.1}"#,
+        );
+        let trace = trace_filter(12345, &filter, GameVersion::V1_6, &PlayerContext::default());
+        assert_eq!(trace.node, "daily_luck");
+        assert_eq!(trace.checks.len(), 10);
+        for check in &trace.checks {
+            let luck = mechanics::daily_luck(12345, check.at, 0, false);
+            assert!(matches!(check.seen, Some(Seen::Luck { luck: l }) if l == luck));
+            assert_eq!(check.matched, luck >= 0.0);
+            assert_eq!(trace.witnesses.contains(&check.at), check.matched);
+        }
+        assert_eq!(trace.passed, !trace.witnesses.is_empty());
+    }
+
+    #[test]
+    fn test_sequence_reports_step_values() {
+        let filter = parse(
+            r#"{"logic": "condition", "type": "sequence", "days": {"type": "range", "start": 1, "end": 20},
+                "steps": [
+                    {"event": {"type": "daily_luck", "min_luck": 0.05, "max_luck": 0.1}},
+                    {"event": {"type": "weather", "weather_type": "rain"}, "min_after": 1, "max_after": 3}
+                ]}"#,
+        );
+        let ctx = PlayerContext::default();
+        let mut matched_any = false;
+        for seed in 0..100 {
+            let trace = trace_filter(seed, &filter, GameVersion::V1_6, &ctx);
+            let forecast = mechanics::forecast_weather(seed, 1, 23, 0, false, false, GameVersion::V1_6);
+            for check in &trace.checks {
+                let Some(Seen::Sequence { steps }) = &check.seen else { panic!("no sequence values") };
+                let luck = mechanics::daily_luck(seed, check.at, 0, false);
+                assert_eq!(steps[0].at, check.at);
+                assert!(matches!(steps[0].seen, Some(Seen::Luck { luck: l }) if l == luck));
+                if check.matched {
+                    matched_any = true;
+                    assert_eq!(steps.len(), 2);
+                    assert!((1..=3).contains(&(steps[1].at - steps[0].at)));
+                    assert_eq!(forecast[(steps[1].at - 1) as usize], mechanics::Weather::Rain);
+                    assert!(matches!(steps[1].seen, Some(Seen::Weather { weather: WeatherType::Rain })));
+                } else {
+                    assert_eq!(steps.len(), 1);
+                    assert_eq!(steps[0].matched, (0.05..=0.1).contains(&luck));
+                }
+            }
+        }
+        assert!(matched_any);
+    }
+
+    #[test]
+    fn test_trace_serializes() {
+        let filter = parse(
+            r#"{"logic": "not", "condition": {"logic": "condition", "type": "dish_of_day",
+                "days": {"type": "exact", "day": 1}, "dish_id": 200}}"#,
+        );
+        let trace = trace_filter(7, &filter, GameVersion::V1_6, &PlayerContext::default());
+        let json = serde_json::to_value(&trace).unwrap();
+        assert_eq!(json["node"], "not");
+        let check = &json["children"][0]["checks"][0];
+        assert_eq!(check["at"], 1);
+        assert_eq!(check["seen"]["kind"], "dish");
+        assert_eq!(check["seen"]["dish_id"], mechanics::dish_of_the_day(7, 1, 0).0);
+    }
+}
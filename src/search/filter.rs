@@ -1,11 +1,15 @@
 //! Filter types for the search kernel.
 //!
-//! These types are deserialized from JSON passed from JavaScript.
+//! These types are deserialized from JSON passed from JavaScript. Saved and
+//! shared filters are wrapped in a `{"schema_version", "filter"}` envelope;
+//! `migrate_filter` upgrades older schema versions to the current shape.
 
-use serde::Deserialize;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
 
-/// Root of the filter tree - can be AND, OR, or a single condition.
-#[derive(Debug, Clone, Deserialize)]
+/// Root of the filter tree - a logic node or a single condition.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "logic")]
 pub enum FilterNode {
     #[serde(rename = "and")]
@@ -14,34 +18,57 @@ pub enum FilterNode {
     #[serde(rename = "or")]
     Or { conditions: Vec<FilterNode> },
 
+    #[serde(rename = "not")]
+    Not { condition: Box<FilterNode> },
+
+    /// Exactly one child matches
+    #[serde(rename = "xor")]
+    Xor { conditions: Vec<FilterNode> },
+
+    /// At least `count` children match
+    #[serde(rename = "at_least")]
+    AtLeast { count: u32, conditions: Vec<FilterNode> },
+
+    /// The number of the condition's days that match is within
+    /// [at_least, at_most]. With neither bound, every day must match.
+    /// A cart condition's days are the cart days in its range.
+    #[serde(rename = "count_days")]
+    CountDays {
+        condition: Box<FilterCondition>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        at_least: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        at_most: Option<u32>,
+    },
+
     #[serde(rename = "condition")]
     Condition(Box<FilterCondition>),
 }
 
 /// A single filter condition.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum FilterCondition {
     #[serde(rename = "daily_luck")]
     DailyLuck {
-        day_start: i32,
-        day_end: i32,
+        #[serde(flatten, deserialize_with = "days_or_range", serialize_with = "days_field")]
+        days: DaySpec,
         min_luck: f64,
         max_luck: f64,
     },
 
     #[serde(rename = "cart_item")]
     CartItem {
-        day_start: i32,
-        day_end: i32,
+        #[serde(flatten, deserialize_with = "days_or_range", serialize_with = "days_field")]
+        days: DaySpec,
         item_id: i32,
         max_price: Option<i32>,
     },
 
     #[serde(rename = "night_event")]
     NightEvent {
-        day_start: i32,
-        day_end: i32,
+        #[serde(flatten, deserialize_with = "days_or_range", serialize_with = "days_field")]
+        days: DaySpec,
         event_type: String,
     },
 
@@ -54,26 +81,725 @@ pub enum FilterCondition {
 
     #[serde(rename = "dish_of_day")]
     DishOfDay {
-        day_start: i32,
-        day_end: i32,
+        #[serde(flatten, deserialize_with = "days_or_range", serialize_with = "days_field")]
+        days: DaySpec,
         dish_id: i32,
     },
 
     #[serde(rename = "weather")]
     Weather {
-        day_start: i32,
-        day_end: i32,
+        #[serde(flatten, deserialize_with = "days_or_range", serialize_with = "days_field")]
+        days: DaySpec,
         weather_type: String,
     },
 
     #[serde(rename = "mine_floor")]
     MineFloor {
-        day_start: i32,
-        day_end: i32,
+        #[serde(flatten, deserialize_with = "days_or_range", serialize_with = "days_field")]
+        days: DaySpec,
         floor_start: i32,
         floor_end: i32,
         no_monsters: bool,
         no_dark: bool,
         has_mushroom: bool,
     },
+
+    /// Events in order: the first step on one of `days`, each later step a
+    /// set number of days after the one before it
+    #[serde(rename = "sequence")]
+    Sequence {
+        #[serde(flatten, deserialize_with = "days_or_range", serialize_with = "days_field")]
+        days: DaySpec,
+        steps: Vec<SequenceStep>,
+    },
+}
+
+impl FilterCondition {
+    /// The condition's `type` tag in filter JSON.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            FilterCondition::DailyLuck { .. } => "daily_luck",
+            FilterCondition::CartItem { .. } => "cart_item",
+            FilterCondition::NightEvent { .. } => "night_event",
+            FilterCondition::Geode { .. } => "geode",
+            FilterCondition::DishOfDay { .. } => "dish_of_day",
+            FilterCondition::Weather { .. } => "weather",
+            FilterCondition::MineFloor { .. } => "mine_floor",
+            FilterCondition::Sequence { .. } => "sequence",
+        }
+    }
+}
+
+/// One event in a `Sequence` condition.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SequenceStep {
+    pub event: DayEvent,
+    /// Days after the previous step, at least 0 (ignored for the first step)
+    #[serde(default = "default_min_after")]
+    pub min_after: i32,
+    /// Defaults to `min_after`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_after: Option<i32>,
+    /// Must also be the event's first occurrence since the sequence's first day
+    #[serde(default)]
+    pub first: bool,
+}
+
+fn default_min_after() -> i32 {
+    1
+}
+
+impl SequenceStep {
+    /// Inclusive range of days after the previous step.
+    pub fn after(&self) -> (i32, i32) {
+        (self.min_after, self.max_after.unwrap_or(self.min_after))
+    }
+}
+
+/// Something that happens on a single day, for sequences.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum DayEvent {
+    #[serde(rename = "daily_luck")]
+    DailyLuck { min_luck: f64, max_luck: f64 },
+
+    #[serde(rename = "cart_item")]
+    CartItem {
+        item_id: i32,
+        #[serde(default)]
+        max_price: Option<i32>,
+    },
+
+    #[serde(rename = "night_event")]
+    NightEvent { event_type: String },
+
+    #[serde(rename = "dish_of_day")]
+    DishOfDay { dish_id: i32 },
+
+    #[serde(rename = "weather")]
+    Weather { weather_type: String },
+}
+
+/// Latest day a filter can name: the end of year `MAX_YEAR`.
+pub(super) const MAX_DAY: i32 = MAX_YEAR * 112;
+pub(super) const MAX_YEAR: i32 = 1000;
+
+/// Which days a condition checks.
+///
+/// Days are 1-based days played: a year is four 28-day seasons, and day 1
+/// (Spring 1) is a Monday.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DaySpec {
+    /// A single day
+    Exact { day: i32 },
+
+    /// Every day in [start, end]
+    Range { start: i32, end: i32 },
+
+    /// A whole season (0=Spring .. 3=Winter) of a year (default 1)
+    Season {
+        season: i32,
+        #[serde(default = "default_year")]
+        year: i32,
+    },
+
+    /// Days of the week (1=Mon .. 7=Sun) within another spec
+    Weekdays {
+        weekdays: Vec<i32>,
+        within: Box<DaySpec>,
+    },
+
+    /// An explicit list of days
+    List { days: Vec<i32> },
+
+    /// Every `step`th day from `start` up to `end`
+    EveryNth { start: i32, end: i32, step: i32 },
+}
+
+fn default_year() -> i32 {
+    1
+}
+
+impl DaySpec {
+    /// First and last day that can match (start > end when nothing can).
+    pub fn bounds(&self) -> (i32, i32) {
+        match self {
+            DaySpec::Exact { day } => (*day, *day),
+            DaySpec::Range { start, end } | DaySpec::EveryNth { start, end, .. } => (*start, *end),
+            DaySpec::Season { season, year } => {
+                // Widened so absurd years clamp instead of overflowing
+                let first = (i64::from(*year) - 1) * 112 + i64::from(*season) * 28 + 1;
+                (clamp_day(first), clamp_day(first + 27))
+            }
+            DaySpec::Weekdays { within, .. } => within.bounds(),
+            DaySpec::List { days } => match (days.iter().min(), days.iter().max()) {
+                (Some(&min), Some(&max)) => (min, max),
+                _ => (1, 0),
+            },
+        }
+    }
+
+    /// Whether `day` is one of the spec's days.
+    pub fn contains(&self, day: i32) -> bool {
+        match self {
+            DaySpec::Exact { day: d } => day == *d,
+            DaySpec::Range { start, end } => (*start..=*end).contains(&day),
+            DaySpec::Season { .. } => {
+                let (first, last) = self.bounds();
+                (first..=last).contains(&day)
+            }
+            DaySpec::Weekdays { weekdays, within } => {
+                weekdays.contains(&weekday(i64::from(day))) && within.contains(day)
+            }
+            DaySpec::List { days } => days.contains(&day),
+            DaySpec::EveryNth { start, end, step } => {
+                *step > 0 && (*start..=*end).contains(&day) && (i64::from(day) - i64::from(*start)) % i64::from(*step) == 0
+            }
+        }
+    }
+
+    /// The spec's days in ascending order, visiting only the days themselves.
+    pub fn days(&self) -> Box<dyn Iterator<Item = i32> + '_> {
+        match self {
+            DaySpec::List { days } => {
+                let mut days = days.clone();
+                days.sort_unstable();
+                days.dedup();
+                Box::new(days.into_iter())
+            }
+            DaySpec::EveryNth { start, end, step } if *step > 0 => {
+                Box::new((*start..=*end).step_by(*step as usize))
+            }
+            DaySpec::EveryNth { .. } => Box::new(std::iter::empty()),
+            DaySpec::Weekdays { weekdays, within } => {
+                Box::new(within.days().filter(move |&day| weekdays.contains(&weekday(i64::from(day)))))
+            }
+            DaySpec::Exact { .. } | DaySpec::Range { .. } | DaySpec::Season { .. } => {
+                let (first, last) = self.bounds();
+                Box::new(first..=last)
+            }
+        }
+    }
+
+    /// How many of the spec's days fall on each weekday (Monday first),
+    /// counted without visiting them.
+    pub fn weekday_counts(&self) -> [u64; 7] {
+        let mut counts = [0; 7];
+        match self {
+            DaySpec::List { .. } => {
+                for day in self.days() {
+                    counts[weekday(i64::from(day)) as usize - 1] += 1;
+                }
+            }
+            DaySpec::EveryNth { start, end, step } => {
+                if *step > 0 && start <= end {
+                    let (start, step) = (i64::from(*start), i64::from(*step));
+                    let last = (i64::from(*end) - start) / step;
+                    // Weekdays repeat every seven steps
+                    for offset in 0..=last.min(6) {
+                        counts[weekday(start + offset * step) as usize - 1] += ((last - offset) / 7 + 1) as u64;
+                    }
+                }
+            }
+            DaySpec::Weekdays { weekdays, within } => {
+                for (i, count) in within.weekday_counts().into_iter().enumerate() {
+                    if weekdays.contains(&(i as i32 + 1)) {
+                        counts[i] = count;
+                    }
+                }
+            }
+            DaySpec::Exact { .. } | DaySpec::Range { .. } | DaySpec::Season { .. } => {
+                let (first, last) = self.bounds();
+                if first <= last {
+                    let (first, last) = (i64::from(first), i64::from(last));
+                    for (i, count) in counts.iter_mut().enumerate() {
+                        // Days d in first..=last with d ≡ i + 1 (mod 7)
+                        let residue = i as i64 + 1;
+                        *count = ((last - residue).div_euclid(7) - (first - 1 - residue).div_euclid(7)) as u64;
+                    }
+                }
+            }
+        }
+        counts
+    }
+
+    /// Number of days in the spec.
+    pub fn day_count(&self) -> u64 {
+        self.weekday_counts().iter().sum()
+    }
+}
+
+/// 1 (Monday) to 7 (Sunday).
+fn weekday(day: i64) -> i32 {
+    (day - 1).rem_euclid(7) as i32 + 1
+}
+
+fn clamp_day(day: i64) -> i32 {
+    day.clamp(i64::from(i32::MIN), i64::from(i32::MAX)) as i32
+}
+
+/// Read a condition's days from either a `days` spec or the older
+/// `day_start`/`day_end` pair.
+fn days_or_range<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DaySpec, D::Error> {
+    #[derive(Deserialize)]
+    struct Fields {
+        days: Option<DaySpec>,
+        day_start: Option<i32>,
+        day_end: Option<i32>,
+    }
+
+    let fields = Fields::deserialize(deserializer)?;
+    match (fields.days, fields.day_start, fields.day_end) {
+        (Some(spec), None, None) => Ok(spec),
+        (None, Some(start), Some(end)) => Ok(DaySpec::Range { start, end }),
+        (Some(_), _, _) => Err(D::Error::custom("use either `days` or `day_start`/`day_end`, not both")),
+        _ => Err(D::Error::custom("missing `days` (or `day_start` and `day_end`)")),
+    }
+}
+
+/// Write a condition's days as a `days` spec, never the older pair.
+fn days_field<S: Serializer>(days: &DaySpec, serializer: S) -> Result<S::Ok, S::Error> {
+    #[derive(Serialize)]
+    struct Fields<'a> {
+        days: &'a DaySpec,
+    }
+
+    Fields { days }.serialize(serializer)
+}
+
+/// Current filter schema version. Renaming or reshaping a field means
+/// bumping this and adding a migration from the previous version.
+pub const FILTER_SCHEMA_VERSION: u32 = 2;
+
+/// JSON Schema (draft 2020-12) for versioned filter documents. The web app
+/// serves the same file as `/filter.schema.json`.
+pub const FILTER_JSON_SCHEMA: &str = include_str!("../../web/static/filter.schema.json");
+
+/// A filter as saved or shared: `{"schema_version": 2, "filter": {...}}`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VersionedFilter {
+    pub schema_version: u32,
+    pub filter: FilterNode,
+}
+
+impl VersionedFilter {
+    /// Wrap a filter in the current schema version.
+    pub fn new(filter: FilterNode) -> Self {
+        Self {
+            schema_version: FILTER_SCHEMA_VERSION,
+            filter,
+        }
+    }
+}
+
+/// Why filter JSON couldn't be upgraded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaError {
+    /// Not valid JSON for its schema version
+    Malformed(String),
+    /// Written by a newer version of the app (or a made-up version)
+    UnsupportedVersion(u64),
+}
+
+impl std::fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Malformed(e) => write!(f, "Filter parse error: {}", e),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "Unsupported filter schema version {} (this build reads 1 to {})",
+                version, FILTER_SCHEMA_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// Whether a document is a versioned envelope rather than a bare filter.
+pub fn is_versioned(document: &Value) -> bool {
+    document.get("schema_version").is_some()
+}
+
+/// Upgrade filter JSON of any schema version to a bare filter in the current shape.
+///
+/// Versioned documents are migrated one version at a time. A bare filter
+/// (no envelope) is read as the current version; it may still give days as
+/// `day_start`/`day_end`, which the current shape accepts.
+pub fn migrate_filter(document: Value) -> Result<Value, SchemaError> {
+    if !is_versioned(&document) {
+        return Ok(document);
+    }
+    let mut version = document["schema_version"]
+        .as_u64()
+        .ok_or_else(|| SchemaError::Malformed("`schema_version` must be a positive integer".into()))?;
+    let Value::Object(mut envelope) = document else {
+        unreachable!("only objects have a schema_version");
+    };
+    let mut filter = envelope
+        .remove("filter")
+        .ok_or_else(|| SchemaError::Malformed("missing `filter`".into()))?;
+
+    if version == 0 || version > FILTER_SCHEMA_VERSION as u64 {
+        return Err(SchemaError::UnsupportedVersion(version));
+    }
+    while version < FILTER_SCHEMA_VERSION as u64 {
+        filter = match version {
+            1 => v1::migrate(filter)?,
+            _ => unreachable!("every older version has a migration"),
+        };
+        version += 1;
+    }
+    Ok(filter)
+}
+
+/// Schema version 1: `and`/`or` trees whose conditions give their days as
+/// `day_start`/`day_end`.
+mod v1 {
+    use serde::Deserialize;
+    use serde_json::{json, Map, Value};
+
+    use super::SchemaError;
+
+    /// The only part of a version 1 filter whose shape changed.
+    #[derive(Deserialize)]
+    struct Days {
+        day_start: i32,
+        day_end: i32,
+    }
+
+    /// Version 1 to 2: days become a `days` range spec.
+    pub(super) fn migrate(mut filter: Value) -> Result<Value, SchemaError> {
+        upgrade(&mut filter)?;
+        Ok(filter)
+    }
+
+    fn upgrade(node: &mut Value) -> Result<(), SchemaError> {
+        match node["logic"].as_str() {
+            Some("and" | "or") => match node.get_mut("conditions") {
+                Some(Value::Array(conditions)) => conditions.iter_mut().try_for_each(upgrade),
+                _ => Err(SchemaError::Malformed("missing `conditions` list".into())),
+            },
+            Some("condition") => {
+                let condition = node.as_object_mut().expect("only objects have a logic");
+                if condition.contains_key("days") {
+                    return Err(SchemaError::Malformed("unknown field `days`, version 1 uses `day_start`/`day_end`".into()));
+                }
+                // Geodes have no days
+                if !condition.contains_key("day_start") && !condition.contains_key("day_end") {
+                    return Ok(());
+                }
+                let mut days = Map::new();
+                for field in ["day_start", "day_end"] {
+                    if let Some(day) = condition.remove(field) {
+                        days.insert(field.into(), day);
+                    }
+                }
+                let Days { day_start, day_end } =
+                    Days::deserialize(Value::Object(days)).map_err(|e| SchemaError::Malformed(e.to_string()))?;
+                condition.insert("days".into(), json!({"type": "range", "start": day_start, "end": day_end}));
+                Ok(())
+            }
+            Some(logic) => Err(SchemaError::Malformed(format!("unknown version 1 node `{}`", logic))),
+            None => Err(SchemaError::Malformed("missing field `logic`".into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(json: &str) -> DaySpec {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_season_days() {
+        let summer_y2 = spec(r#"{"type": "season", "season": 1, "year": 2}"#);
+        assert_eq!(summer_y2.days().collect::<Vec<_>>(), (141..=168).collect::<Vec<_>>());
+        // The year defaults to 1, like the web UI
+        assert_eq!(spec(r#"{"type": "season", "season": 3}"#).bounds(), (85, 112));
+    }
+
+    #[test]
+    fn test_weekdays_and_every_nth() {
+        // Fridays and Sundays of Spring Y1
+        let cart_days = spec(
+            r#"{"type": "weekdays", "weekdays": [5, 7], "within": {"type": "season", "season": 0}}"#,
+        );
+        assert_eq!(cart_days.days().collect::<Vec<_>>(), vec![5, 7, 12, 14, 19, 21, 26, 28]);
+
+        let every_third = spec(r#"{"type": "every_nth", "start": 2, "end": 12, "step": 3}"#);
+        assert_eq!(every_third.days().collect::<Vec<_>>(), vec![2, 5, 8, 11]);
+
+        // A zero step never matches rather than dividing by zero
+        assert_eq!(spec(r#"{"type": "every_nth", "start": 1, "end": 5, "step": 0}"#).days().count(), 0);
+    }
+
+    #[test]
+    fn test_list_days() {
+        let list = spec(r#"{"type": "list", "days": [9, 3, 27]}"#);
+        assert_eq!(list.days().collect::<Vec<_>>(), vec![3, 9, 27]);
+        assert_eq!(spec(r#"{"type": "list", "days": []}"#).days().count(), 0);
+    }
+
+    #[test]
+    fn test_far_apart_days_are_not_walked() {
+        // Only the listed days and every step are visited, not the span between
+        let list = spec(r#"{"type": "list", "days": [2000000000, 1, 1]}"#);
+        assert_eq!(list.days().collect::<Vec<_>>(), vec![1, 2000000000]);
+        let sparse = spec(r#"{"type": "every_nth", "start": 1, "end": 2000000000, "step": 500000000}"#);
+        assert_eq!(sparse.days().collect::<Vec<_>>(), vec![1, 500000001, 1000000001, 1500000001]);
+        let fridays = spec(r#"{"type": "weekdays", "weekdays": [5], "within": {"type": "list", "days": [5, 6, 2000000001]}}"#);
+        assert_eq!(fridays.days().collect::<Vec<_>>(), vec![5]);
+    }
+
+    #[test]
+    fn test_weekday_counts_match_the_days() {
+        for json in [
+            r#"{"type": "exact", "day": 12}"#,
+            r#"{"type": "range", "start": 3, "end": 60}"#,
+            r#"{"type": "range", "start": 9, "end": 3}"#,
+            r#"{"type": "season", "season": 2, "year": 3}"#,
+            r#"{"type": "list", "days": [5, 5, 12, 40]}"#,
+            r#"{"type": "every_nth", "start": 2, "end": 300, "step": 3}"#,
+            r#"{"type": "every_nth", "start": 4, "end": 200, "step": 14}"#,
+            r#"{"type": "weekdays", "weekdays": [5, 7], "within": {"type": "every_nth", "start": 1, "end": 100, "step": 4}}"#,
+        ] {
+            let days = spec(json);
+            let mut expected = [0; 7];
+            for day in days.days() {
+                expected[weekday(i64::from(day)) as usize - 1] += 1;
+            }
+            assert_eq!(days.weekday_counts(), expected, "{}", json);
+        }
+        let wide = spec(r#"{"type": "range", "start": 1, "end": 2000000000}"#);
+        assert_eq!(wide.day_count(), 2_000_000_000);
+    }
+
+    #[test]
+    fn test_huge_years_do_not_overflow() {
+        let far = spec(r#"{"type": "season", "season": 1, "year": 20000000}"#);
+        assert_eq!(far.bounds(), (i32::MAX, i32::MAX));
+        assert!(!far.contains(1));
+        let past = spec(r#"{"type": "season", "season": 3, "year": -20000000}"#);
+        assert_eq!(past.bounds(), (i32::MIN, i32::MIN));
+    }
+
+    #[test]
+    fn test_condition_days_forms() {
+        let legacy: FilterCondition = serde_json::from_str(
+            r#"{"type": "dish_of_day", "day_start": 3, "day_end": 9, "dish_id": 210}"#,
+        )
+        .unwrap();
+        let spec: FilterCondition = serde_json::from_str(
+            r#"{"type": "dish_of_day", "days": {"type": "range", "start": 3, "end": 9}, "dish_id": 210}"#,
+        )
+        .unwrap();
+        match (legacy, spec) {
+            (FilterCondition::DishOfDay { days: a, .. }, FilterCondition::DishOfDay { days: b, .. }) => {
+                assert_eq!(a, DaySpec::Range { start: 3, end: 9 });
+                assert_eq!(a, b);
+            }
+            _ => panic!("Expected DishOfDay conditions"),
+        }
+
+        // Both forms at once, or neither, is ambiguous
+        assert!(serde_json::from_str::<FilterCondition>(
+            r#"{"type": "dish_of_day", "days": {"type": "exact", "day": 1}, "day_start": 1, "day_end": 2, "dish_id": 210}"#,
+        )
+        .is_err());
+        assert!(serde_json::from_str::<FilterCondition>(r#"{"type": "dish_of_day", "dish_id": 210}"#).is_err());
+    }
+
+    fn document(filter: &FilterNode) -> Value {
+        serde_json::to_value(VersionedFilter::new(filter.clone())).unwrap()
+    }
+
+    /// Every node, condition and day spec in one filter, with optional fields set.
+    const EVERYTHING: &str = r#"{"logic": "and", "conditions": [
+        {"logic": "or", "conditions": [
+            {"logic": "condition", "type": "daily_luck", "days": {"type": "exact", "day": 1}, "min_luck": 0.05, "max_luck": 0.1},
+            {"logic": "condition", "type": "cart_item", "days": {"type": "weekdays", "weekdays": [5, 7], "within": {"type": "season", "season": 0, "year": 1}}, "item_id": 266, "max_price": 500}
+        ]},
+        {"logic": "not", "condition": {"logic": "condition", "type": "night_event", "days": {"type": "range", "start": 1, "end": 28}, "event_type": "fairy"}},
+        {"logic": "xor", "conditions": [
+            {"logic": "condition", "type": "geode", "geode_number": 1, "geode_type": "omni", "target_items": [74]},
+            {"logic": "condition", "type": "dish_of_day", "days": {"type": "list", "days": [3, 9]}, "dish_id": 200}
+        ]},
+        {"logic": "at_least", "count": 1, "conditions": [
+            {"logic": "condition", "type": "weather", "days": {"type": "every_nth", "start": 2, "end": 20, "step": 3}, "weather_type": "rain"},
+            {"logic": "condition", "type": "mine_floor", "days": {"type": "exact", "day": 5}, "floor_start": 81, "floor_end": 100,
+             "no_monsters": true, "no_dark": false, "has_mushroom": true}
+        ]},
+        {"logic": "count_days", "condition": {"type": "sequence", "days": {"type": "range", "start": 1, "end": 28},
+            "steps": [
+                {"event": {"type": "night_event", "event_type": "any"}},
+                {"event": {"type": "daily_luck", "min_luck": 0.0, "max_luck": 0.1}, "min_after": 1, "max_after": 3, "first": true},
+                {"event": {"type": "cart_item", "item_id": 266, "max_price": 900}},
+                {"event": {"type": "dish_of_day", "dish_id": 200}},
+                {"event": {"type": "weather", "weather_type": "storm"}}
+            ]}, "at_least": 1, "at_most": 2}
+    ]}"#;
+
+    #[test]
+    fn test_serialize_round_trip() {
+        let filter: FilterNode = serde_json::from_str(EVERYTHING).unwrap();
+        let migrated = migrate_filter(document(&filter)).unwrap();
+        assert_eq!(serde_json::from_value::<FilterNode>(migrated).unwrap(), filter);
+
+        // Older day fields come back out as a `days` spec
+        let legacy: FilterNode = serde_json::from_str(
+            r#"{"logic": "condition", "type": "dish_of_day", "day_start": 3, "day_end": 9, "dish_id": 210}"#,
+        )
+        .unwrap();
+        let json = serde_json::to_value(&legacy).unwrap();
+        assert_eq!(json["days"], serde_json::json!({"type": "range", "start": 3, "end": 9}));
+        assert!(json.get("day_start").is_none());
+    }
+
+    #[test]
+    fn test_migrate_v1_document() {
+        // The shape filters had before day specs and the newer logic nodes
+        let v1 = serde_json::json!({
+            "schema_version": 1,
+            "filter": {"logic": "or", "conditions": [
+                {"logic": "condition", "type": "night_event", "day_start": 1, "day_end": 28, "event_type": "fairy"},
+                {"logic": "and", "conditions": [
+                    {"logic": "condition", "type": "geode", "geode_number": 2, "geode_type": "frozen", "target_items": [74]},
+                    {"logic": "condition", "type": "cart_item", "day_start": 5, "day_end": 7, "item_id": 266, "max_price": null}
+                ]}
+            ]}
+        });
+        let migrated = migrate_filter(v1).unwrap();
+        assert_eq!(migrated["conditions"][0]["days"], serde_json::json!({"type": "range", "start": 1, "end": 28}));
+
+        let expected: FilterNode = serde_json::from_str(
+            r#"{"logic": "or", "conditions": [
+                {"logic": "condition", "type": "night_event", "days": {"type": "range", "start": 1, "end": 28}, "event_type": "fairy"},
+                {"logic": "and", "conditions": [
+                    {"logic": "condition", "type": "geode", "geode_number": 2, "geode_type": "frozen", "target_items": [74]},
+                    {"logic": "condition", "type": "cart_item", "days": {"type": "range", "start": 5, "end": 7}, "item_id": 266, "max_price": null}
+                ]}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(serde_json::from_value::<FilterNode>(migrated).unwrap(), expected);
+
+        // Version 1 predates `days`, so a v1 document using it is malformed
+        let mixed = serde_json::json!({"schema_version": 1, "filter":
+            {"logic": "condition", "type": "dish_of_day", "days": {"type": "exact", "day": 1}, "dish_id": 200}});
+        assert!(matches!(migrate_filter(mixed), Err(SchemaError::Malformed(_))));
+
+        // So are nodes it didn't have and half a day range
+        for filter in [
+            serde_json::json!({"logic": "not", "condition": {"logic": "and", "conditions": []}}),
+            serde_json::json!({"logic": "condition", "type": "dish_of_day", "day_start": 1, "dish_id": 200}),
+        ] {
+            let document = serde_json::json!({"schema_version": 1, "filter": filter});
+            assert!(matches!(migrate_filter(document), Err(SchemaError::Malformed(_))));
+        }
+    }
+
+    #[test]
+    fn test_unsupported_documents() {
+        let filter = serde_json::json!({"logic": "and", "conditions": []});
+        assert_eq!(
+            migrate_filter(serde_json::json!({"schema_version": 3, "filter": filter})),
+            Err(SchemaError::UnsupportedVersion(3))
+        );
+        assert_eq!(
+            migrate_filter(serde_json::json!({"schema_version": 0, "filter": filter})),
+            Err(SchemaError::UnsupportedVersion(0))
+        );
+        assert!(matches!(
+            migrate_filter(serde_json::json!({"schema_version": 2})),
+            Err(SchemaError::Malformed(_))
+        ));
+        // A bare filter is already current
+        assert_eq!(migrate_filter(filter.clone()), Ok(filter));
+    }
+
+    /// Check an object's keys against a schema definition, both ways.
+    fn check_keys(definition: &Value, value: &Value, ignore: &[&str]) {
+        let allowed = definition["properties"].as_object().unwrap();
+        let object = value.as_object().unwrap();
+        for key in object.keys().filter(|key| !ignore.contains(&key.as_str())) {
+            assert!(allowed.contains_key(key), "`{}` of {} is missing from the schema", key, value);
+        }
+        for key in definition["required"].as_array().into_iter().flatten() {
+            assert!(object.contains_key(key.as_str().unwrap()), "{} lacks required `{}`", value, key);
+        }
+    }
+
+    /// The variant of a `oneOf` whose `type` is `tag`.
+    fn variant<'a>(schema: &'a Value, one_of: &'a Value, tag: &str) -> &'a Value {
+        one_of["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|option| match option["$ref"].as_str() {
+                Some(reference) => &schema["$defs"][reference.trim_start_matches("#/$defs/")],
+                None => option,
+            })
+            .find(|option| option["properties"]["type"]["const"] == tag)
+            .unwrap_or_else(|| panic!("no `{}` in the schema", tag))
+    }
+
+    fn check_days(schema: &Value, days: &Value) {
+        let definition = variant(schema, &schema["$defs"]["days"], days["type"].as_str().unwrap());
+        check_keys(definition, days, &[]);
+        if let Some(within) = days.get("within") {
+            check_days(schema, within);
+        }
+    }
+
+    fn check_condition(schema: &Value, condition: &Value, types: &mut Vec<String>) {
+        let tag = condition["type"].as_str().unwrap();
+        types.push(tag.to_string());
+        check_keys(variant(schema, &schema["$defs"]["condition"], tag), condition, &["logic"]);
+        if let Some(days) = condition.get("days") {
+            check_days(schema, days);
+        }
+        for step in condition["steps"].as_array().into_iter().flatten() {
+            check_keys(&schema["$defs"]["sequence_step"], step, &[]);
+            let event = &step["event"];
+            check_keys(variant(schema, &schema["$defs"]["day_event"], event["type"].as_str().unwrap()), event, &[]);
+        }
+    }
+
+    fn check_node(schema: &Value, node: &Value, types: &mut Vec<String>) {
+        match node["logic"].as_str().unwrap() {
+            "condition" => check_condition(schema, node, types),
+            logic => {
+                check_keys(&schema["$defs"][logic], node, &[]);
+                for child in node["conditions"].as_array().into_iter().flatten() {
+                    check_node(schema, child, types);
+                }
+                match (logic, node.get("condition")) {
+                    ("count_days", Some(condition)) => check_condition(schema, condition, types),
+                    (_, Some(child)) => check_node(schema, child, types),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_json_schema_matches_types() {
+        let schema: Value = serde_json::from_str(FILTER_JSON_SCHEMA).unwrap();
+        assert_eq!(schema["properties"]["schema_version"]["const"], FILTER_SCHEMA_VERSION);
+
+        let filter: FilterNode = serde_json::from_str(EVERYTHING).unwrap();
+        let document = document(&filter);
+        check_keys(&schema, &document, &[]);
+
+        let mut types = Vec::new();
+        check_node(&schema, &document["filter"], &mut types);
+        types.sort();
+        types.dedup();
+        assert_eq!(types.len(), schema["$defs"]["condition"]["oneOf"].as_array().unwrap().len());
+    }
 }
@@ -1,16 +1,40 @@
 //! Search kernel for Rasmodius.
 //!
 //! This module provides the `search_range` function that evaluates filters
-//! entirely in Rust/WASM for maximum performance.
+//! entirely in Rust/WASM for maximum performance, `search_range_into` which
+//! fills a buffer with matches instead, `search_range_checkpointed` and
+//! `search_range_into_checkpointed` which resume long searches, `search_top_k` which ranks seeds by a score,
+//! `explain_filter` which shows why a seed matched, `estimate_filter` which
+//! samples how rare a filter is, `validate_filter` which points at mistakes in
+//! a filter, `upgrade_filter` which migrates saved filters to the current
+//! schema, and `recover_seeds` which finds game IDs from in-game observations.
 
+mod blocks;
+mod checkpoint;
 mod filter;
 mod evaluate;
+mod estimate;
+mod explain;
+mod plan;
+mod recover;
+mod score;
+mod sequence;
+mod validate;
 
+pub use checkpoint::*;
 pub use filter::*;
 pub use evaluate::*;
+pub use estimate::*;
+pub use explain::*;
+pub use plan::*;
+pub use recover::*;
+pub use score::*;
+pub use validate::*;
 
+use crate::types::PlayerContext;
 use crate::version::GameVersion;
 use js_sys::Function;
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
 /// Search a range of seeds with a filter, calling callback for each match.
@@ -19,15 +43,16 @@ use wasm_bindgen::prelude::*;
 /// This avoids expensive WASM↔JS boundary crossings in the hot loop.
 ///
 /// # Arguments
-/// * `filter_json` - JSON string representing the filter tree
+/// * `filter_json` - Filter tree JSON, bare or in a `{"schema_version", "filter"}` document
 /// * `start_seed` - First seed to check (inclusive)
 /// * `end_seed` - Last seed to check (inclusive)
 /// * `max_results` - Stop after finding this many matches
-/// * `version` - Game version string ("1.6", "1.5", etc.)
+/// * `version` - Game version string ("1.6", "1.5.6", etc.); unknown versions are an error
 /// * `on_match` - Called for each matching seed with (seed). Return false to stop.
+/// * `context` - Optional `PlayerContext` object (undefined uses defaults)
 ///
 /// # Returns
-/// Ok(()) on success, Err with message on parse error
+/// Ok(()) on success, Err with message on an invalid filter or unknown version
 #[wasm_bindgen]
 pub fn search_range(
     filter_json: &str,
@@ -36,31 +61,435 @@ pub fn search_range(
     max_results: u32,
     version: &str,
     on_match: &Function,
+    context: JsValue,
 ) -> Result<(), JsValue> {
+    // Searching the wrong game silently is worse than failing
+    let game_version = parse_version(version)?;
+    let ctx = PlayerContext::from_js(context)?;
     // Parse filter once at the start
-    let filter: FilterNode = serde_json::from_str(filter_json)
-        .map_err(|e| JsValue::from_str(&format!("Filter parse error: {}", e)))?;
+    let filter = parse_filter(filter_json, game_version, &ctx)?;
+    let mut plan = FilterPlan::compile(&filter, game_version);
 
-    let game_version = GameVersion::parse(version);
+    if max_results > 0 {
+        scan_to_callback(&mut plan, start_seed, end_seed, max_results, &ctx, on_match)?;
+    }
+    Ok(())
+}
 
-    let mut matches = 0u32;
+/// Search a range of seeds with a filter, writing matches into a buffer.
+///
+/// Works like `search_range`, but fills `out` instead of calling back into JS
+/// for each match, so loose filters don't pay a boundary crossing per seed.
+/// The buffer length is the match limit: call again from `next_seed` while
+/// `stopped_early` is true to continue.
+///
+/// # Arguments
+/// * `out` - Caller's `Int32Array`; matches are written to its front
+/// * Other arguments as for `search_range`
+///
+/// # Returns
+/// `{count, next_seed, stopped_early}`, or Err with message on an invalid filter
+/// or unknown version
+#[wasm_bindgen]
+pub fn search_range_into(
+    filter_json: &str,
+    start_seed: i32,
+    end_seed: i32,
+    version: &str,
+    out: &mut [i32],
+    context: JsValue,
+) -> Result<JsValue, JsValue> {
+    let game_version = parse_version(version)?;
+    let ctx = PlayerContext::from_js(context)?;
+    let filter = parse_filter(filter_json, game_version, &ctx)?;
+    let mut plan = FilterPlan::compile(&filter, game_version);
+
+    let batch = plan.scan_into(start_seed, end_seed, &ctx, out);
+    Ok(serde_wasm_bindgen::to_value(&batch).unwrap())
+}
+
+/// Start a resumable search over `[start_seed, end_seed]`.
+///
+/// # Returns
+/// Checkpoint JSON with nothing checked yet, or Err with message on an invalid
+/// filter or a version or context parse error
+#[wasm_bindgen]
+pub fn new_checkpoint(
+    filter_json: &str,
+    start_seed: i32,
+    end_seed: i32,
+    version: &str,
+    context: JsValue,
+) -> Result<String, JsValue> {
+    let game_version = parse_version(version)?;
+    let ctx = PlayerContext::from_js(context)?;
+    parse_filter(filter_json, game_version, &ctx)?;
+    let checkpoint = Checkpoint::new(filter_json, game_version, &ctx, start_seed, end_seed)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    Ok(checkpoint.to_json())
+}
 
-    for seed in start_seed..=end_seed {
-        // Stop if we've found enough matches
-        if matches >= max_results {
+/// Search the unchecked seeds of `[start_seed, end_seed]`, resuming from a checkpoint.
+///
+/// Works like `search_range`, but skips seeds the checkpoint already covers and
+/// returns the checkpoint updated with the seeds this call checked. If the search
+/// stops early (max results or cancellation), only seeds up to the last match
+/// are recorded. Matches from earlier calls are not passed to `on_match` again.
+///
+/// # Arguments
+/// * `checkpoint_json` - From `new_checkpoint` or an earlier call
+/// * Other arguments as for `search_range`
+///
+/// # Returns
+/// Updated checkpoint JSON, or Err with message if the checkpoint was made with a
+/// different filter, version or context, or `[start_seed, end_seed]` reaches
+/// outside the checkpoint's range
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn search_range_checkpointed(
+    filter_json: &str,
+    checkpoint_json: &str,
+    start_seed: i32,
+    end_seed: i32,
+    max_results: u32,
+    version: &str,
+    on_match: &Function,
+    context: JsValue,
+) -> Result<String, JsValue> {
+    let game_version = parse_version(version)?;
+    let ctx = PlayerContext::from_js(context)?;
+    let filter = parse_filter(filter_json, game_version, &ctx)?;
+    let mut checkpoint = Checkpoint::resume(checkpoint_json, filter_json, game_version, &ctx)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    checkpoint
+        .verify_range(start_seed, end_seed)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let mut plan = FilterPlan::compile(&filter, game_version);
+
+    let mut results_left = max_results;
+    for (start, end) in checkpoint.remaining_within(start_seed, end_seed) {
+        if results_left == 0 {
+            break;
+        }
+        let (found, stopped_at) = scan_to_callback(&mut plan, start, end, results_left, &ctx, on_match)?;
+        results_left -= found.len() as u32;
+        checkpoint.record(start, stopped_at.unwrap_or(end), &found);
+        if stopped_at.is_some() {
             break;
         }
+    }
+
+    Ok(checkpoint.to_json())
+}
+
+/// Result of `search_range_into_checkpointed`.
+#[derive(Serialize)]
+struct CheckpointedBatch {
+    /// Updated checkpoint JSON
+    checkpoint: String,
+    count: u32,
+    stopped_early: bool,
+}
+
+/// Search the unchecked seeds of `[start_seed, end_seed]` into a buffer,
+/// resuming from a checkpoint.
+///
+/// Combines `search_range_into` and `search_range_checkpointed`: matches are
+/// written to the front of `out`, whose length is the match limit, and only
+/// seeds the checkpoint doesn't cover are checked. If the buffer fills up, the
+/// checkpoint records seeds up to the last match, so calling again with the
+/// same range and the returned checkpoint carries on from there.
+///
+/// # Arguments
+/// * `checkpoint_json` - From `new_checkpoint` or an earlier call
+/// * `out` - Caller's `Int32Array`; matches are written to its front
+/// * Other arguments as for `search_range`
+///
+/// # Returns
+/// `{checkpoint, count, stopped_early}`, or Err with message as for
+/// `search_range_checkpointed`
+#[wasm_bindgen]
+pub fn search_range_into_checkpointed(
+    filter_json: &str,
+    checkpoint_json: &str,
+    start_seed: i32,
+    end_seed: i32,
+    version: &str,
+    out: &mut [i32],
+    context: JsValue,
+) -> Result<JsValue, JsValue> {
+    let game_version = parse_version(version)?;
+    let ctx = PlayerContext::from_js(context)?;
+    let filter = parse_filter(filter_json, game_version, &ctx)?;
+    let mut checkpoint = Checkpoint::resume(checkpoint_json, filter_json, game_version, &ctx)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    checkpoint
+        .verify_range(start_seed, end_seed)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let mut plan = FilterPlan::compile(&filter, game_version);
 
-        // Evaluate filter
-        if evaluate_filter(seed, &filter, game_version) {
-            matches += 1;
-            // on_match returns false to signal cancellation (e.g., global maxResults hit)
-            let result = on_match.call1(&JsValue::NULL, &JsValue::from(seed))?;
-            if !result.as_bool().unwrap_or(true) {
-                break;
+    let batch = checkpoint.scan_into(&mut plan, start_seed, end_seed, &ctx, out);
+    let result = CheckpointedBatch {
+        checkpoint: checkpoint.to_json(),
+        count: batch.count,
+        stopped_early: batch.stopped_early,
+    };
+    Ok(serde_wasm_bindgen::to_value(&result).unwrap())
+}
+
+/// Combine two workers' checkpoints for the same search.
+///
+/// # Returns
+/// Merged checkpoint JSON, or Err with message if they belong to different searches
+#[wasm_bindgen]
+pub fn merge_checkpoints(checkpoint_json: &str, other_json: &str) -> Result<String, JsValue> {
+    let parse = |json: &str| {
+        serde_json::from_str::<Checkpoint>(json)
+            .map_err(|e| JsValue::from_str(&CheckpointError::Malformed(e.to_string()).to_string()))
+    };
+    let mut checkpoint = parse(checkpoint_json)?;
+    checkpoint
+        .merge(&parse(other_json)?)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    Ok(checkpoint.to_json())
+}
+
+/// Pass the plan's matches in `[start, end]` to `on_match`, at most `limit` of them.
+///
+/// Returns the matches and, if the search stopped before `end`, the seed it stopped at.
+fn scan_to_callback(
+    plan: &mut FilterPlan,
+    start: i32,
+    end: i32,
+    limit: u32,
+    ctx: &PlayerContext,
+    on_match: &Function,
+) -> Result<(Vec<i32>, Option<i32>), JsValue> {
+    let mut found = Vec::new();
+    let mut stopped_at = None;
+    let mut error = None;
+
+    // Seeds sharing `seed / 100` or `seed / 2` are decided together where the filter allows
+    plan.scan(start, end, ctx, |seed| {
+        found.push(seed);
+        // on_match returns false to signal cancellation (e.g., global maxResults hit)
+        let keep_going = match on_match.call1(&JsValue::NULL, &JsValue::from(seed)) {
+            Ok(result) => result.as_bool().unwrap_or(true) && (found.len() as u32) < limit,
+            Err(e) => {
+                error = Some(e);
+                false
             }
+        };
+        if !keep_going {
+            stopped_at = Some(seed);
         }
+        keep_going
+    });
+
+    match error {
+        Some(e) => Err(e),
+        None => Ok((found, stopped_at)),
     }
+}
 
-    Ok(())
+/// Explain why a seed matches a filter or not.
+///
+/// # Arguments
+/// * `seed` - Game ID to explain
+/// * `filter_json` - JSON string representing the filter tree
+/// * `version` - Game version string ("1.6", "1.5.6", etc.); unknown versions are an error
+/// * `context` - Optional `PlayerContext` object (undefined uses defaults)
+///
+/// # Returns
+/// A trace tree mirroring the filter: each node has `passed`, the days or geodes
+/// it checked with the values seen, and the `witnesses` that satisfied it
+#[wasm_bindgen]
+pub fn explain_filter(seed: i32, filter_json: &str, version: &str, context: JsValue) -> Result<JsValue, JsValue> {
+    let game_version = parse_version(version)?;
+    let ctx = PlayerContext::from_js(context)?;
+    let filter = parse_filter(filter_json, game_version, &ctx)?;
+
+    let trace = trace_filter(seed, &filter, game_version, &ctx);
+    Ok(serde_wasm_bindgen::to_value(&trace).unwrap())
+}
+
+/// Find the highest-scoring seeds in a range.
+///
+/// # Arguments
+/// * `score_json` - Score expression: weighted `terms`, plus an optional `filter` seeds must pass
+/// * `start_seed` - First seed to check (inclusive)
+/// * `end_seed` - Last seed to check (inclusive)
+/// * `k` - How many seeds to keep
+/// * `version` - Game version string ("1.6", "1.5.6", etc.); unknown versions are an error
+/// * `context` - Optional `PlayerContext` object (undefined uses defaults)
+///
+/// # Returns
+/// Up to `k` `{seed, score}` objects, best first (ties go to the lower seed), or
+/// Err with message on a score expression or version parse error
+#[wasm_bindgen]
+pub fn search_top_k(
+    score_json: &str,
+    start_seed: i32,
+    end_seed: i32,
+    k: u32,
+    version: &str,
+    context: JsValue,
+) -> Result<JsValue, JsValue> {
+    let mut value: serde_json::Value = serde_json::from_str(score_json)
+        .map_err(|e| JsValue::from_str(&format!("Score parse error: {}", e)))?;
+    // The filter is read separately so it can be validated (and be a versioned document)
+    let filter = value.as_object_mut().and_then(|score| score.remove("filter"));
+    let mut expr = ScoreExpr::deserialize(&value)
+        .map_err(|e| JsValue::from_str(&format!("Score parse error: {}", e)))?;
+    let game_version = parse_version(version)?;
+    let ctx = PlayerContext::from_js(context)?;
+    if let Some(filter) = filter.filter(|filter| !filter.is_null()) {
+        let filter = validate_filter_value(&filter, game_version, &ctx).map_err(|mut errors| {
+            for error in &mut errors {
+                error.path.insert_str(0, "/filter");
+            }
+            invalid_filter(&errors)
+        })?;
+        expr.filter = Some(filter);
+    }
+
+    let best = top_k_in_range(&expr, start_seed, end_seed, k as usize, game_version, &ctx);
+    Ok(serde_wasm_bindgen::to_value(&best).unwrap())
+}
+
+/// Estimate how rare a filter is from a random sample of seeds.
+///
+/// # Arguments
+/// * `filter_json` - JSON string representing the filter tree
+/// * `start_seed` - First seed of the range to sample (inclusive)
+/// * `end_seed` - Last seed of the range to sample (inclusive)
+/// * `samples` - How many seeds to evaluate, 1 to 1,000,000; the sample is the same on every call
+/// * `version` - Game version string ("1.6", "1.5.6", etc.); unknown versions are an error
+/// * `context` - Optional `PlayerContext` object (undefined uses defaults)
+///
+/// # Returns
+/// The match rate with a 95% interval, measured seeds per second, expected
+/// seeds and seconds to a first match, and each condition's pass rate
+#[wasm_bindgen]
+pub fn estimate_filter(
+    filter_json: &str,
+    start_seed: i32,
+    end_seed: i32,
+    samples: u32,
+    version: &str,
+    context: JsValue,
+) -> Result<JsValue, JsValue> {
+    let game_version = parse_version(version)?;
+    let ctx = PlayerContext::from_js(context)?;
+    let filter = parse_filter(filter_json, game_version, &ctx)?;
+    if samples == 0 {
+        return Err(JsValue::from_str("samples must be at least 1"));
+    }
+    if samples > MAX_SAMPLES {
+        return Err(JsValue::from_str(&format!("samples must be at most {}", MAX_SAMPLES)));
+    }
+    if start_seed > end_seed {
+        return Err(JsValue::from_str("start_seed must not exceed end_seed"));
+    }
+
+    let estimate = estimate_in_range(&filter, start_seed, end_seed, samples, game_version, &ctx);
+    Ok(serde_wasm_bindgen::to_value(&estimate).unwrap())
+}
+
+/// Check a filter without searching.
+///
+/// Rejects unknown event, weather and geode names, inverted day and floor
+/// ranges, luck bounds outside ±0.1, and conditions that can't happen on
+/// their days in this version (a fairy in winter, a capsule in year 1) when
+/// they leave the whole filter unable to match.
+///
+/// # Arguments
+/// * `filter_json` - JSON string representing the filter tree
+/// * `version` - Game version string ("1.6", "1.5.6", etc.); unknown versions are an error
+/// * `context` - Optional `PlayerContext` object (undefined uses defaults)
+///
+/// # Returns
+/// An array of `{path, message}` errors, where `path` is a JSON pointer into
+/// the filter; empty if the filter is valid
+#[wasm_bindgen]
+pub fn validate_filter(filter_json: &str, version: &str, context: JsValue) -> Result<JsValue, JsValue> {
+    let game_version = parse_version(version)?;
+    let ctx = PlayerContext::from_js(context)?;
+    let errors = validate_filter_json(filter_json, game_version, &ctx).err().unwrap_or_default();
+    Ok(serde_wasm_bindgen::to_value(&errors).unwrap())
+}
+
+/// Upgrade filter JSON from any schema version to the current one.
+///
+/// # Arguments
+/// * `filter_json` - A versioned `{"schema_version", "filter"}` document or a bare filter
+///
+/// # Returns
+/// A `{"schema_version", "filter"}` document in the current schema, or Err with
+/// message if the filter can't be read or comes from a newer version
+#[wasm_bindgen]
+pub fn upgrade_filter(filter_json: &str) -> Result<String, JsValue> {
+    let document: serde_json::Value = serde_json::from_str(filter_json)
+        .map_err(|e| JsValue::from_str(&format!("Filter parse error: {}", e)))?;
+    let value = migrate_filter(document).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let filter = FilterNode::deserialize(&value)
+        .map_err(|e| JsValue::from_str(&format!("Filter parse error: {}", e)))?;
+    Ok(serde_json::to_string(&VersionedFilter::new(filter)).expect("filters always serialize"))
+}
+
+/// The JSON Schema for versioned filter documents, for tools that generate filters.
+#[wasm_bindgen]
+pub fn filter_json_schema() -> String {
+    FILTER_JSON_SCHEMA.to_string()
+}
+
+/// Find every game ID in a range consistent with a list of observations.
+///
+/// # Arguments
+/// * `observations_json` - JSON array of observations (dish, cart, geode, night event)
+/// * `start_seed` - First seed to check (inclusive)
+/// * `end_seed` - Last seed to check (inclusive)
+/// * `max_results` - Stop after finding this many matches
+/// * `version` - Game version string ("1.6", "1.5.6", etc.); unknown versions are an error
+/// * `context` - Optional `PlayerContext` object (undefined uses defaults)
+///
+/// # Returns
+/// Matching seeds in ascending order, or Err with message on an observation or version parse error
+#[wasm_bindgen]
+pub fn recover_seeds(
+    observations_json: &str,
+    start_seed: i32,
+    end_seed: i32,
+    max_results: u32,
+    version: &str,
+    context: JsValue,
+) -> Result<Vec<i32>, JsValue> {
+    let observations: Vec<Observation> = serde_json::from_str(observations_json)
+        .map_err(|e| JsValue::from_str(&format!("Observation parse error: {}", e)))?;
+    let game_version = parse_version(version)?;
+    let ctx = PlayerContext::from_js(context)?;
+
+    Ok(recover_seeds_in_range(
+        &observations,
+        start_seed,
+        end_seed,
+        max_results as usize,
+        game_version,
+        &ctx,
+    ))
+}
+
+/// Parse and validate a filter for the search exports.
+fn parse_filter(filter_json: &str, version: GameVersion, ctx: &PlayerContext) -> Result<FilterNode, JsValue> {
+    validate_filter_json(filter_json, version, ctx).map_err(|errors| invalid_filter(&errors))
+}
+
+fn invalid_filter(errors: &[ValidationError]) -> JsValue {
+    let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+    JsValue::from_str(&format!("Invalid filter: {}", errors.join("; ")))
+}
+
+/// Strictly parse a version string for the WASM exports.
+pub(crate) fn parse_version(version: &str) -> Result<GameVersion, JsValue> {
+    GameVersion::try_parse(version).map_err(|e| JsValue::from_str(&e.to_string()))
 }
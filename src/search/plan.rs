@@ -0,0 +1,1104 @@
+//! Filter planning.
+//!
+//! `evaluate_filter` walks the tree in the order it was written. A plan is
+//! compiled once per search instead: it estimates what each condition costs
+//! to check and how often it matches, orders AND/OR children so cheap,
+//! selective conditions short-circuit the rest, and checks a condition that
+//! appears more than once at most once per seed.
+//!
+//! Leaves also know how much of the seed they depend on, so a range scan can
+//! decide whole `seed / 100` and `seed / 2` runs from one representative.
+
+use serde::Serialize;
+
+use super::blocks::quotient_blocks;
+use super::evaluate::{count_days_in_bounds, evaluate_condition, parse_weather};
+use super::filter::{DayEvent, DaySpec, FilterCondition, FilterNode, SequenceStep};
+use super::recover::SeedDependency;
+use super::sequence::sequence_reach;
+use crate::mechanics::Weather;
+use crate::types::PlayerContext;
+use crate::version::GameVersion;
+
+// Rough per-day costs, in RNG samples
+const LUCK_COST: f64 = 25.0;
+const DISH_COST: f64 = 20.0;
+const NIGHT_COST: f64 = 8.0;
+const NIGHT_COST_V16: f64 = 15.0;
+const WEATHER_COST: f64 = 30.0;
+const CART_COST: f64 = 60.0;
+/// 1.6 rolls a shuffle key for every object before picking the stock
+const CART_COST_V16: f64 = 900.0;
+/// A price limit needs the whole stock instead of the early-exit item check
+const CART_PRICE_COST_V16: f64 = 1000.0;
+const GEODE_COST: f64 = 30.0;
+const MINE_FLOOR_COST: f64 = 6.0;
+
+// Rough per-day chances
+const DISH_CHANCE: f64 = 1.0 / 39.0;
+const NIGHT_EVENT_CHANCE: f64 = 0.01;
+const ANY_NIGHT_EVENT_CHANCE: f64 = 0.04;
+const CART_ITEM_CHANCE: f64 = 0.02;
+const MONSTER_FLOOR_CHANCE: f64 = 0.044;
+const DARK_FLOOR_CHANCE: f64 = 0.05;
+const MUSHROOM_FLOOR_CHANCE: f64 = 0.035;
+
+/// Longest `count_days` span whose match chance is summed exactly; wider
+/// spans use a normal approximation.
+const EXACT_COUNT_DAYS: u64 = 10_000;
+
+/// What `FilterPlan::scan_into` wrote and where to carry on from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ScanBatch {
+    /// Matches written to the front of the buffer
+    pub count: u32,
+    /// First seed not checked yet (`end_seed + 1` once the range is done)
+    pub next_seed: i64,
+    /// Whether the buffer filled up before the end of the range
+    pub stopped_early: bool,
+}
+
+/// A filter compiled for repeated evaluation.
+pub struct FilterPlan {
+    root: PlanNode,
+    leaves: Leaves,
+    cost: f64,
+    match_rate: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PlanNode {
+    And(Vec<PlanNode>),
+    Or(Vec<PlanNode>),
+    Not(Box<PlanNode>),
+    Xor(Vec<PlanNode>),
+    AtLeast(u32, Vec<PlanNode>),
+    /// Index into the plan's leaves
+    Leaf(usize),
+}
+
+/// A condition check, deduplicated across the tree.
+#[derive(Debug, Clone, PartialEq)]
+enum Leaf {
+    /// Any of the condition's days match
+    Any(FilterCondition),
+    /// The number of matching days is within bounds (`count_days`)
+    Count {
+        condition: FilterCondition,
+        at_least: Option<u32>,
+        at_most: Option<u32>,
+    },
+}
+
+impl Leaf {
+    fn condition(&self) -> &FilterCondition {
+        match self {
+            Leaf::Any(condition) | Leaf::Count { condition, .. } => condition,
+        }
+    }
+}
+
+struct Leaves {
+    conditions: Vec<Leaf>,
+    /// How much of the seed each leaf depends on
+    dependencies: Vec<SeedDependency>,
+    /// Results for the current seed, kept while the part of the seed the
+    /// leaf depends on is unchanged
+    cache: Vec<Option<bool>>,
+    version: GameVersion,
+}
+
+impl Leaves {
+    /// The leaf's result, or None if it depends on more of the seed than `level`.
+    fn check(&mut self, index: usize, seed: i32, ctx: &PlayerContext, level: SeedDependency) -> Option<bool> {
+        if self.dependencies[index] > level {
+            return None;
+        }
+        if let Some(hit) = self.cache[index] {
+            return Some(hit);
+        }
+        let hit = match &self.conditions[index] {
+            Leaf::Any(cond) => evaluate_condition(seed, cond, self.version, ctx),
+            Leaf::Count {
+                condition,
+                at_least,
+                at_most,
+            } => count_days_in_bounds(seed, condition, *at_least, *at_most, self.version, ctx),
+        };
+        self.cache[index] = Some(hit);
+        Some(hit)
+    }
+
+    /// Forget results that may differ once the seed leaves the current
+    /// `level` block (everything for a new `seed / 100` block).
+    fn invalidate(&mut self, level: SeedDependency) {
+        for (cached, dependency) in self.cache.iter_mut().zip(&self.dependencies) {
+            if *dependency >= level {
+                *cached = None;
+            }
+        }
+    }
+}
+
+impl FilterPlan {
+    /// Compile a filter tree for `version`.
+    pub fn compile(filter: &FilterNode, version: GameVersion) -> Self {
+        let mut compiler = Compiler {
+            leaves: Vec::new(),
+            version,
+        };
+        let planned = compiler.compile(filter);
+        let dependencies = compiler
+            .leaves
+            .iter()
+            .map(|leaf| condition_dependency(leaf.condition(), version))
+            .collect();
+        let cache = vec![None; compiler.leaves.len()];
+
+        Self {
+            root: planned.node,
+            leaves: Leaves {
+                conditions: compiler.leaves,
+                dependencies,
+                cache,
+                version,
+            },
+            cost: planned.cost,
+            match_rate: planned.p,
+        }
+    }
+
+    /// Whether `seed` matches. Gives the same answer as `evaluate_filter`.
+    pub fn matches(&mut self, seed: i32, ctx: &PlayerContext) -> bool {
+        self.leaves.invalidate(SeedDependency::Quotient100);
+        self.eval_at(seed, ctx, SeedDependency::Full).unwrap_or(false)
+    }
+
+    /// Call `on_match` with each matching seed in `[start_seed, end_seed]`,
+    /// in order, until it returns false.
+    ///
+    /// Conditions that only see `seed / 100` or `seed / 2` are checked once
+    /// per run of seeds sharing that quotient, and runs the filter already
+    /// decides are rejected or accepted whole.
+    pub fn scan(
+        &mut self,
+        start_seed: i32,
+        end_seed: i32,
+        ctx: &PlayerContext,
+        mut on_match: impl FnMut(i32) -> bool,
+    ) {
+        self.scan_level(start_seed, end_seed, SeedDependency::Quotient100, ctx, &mut on_match);
+    }
+
+    /// Write matching seeds in `[start_seed, end_seed]` to `out`, in order,
+    /// stopping once it is full.
+    pub fn scan_into(
+        &mut self,
+        start_seed: i32,
+        end_seed: i32,
+        ctx: &PlayerContext,
+        out: &mut [i32],
+    ) -> ScanBatch {
+        let done = |count| ScanBatch {
+            count,
+            next_seed: end_seed as i64 + 1,
+            stopped_early: false,
+        };
+        if start_seed > end_seed {
+            return done(0);
+        }
+        if out.is_empty() {
+            return ScanBatch {
+                count: 0,
+                next_seed: start_seed as i64,
+                stopped_early: true,
+            };
+        }
+
+        let mut count = 0;
+        let mut last = start_seed;
+        self.scan(start_seed, end_seed, ctx, |seed| {
+            out[count] = seed;
+            count += 1;
+            last = seed;
+            count < out.len()
+        });
+
+        // A buffer that fills on the last seed still finished the range
+        if count == out.len() && last < end_seed {
+            ScanBatch {
+                count: count as u32,
+                next_seed: last as i64 + 1,
+                stopped_early: true,
+            }
+        } else {
+            done(count as u32)
+        }
+    }
+
+    /// Scan `[start, end]` in runs that share the quotient for `level`.
+    /// Returns false once `on_match` asks to stop.
+    fn scan_level(
+        &mut self,
+        start: i32,
+        end: i32,
+        level: SeedDependency,
+        ctx: &PlayerContext,
+        on_match: &mut impl FnMut(i32) -> bool,
+    ) -> bool {
+        let (divisor, finer) = match level {
+            SeedDependency::Quotient100 => (100, SeedDependency::Quotient2),
+            SeedDependency::Quotient2 => (2, SeedDependency::Full),
+            SeedDependency::Full => (1, SeedDependency::Full),
+        };
+
+        for (block_start, block_end) in quotient_blocks(start, end, divisor) {
+            self.leaves.invalidate(level);
+            let decided = self.eval_at(block_start, ctx, level);
+            let keep_going = match decided {
+                Some(false) => true,
+                Some(true) => (block_start..=block_end).all(&mut *on_match),
+                None => self.scan_level(block_start, block_end, finer, ctx, on_match),
+            };
+            if !keep_going {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Evaluate with only the leaves that depend on at most `level`;
+    /// None when the rest of the seed could still change the answer.
+    fn eval_at(&mut self, seed: i32, ctx: &PlayerContext, level: SeedDependency) -> Option<bool> {
+        eval(&self.root, &mut self.leaves, seed, ctx, level)
+    }
+
+    /// Expected RNG samples to evaluate one seed (a rough estimate).
+    pub fn estimated_cost(&self) -> f64 {
+        self.cost
+    }
+
+    /// Expected fraction of seeds that match (a rough estimate).
+    pub fn estimated_match_rate(&self) -> f64 {
+        self.match_rate
+    }
+}
+
+/// Three-valued evaluation: None means "depends on the rest of the seed".
+fn eval(
+    node: &PlanNode,
+    leaves: &mut Leaves,
+    seed: i32,
+    ctx: &PlayerContext,
+    level: SeedDependency,
+) -> Option<bool> {
+    match node {
+        PlanNode::And(children) => {
+            let mut result = Some(true);
+            for child in children {
+                match eval(child, leaves, seed, ctx, level) {
+                    Some(false) => return Some(false),
+                    Some(true) => {}
+                    None => result = None,
+                }
+            }
+            result
+        }
+        PlanNode::Or(children) => {
+            let mut result = Some(false);
+            for child in children {
+                match eval(child, leaves, seed, ctx, level) {
+                    Some(true) => return Some(true),
+                    Some(false) => {}
+                    None => result = None,
+                }
+            }
+            result
+        }
+        PlanNode::Not(child) => eval(child, leaves, seed, ctx, level).map(|hit| !hit),
+        PlanNode::Xor(children) => {
+            let (mut hits, mut unknown) = (0, 0);
+            for child in children {
+                match eval(child, leaves, seed, ctx, level) {
+                    Some(true) => hits += 1,
+                    Some(false) => {}
+                    None => unknown += 1,
+                }
+                if hits > 1 {
+                    return Some(false);
+                }
+            }
+            match (hits, unknown) {
+                (_, 0) => Some(hits == 1),
+                _ => None,
+            }
+        }
+        PlanNode::AtLeast(count, children) => {
+            let count = *count as usize;
+            let (mut hits, mut unknown) = (0, 0);
+            for (i, child) in children.iter().enumerate() {
+                if hits >= count {
+                    break;
+                }
+                match eval(child, leaves, seed, ctx, level) {
+                    Some(true) => hits += 1,
+                    Some(false) => {}
+                    None => unknown += 1,
+                }
+                // Not enough children left to reach the count
+                if hits + unknown + (children.len() - i - 1) < count {
+                    return Some(false);
+                }
+            }
+            if hits >= count {
+                Some(true)
+            } else if unknown > 0 {
+                None
+            } else {
+                Some(false)
+            }
+        }
+        PlanNode::Leaf(index) => leaves.check(*index, seed, ctx, level),
+    }
+}
+
+/// How much of the seed a condition's result depends on.
+fn condition_dependency(cond: &FilterCondition, version: GameVersion) -> SeedDependency {
+    match cond {
+        FilterCondition::DailyLuck { .. } | FilterCondition::DishOfDay { .. } => SeedDependency::Quotient100,
+        FilterCondition::Weather { days, .. } => weather_dependency(days, version),
+        FilterCondition::NightEvent { .. } | FilterCondition::Geode { .. } | FilterCondition::MineFloor { .. } => {
+            SeedDependency::Quotient2
+        }
+        FilterCondition::CartItem { .. } => cart_dependency(version),
+        FilterCondition::Sequence { days, steps } => steps
+            .iter()
+            .map(|step| match step.event {
+                DayEvent::DailyLuck { .. } | DayEvent::DishOfDay { .. } => SeedDependency::Quotient100,
+                DayEvent::NightEvent { .. } => SeedDependency::Quotient2,
+                DayEvent::CartItem { .. } => cart_dependency(version),
+                // A step can land after the window, so check the whole reach
+                DayEvent::Weather { .. } => {
+                    let (first, last) = days.bounds();
+                    let end = last.saturating_add(sequence_reach(steps));
+                    weather_dependency(&DaySpec::Range { start: first, end }, version)
+                }
+            })
+            .max()
+            .unwrap_or(SeedDependency::Quotient100),
+    }
+}
+
+/// Before 1.6 the cart is seeded with the full game ID plus the day.
+fn cart_dependency(version: GameVersion) -> SeedDependency {
+    if version.has_new_cart_system() {
+        SeedDependency::Quotient2
+    } else {
+        SeedDependency::Full
+    }
+}
+
+/// Weather rolls only see `seed / 100`, but 1.6's green rain day is picked
+/// from the full game ID, so forecasts that reach summer need all of it.
+fn weather_dependency(days: &DaySpec, version: GameVersion) -> SeedDependency {
+    let (first, last) = days.bounds();
+    // Forecasts are chained from the start of the first day's season
+    let season_start = first - (first - 1).rem_euclid(28);
+    let reaches_summer = (season_start..=last).any(|day| (day - 1).div_euclid(28).rem_euclid(4) == 1);
+    if version.has_green_rain() && reaches_summer {
+        SeedDependency::Full
+    } else {
+        SeedDependency::Quotient100
+    }
+}
+
+/// A compiled subtree with its expected cost and match probability.
+struct Planned {
+    node: PlanNode,
+    cost: f64,
+    p: f64,
+}
+
+struct Compiler {
+    leaves: Vec<Leaf>,
+    version: GameVersion,
+}
+
+impl Compiler {
+    fn compile(&mut self, node: &FilterNode) -> Planned {
+        match node {
+            FilterNode::And { conditions } => {
+                let mut children = self.compile_flat(conditions, true);
+                // Cheapest per chance of failing first
+                sort_by_key(&mut children, |c| c.cost / (1.0 - c.p).max(f64::EPSILON));
+                if children.len() == 1 {
+                    return children.pop().unwrap();
+                }
+                let (mut cost, mut p) = (0.0, 1.0);
+                for child in &children {
+                    cost += p * child.cost;
+                    p *= child.p;
+                }
+                Planned {
+                    node: PlanNode::And(children.into_iter().map(|c| c.node).collect()),
+                    cost,
+                    p,
+                }
+            }
+
+            FilterNode::Or { conditions } => {
+                let mut children = self.compile_flat(conditions, false);
+                // Cheapest per chance of matching first
+                sort_by_key(&mut children, |c| c.cost / c.p.max(f64::EPSILON));
+                if children.len() == 1 {
+                    return children.pop().unwrap();
+                }
+                let (mut cost, mut miss) = (0.0, 1.0);
+                for child in &children {
+                    cost += miss * child.cost;
+                    miss *= 1.0 - child.p;
+                }
+                Planned {
+                    node: PlanNode::Or(children.into_iter().map(|c| c.node).collect()),
+                    cost,
+                    p: 1.0 - miss,
+                }
+            }
+
+            FilterNode::Not { condition } => {
+                let inner = self.compile(condition);
+                let node = match inner.node {
+                    PlanNode::Not(child) => *child,
+                    node => PlanNode::Not(Box::new(node)),
+                };
+                Planned {
+                    node,
+                    cost: inner.cost,
+                    p: 1.0 - inner.p,
+                }
+            }
+
+            FilterNode::Xor { conditions } => {
+                let children = self.compile_counted(conditions);
+                let distribution = count_distribution(children.iter().map(|c| c.p));
+                Planned {
+                    cost: children.iter().map(|c| c.cost).sum(),
+                    p: distribution.get(1).copied().unwrap_or(0.0),
+                    node: PlanNode::Xor(children.into_iter().map(|c| c.node).collect()),
+                }
+            }
+
+            FilterNode::AtLeast { count, conditions } => {
+                let children = self.compile_counted(conditions);
+                let distribution = count_distribution(children.iter().map(|c| c.p));
+                Planned {
+                    cost: children.iter().map(|c| c.cost).sum(),
+                    p: distribution.iter().skip(*count as usize).sum(),
+                    node: PlanNode::AtLeast(*count, children.into_iter().map(|c| c.node).collect()),
+                }
+            }
+
+            FilterNode::CountDays {
+                condition,
+                at_least,
+                at_most,
+            } => {
+                let estimate = estimate(condition, self.version);
+                let cost = estimate.fixed_cost + estimate.day_cost * estimate.days as f64;
+                let p = if at_least.is_none() && at_most.is_none() {
+                    estimate.p.powf(estimate.days as f64)
+                } else {
+                    let min = at_least.map_or(0, u64::from);
+                    let max = at_most.map_or(u64::MAX, u64::from);
+                    binomial_between(estimate.days, estimate.p, min, max)
+                };
+                Planned {
+                    node: self.leaf(Leaf::Count {
+                        condition: (**condition).clone(),
+                        at_least: *at_least,
+                        at_most: *at_most,
+                    }),
+                    cost,
+                    p,
+                }
+            }
+
+            FilterNode::Condition(cond) => {
+                let estimate = estimate(cond, self.version);
+                // Days are checked in order until one matches, so day k is
+                // reached with chance (1 - p)^k: a geometric series
+                let days = estimate.days as f64;
+                let miss = (1.0 - estimate.p).powf(days);
+                let days_checked = if estimate.p > 0.0 {
+                    (1.0 - miss) / estimate.p
+                } else {
+                    days
+                };
+                Planned {
+                    node: self.leaf(Leaf::Any((**cond).clone())),
+                    cost: estimate.fixed_cost + estimate.day_cost * days_checked,
+                    p: 1.0 - miss,
+                }
+            }
+        }
+    }
+
+    /// Compile AND/OR children, lifting nested nodes of the same kind and
+    /// dropping repeated children (which can't change the result).
+    fn compile_flat(&mut self, conditions: &[FilterNode], and: bool) -> Vec<Planned> {
+        let mut flat = Vec::new();
+        flatten_into(conditions, and, &mut flat);
+        let mut unique: Vec<&FilterNode> = Vec::new();
+        for node in flat {
+            if !unique.contains(&node) {
+                unique.push(node);
+            }
+        }
+        unique.into_iter().map(|node| self.compile(node)).collect()
+    }
+
+    /// Compile XOR/AT_LEAST children, where repeats count separately.
+    fn compile_counted(&mut self, conditions: &[FilterNode]) -> Vec<Planned> {
+        let mut children: Vec<Planned> = conditions.iter().map(|node| self.compile(node)).collect();
+        // Both stop early once enough children match
+        sort_by_key(&mut children, |c| c.cost / c.p.max(f64::EPSILON));
+        children
+    }
+
+    fn leaf(&mut self, leaf: Leaf) -> PlanNode {
+        let index = match self.leaves.iter().position(|l| *l == leaf) {
+            Some(index) => index,
+            None => {
+                self.leaves.push(leaf);
+                self.leaves.len() - 1
+            }
+        };
+        PlanNode::Leaf(index)
+    }
+}
+
+fn flatten_into<'a>(conditions: &'a [FilterNode], and: bool, out: &mut Vec<&'a FilterNode>) {
+    for node in conditions {
+        match node {
+            FilterNode::And { conditions } if and => flatten_into(conditions, and, out),
+            FilterNode::Or { conditions } if !and => flatten_into(conditions, and, out),
+            _ => out.push(node),
+        }
+    }
+}
+
+/// Stable sort, so ties keep the order the filter was written in.
+fn sort_by_key(children: &mut [Planned], key: impl Fn(&Planned) -> f64) {
+    children.sort_by(|a, b| key(a).total_cmp(&key(b)));
+}
+
+/// Probability of each number of successes among independent trials.
+fn count_distribution(ps: impl Iterator<Item = f64>) -> Vec<f64> {
+    let mut distribution = vec![1.0];
+    for p in ps {
+        let mut next = vec![0.0; distribution.len() + 1];
+        for (hits, q) in distribution.iter().enumerate() {
+            next[hits] += q * (1.0 - p);
+            next[hits + 1] += q * p;
+        }
+        distribution = next;
+    }
+    distribution
+}
+
+/// Chance that between `min` and `max` of `days` independent days match,
+/// each with chance `p`.
+fn binomial_between(days: u64, p: f64, min: u64, max: u64) -> f64 {
+    let max = max.min(days);
+    if min > max {
+        return 0.0;
+    }
+    if p <= 0.0 {
+        return if min == 0 { 1.0 } else { 0.0 };
+    }
+    if p >= 1.0 {
+        return if max == days { 1.0 } else { 0.0 };
+    }
+    if days <= EXACT_COUNT_DAYS {
+        // Step through the probabilities in log space so long spans don't underflow
+        let log_odds = (p / (1.0 - p)).ln();
+        let mut log_p = days as f64 * (1.0 - p).ln();
+        let mut total = 0.0;
+        for hits in 0..=max {
+            if hits >= min {
+                total += log_p.exp();
+            }
+            log_p += ((days - hits) as f64 / (hits + 1) as f64).ln() + log_odds;
+        }
+        return total.min(1.0);
+    }
+    let mean = days as f64 * p;
+    let sd = (mean * (1.0 - p)).sqrt();
+    let below = |hits: f64| normal_cdf((hits - mean) / sd);
+    let upper = if max == days { 1.0 } else { below(max as f64 + 0.5) };
+    let lower = if min == 0 { 0.0 } else { below(min as f64 - 0.5) };
+    (upper - lower).max(0.0)
+}
+
+/// Standard normal CDF (Abramowitz & Stegun 7.1.26, good to about 1e-7).
+fn normal_cdf(z: f64) -> f64 {
+    let x = z.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let tail = 0.5 * poly * (-x * x).exp();
+    if z >= 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+/// Estimated work for one condition, which checks `days` alike days in order.
+struct Estimate {
+    /// Paid once, whatever the days (e.g. a chained weather forecast)
+    fixed_cost: f64,
+    /// Cost of checking one day
+    day_cost: f64,
+    /// Chance one day matches
+    p: f64,
+    /// Days that can match
+    days: u64,
+}
+
+fn estimate(cond: &FilterCondition, version: GameVersion) -> Estimate {
+    let per_day = |days: &DaySpec, day_cost: f64, p: f64| Estimate {
+        fixed_cost: 0.0,
+        day_cost,
+        p,
+        days: days.day_count(),
+    };
+
+    match cond {
+        FilterCondition::DailyLuck {
+            days,
+            min_luck,
+            max_luck,
+        } => per_day(days, LUCK_COST, luck_chance(*min_luck, *max_luck)),
+
+        FilterCondition::CartItem {
+            days, max_price, ..
+        } => {
+            // The cart only comes on Fridays and Sundays; other days are skipped for free
+            let weekdays = days.weekday_counts();
+            Estimate {
+                fixed_cost: 0.0,
+                day_cost: cart_cost(max_price.is_some(), version),
+                p: cart_chance(max_price.is_some()),
+                days: weekdays[4] + weekdays[6],
+            }
+        }
+
+        FilterCondition::NightEvent { days, event_type } => {
+            per_day(days, night_cost(version), night_chance(event_type))
+        }
+
+        FilterCondition::Geode { target_items, .. } => Estimate {
+            fixed_cost: 0.0,
+            day_cost: GEODE_COST,
+            p: (target_items.len() as f64 / 20.0).min(1.0),
+            days: 1,
+        },
+
+        FilterCondition::DishOfDay { days, .. } => per_day(days, DISH_COST, DISH_CHANCE),
+
+        FilterCondition::Weather { days, weather_type } => {
+            // The whole span is forecast up front, then days are read off it
+            let (first, last) = days.bounds();
+            Estimate {
+                fixed_cost: WEATHER_COST * (i64::from(last) - i64::from(first) + 1).max(0) as f64,
+                day_cost: 0.0,
+                p: weather_chance(weather_type),
+                days: days.day_count(),
+            }
+        }
+
+        FilterCondition::MineFloor {
+            days,
+            floor_start,
+            floor_end,
+            no_monsters,
+            no_dark,
+            has_mushroom,
+        } => {
+            let floors = (floor_end - floor_start + 1).max(0);
+            let mushroom_floors = (floor_end - (*floor_start).max(81) + 1).max(0);
+            let checks = [*no_monsters, *no_dark, *has_mushroom].iter().filter(|&&c| c).count();
+
+            let mut p = 1.0;
+            if *no_monsters {
+                p *= (1.0 - MONSTER_FLOOR_CHANCE).powi(floors);
+            }
+            if *no_dark {
+                p *= (1.0 - DARK_FLOOR_CHANCE).powi(floors);
+            }
+            if *has_mushroom {
+                p *= 1.0 - (1.0 - MUSHROOM_FLOOR_CHANCE).powi(mushroom_floors);
+            }
+            per_day(days, MINE_FLOOR_COST * (floors as usize * checks) as f64, p)
+        }
+
+        FilterCondition::Sequence { days, steps } => {
+            let cost: f64 = steps.iter().map(|step| event_cost(&step.event, version)).sum();
+            per_day(days, cost, sequence_chance(steps))
+        }
+    }
+}
+
+/// Chance that a start day begins a full match, treating steps as independent.
+fn sequence_chance(steps: &[SequenceStep]) -> f64 {
+    steps
+        .iter()
+        .enumerate()
+        .map(|(i, step)| {
+            let p = event_chance(&step.event);
+            if i == 0 {
+                return p;
+            }
+            let (min, max) = step.after();
+            let window = max.saturating_sub(min.max(0)).saturating_add(1).max(0);
+            1.0 - (1.0 - p).powi(window)
+        })
+        .product()
+}
+
+fn event_cost(event: &DayEvent, version: GameVersion) -> f64 {
+    match event {
+        DayEvent::DailyLuck { .. } => LUCK_COST,
+        DayEvent::CartItem { max_price, .. } => cart_cost(max_price.is_some(), version),
+        DayEvent::NightEvent { .. } => night_cost(version),
+        DayEvent::DishOfDay { .. } => DISH_COST,
+        DayEvent::Weather { .. } => WEATHER_COST,
+    }
+}
+
+fn event_chance(event: &DayEvent) -> f64 {
+    match event {
+        DayEvent::DailyLuck { min_luck, max_luck } => luck_chance(*min_luck, *max_luck),
+        DayEvent::CartItem { max_price, .. } => cart_chance(max_price.is_some()) * 2.0 / 7.0,
+        DayEvent::NightEvent { event_type } => night_chance(event_type),
+        DayEvent::DishOfDay { .. } => DISH_CHANCE,
+        DayEvent::Weather { weather_type } => weather_chance(weather_type),
+    }
+}
+
+/// Luck is `Next(-100, 101) / 1000`, so each of the 201 rolls is equally likely.
+fn luck_chance(min_luck: f64, max_luck: f64) -> f64 {
+    let hits = (-100..=100)
+        .map(|roll| roll as f64 / 1000.0)
+        .filter(|luck| *luck >= min_luck && *luck <= max_luck)
+        .count();
+    hits as f64 / 201.0
+}
+
+fn cart_cost(has_price_limit: bool, version: GameVersion) -> f64 {
+    match (version.has_new_cart_system(), has_price_limit) {
+        (true, true) => CART_PRICE_COST_V16,
+        (true, false) => CART_COST_V16,
+        (false, _) => CART_COST,
+    }
+}
+
+fn cart_chance(has_price_limit: bool) -> f64 {
+    if has_price_limit {
+        CART_ITEM_CHANCE / 2.0
+    } else {
+        CART_ITEM_CHANCE
+    }
+}
+
+fn night_cost(version: GameVersion) -> f64 {
+    if version.uses_hash_seeding() {
+        NIGHT_COST_V16
+    } else {
+        NIGHT_COST
+    }
+}
+
+fn night_chance(event_type: &str) -> f64 {
+    if event_type == "any" {
+        ANY_NIGHT_EVENT_CHANCE
+    } else {
+        NIGHT_EVENT_CHANCE
+    }
+}
+
+fn weather_chance(weather_type: &str) -> f64 {
+    if weather_type == "any" {
+        return 0.4;
+    }
+    match parse_weather(weather_type) {
+        Weather::Sunny => 0.6,
+        Weather::Rain | Weather::Snow => 0.2,
+        Weather::Debris => 0.1,
+        Weather::Lightning => 0.05,
+        Weather::GreenRain => 0.01,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::evaluate_filter;
+
+    fn compile(json: &str) -> (FilterNode, FilterPlan) {
+        let filter: FilterNode = serde_json::from_str(json).unwrap();
+        let plan = FilterPlan::compile(&filter, GameVersion::V1_6);
+        (filter, plan)
+    }
+
+    #[test]
+    fn test_plan_agrees_with_evaluate_filter() {
+        let json = r#"{
+            "logic": "or",
+            "conditions": [
+                {"logic": "and", "conditions": [
+                    {"logic": "condition", "type": "daily_luck", "days": {"type": "range", "start": 1, "end": 5}, "min_luck": 0.07, "max_luck": 0.1},
+                    {"logic": "not", "condition": {"logic": "condition", "type": "weather", "days": {"type": "exact", "day": 3}, "weather_type": "rain"}},
+                    {"logic": "and", "conditions": [
+                        {"logic": "condition", "type": "dish_of_day", "days": {"type": "range", "start": 1, "end": 10}, "dish_id": 200}
+                    ]}
+                ]},
+                {"logic": "at_least", "count": 2, "conditions": [
+                    {"logic": "condition", "type": "daily_luck", "days": {"type": "range", "start": 1, "end": 5}, "min_luck": 0.07, "max_luck": 0.1},
+                    {"logic": "condition", "type": "night_event", "days": {"type": "range", "start": 1, "end": 28}, "event_type": "any"},
+                    {"logic": "count_days", "condition": {"type": "weather", "days": {"type": "range", "start": 1, "end": 7}, "weather_type": "sunny"}, "at_least": 5}
+                ]},
+                {"logic": "xor", "conditions": [
+                    {"logic": "condition", "type": "geode", "geode_number": 1, "geode_type": "geode", "target_items": [535, 378]},
+                    {"logic": "condition", "type": "cart_item", "days": {"type": "range", "start": 1, "end": 7}, "item_id": 266}
+                ]}
+            ]
+        }"#;
+        let (filter, mut plan) = compile(json);
+        let ctx = PlayerContext::default();
+        let mut matched = 0;
+        for seed in 0..2000 {
+            let expected = evaluate_filter(seed, &filter, GameVersion::V1_6, &ctx);
+            matched += expected as u32;
+            assert_eq!(plan.matches(seed, &ctx), expected, "seed {}", seed);
+        }
+        assert!(matched > 0 && matched < 2000, "Filter should split the seeds");
+    }
+
+    #[test]
+    fn test_cheap_selective_conditions_first() {
+        // Written cart-first; the geode is far cheaper than a 1.6 cart
+        let json = r#"{
+            "logic": "and",
+            "conditions": [
+                {"logic": "condition", "type": "cart_item", "days": {"type": "range", "start": 1, "end": 28}, "item_id": 266},
+                {"logic": "condition", "type": "night_event", "days": {"type": "range", "start": 1, "end": 28}, "event_type": "fairy"},
+                {"logic": "condition", "type": "geode", "geode_number": 1, "geode_type": "geode", "target_items": [535]}
+            ]
+        }"#;
+        let (_, plan) = compile(json);
+        let PlanNode::And(children) = &plan.root else {
+            panic!("Expected And root, got {:?}", plan.root);
+        };
+        let kinds: Vec<&FilterCondition> = children
+            .iter()
+            .map(|child| match child {
+                PlanNode::Leaf(index) => match &plan.leaves.conditions[*index] {
+                    Leaf::Any(cond) => cond,
+                    leaf => panic!("Unexpected leaf {:?}", leaf),
+                },
+                node => panic!("Unexpected node {:?}", node),
+            })
+            .collect();
+        assert!(matches!(kinds[0], FilterCondition::Geode { .. }));
+        assert!(matches!(kinds[2], FilterCondition::CartItem { .. }));
+    }
+
+    #[test]
+    fn test_repeated_conditions_are_shared() {
+        let luck = r#"{"logic": "condition", "type": "daily_luck", "days": {"type": "exact", "day": 1}, "min_luck": 0.05, "max_luck": 0.1}"#;
+        let dish = r#"{"logic": "condition", "type": "dish_of_day", "days": {"type": "exact", "day": 1}, "dish_id": 200}"#;
+        let rain = r#"{"logic": "condition", "type": "weather", "days": {"type": "exact", "day": 3}, "weather_type": "rain"}"#;
+        let json = format!(
+            r#"{{"logic": "or", "conditions": [
+                {{"logic": "and", "conditions": [{luck}, {dish}, {luck}]}},
+                {{"logic": "and", "conditions": [{luck}, {rain}]}}
+            ]}}"#
+        );
+        let (_, plan) = compile(&json);
+        assert_eq!(plan.leaves.conditions.len(), 3);
+        // The duplicate inside the first AND is dropped, and both branches
+        // point at the same luck leaf
+        let PlanNode::Or(branches) = &plan.root else {
+            panic!("Expected Or root, got {:?}", plan.root);
+        };
+        let luck_leaves: Vec<&PlanNode> = branches
+            .iter()
+            .map(|branch| match branch {
+                PlanNode::And(children) => {
+                    assert_eq!(children.len(), 2);
+                    children
+                        .iter()
+                        .find(|c| matches!(c, PlanNode::Leaf(i) if matches!(plan.leaves.conditions[*i], Leaf::Any(FilterCondition::DailyLuck { .. }))))
+                        .unwrap()
+                }
+                node => panic!("Unexpected node {:?}", node),
+            })
+            .collect();
+        assert_eq!(luck_leaves[0], luck_leaves[1]);
+    }
+
+    #[test]
+    fn test_double_not_and_nested_and_collapse() {
+        let json = r#"{
+            "logic": "and",
+            "conditions": [
+                {"logic": "not", "condition": {"logic": "not", "condition":
+                    {"logic": "condition", "type": "dish_of_day", "days": {"type": "exact", "day": 1}, "dish_id": 200}}},
+                {"logic": "and", "conditions": [
+                    {"logic": "condition", "type": "daily_luck", "days": {"type": "exact", "day": 1}, "min_luck": 0.05, "max_luck": 0.1}
+                ]}
+            ]
+        }"#;
+        let (_, plan) = compile(json);
+        // The rare dish is checked before the common lucky day
+        assert_eq!(plan.root, PlanNode::And(vec![PlanNode::Leaf(0), PlanNode::Leaf(1)]));
+    }
+
+    fn scan(json: &str, version: GameVersion, start: i32, end: i32) -> (Vec<i32>, Vec<i32>) {
+        let filter: FilterNode = serde_json::from_str(json).unwrap();
+        let ctx = PlayerContext::default();
+        let mut plan = FilterPlan::compile(&filter, version);
+        let mut found = Vec::new();
+        plan.scan(start, end, &ctx, |seed| {
+            found.push(seed);
+            true
+        });
+        let expected = (start..=end)
+            .filter(|&seed| evaluate_filter(seed, &filter, version, &ctx))
+            .collect();
+        (found, expected)
+    }
+
+    #[test]
+    fn test_scan_agrees_with_brute_force() {
+        let luck = r#"{"logic": "condition", "type": "daily_luck", "days": {"type": "range", "start": 1, "end": 3}, "min_luck": 0.07, "max_luck": 0.1}"#;
+        let fairy = r#"{"logic": "condition", "type": "night_event", "days": {"type": "range", "start": 1, "end": 28}, "event_type": "any"}"#;
+        let cart = r#"{"logic": "condition", "type": "cart_item", "days": {"type": "range", "start": 1, "end": 7}, "item_id": 266}"#;
+        let summer_rain = r#"{"logic": "condition", "type": "weather", "days": {"type": "range", "start": 29, "end": 35}, "weather_type": "rain"}"#;
+        let filters = [
+            luck.to_string(),
+            format!(r#"{{"logic": "and", "conditions": [{luck}, {{"logic": "not", "condition": {fairy}}}]}}"#),
+            format!(r#"{{"logic": "or", "conditions": [{luck}, {cart}]}}"#),
+            format!(r#"{{"logic": "xor", "conditions": [{luck}, {fairy}, {summer_rain}]}}"#),
+            format!(r#"{{"logic": "at_least", "count": 2, "conditions": [{luck}, {fairy}, {cart}]}}"#),
+        ];
+        for version in [GameVersion::V1_5, GameVersion::V1_6] {
+            for json in &filters {
+                // The run around zero is twice as wide as the others
+                let (found, expected) = scan(json, version, -1500, 1500);
+                assert_eq!(found, expected, "{:?} {}", version, json);
+            }
+        }
+    }
+
+    #[test]
+    fn test_scan_accepts_whole_blocks() {
+        // Luck only sees seed / 100, so matches come in full runs of 100
+        let json = r#"{"logic": "condition", "type": "daily_luck", "days": {"type": "exact", "day": 1}, "min_luck": 0.0, "max_luck": 0.1}"#;
+        let (found, expected) = scan(json, GameVersion::V1_6, 100, 5099);
+        assert_eq!(found, expected);
+        assert!(!found.is_empty());
+        assert_eq!(found.len() % 100, 0);
+    }
+
+    #[test]
+    fn test_scan_stops_when_asked() {
+        let json = r#"{"logic": "condition", "type": "dish_of_day", "days": {"type": "range", "start": 1, "end": 28}, "dish_id": 200}"#;
+        let filter: FilterNode = serde_json::from_str(json).unwrap();
+        let mut plan = FilterPlan::compile(&filter, GameVersion::V1_6);
+        let mut found = Vec::new();
+        plan.scan(0, 100_000, &PlayerContext::default(), |seed| {
+            found.push(seed);
+            found.len() < 3
+        });
+        assert_eq!(found.len(), 3);
+    }
+
+    #[test]
+    fn test_scan_into_resumes_where_it_stopped() {
+        let json = r#"{"logic": "condition", "type": "daily_luck", "days": {"type": "range", "start": 1, "end": 3}, "min_luck": 0.07, "max_luck": 0.1}"#;
+        let (_, expected) = scan(json, GameVersion::V1_6, -3000, 3000);
+        assert!(expected.len() > 7);
+
+        let filter: FilterNode = serde_json::from_str(json).unwrap();
+        let mut plan = FilterPlan::compile(&filter, GameVersion::V1_6);
+        let ctx = PlayerContext::default();
+        let mut buffer = [0; 7];
+        let mut found = Vec::new();
+        let mut next = -3000;
+        loop {
+            let batch = plan.scan_into(next as i32, 3000, &ctx, &mut buffer);
+            found.extend_from_slice(&buffer[..batch.count as usize]);
+            next = batch.next_seed;
+            if !batch.stopped_early {
+                break;
+            }
+            assert_eq!(batch.count, 7);
+        }
+        assert_eq!(found, expected);
+        assert_eq!(next, 3001);
+
+        // No room means nothing checked
+        let batch = plan.scan_into(0, 10, &ctx, &mut []);
+        assert_eq!(batch, ScanBatch { count: 0, next_seed: 0, stopped_early: true });
+        // The range end doesn't overflow
+        let batch = plan.scan_into(i32::MAX - 10, i32::MAX, &ctx, &mut [0; 16]);
+        assert_eq!(batch.next_seed, i32::MAX as i64 + 1);
+        assert!(!batch.stopped_early);
+    }
+
+    #[test]
+    fn test_wide_ranges_compile_quickly() {
+        let luck = r#"{"logic": "condition", "type": "daily_luck", "days": {"type": "range", "start": 1, "end": 2000000000}, "min_luck": 0.1, "max_luck": 0.1}"#;
+        let (_, plan) = compile(luck);
+        assert!(plan.estimated_match_rate() > 0.99);
+        // About 201 days are checked before one rolls the maximum
+        assert!((plan.estimated_cost() - 201.0 * LUCK_COST).abs() < 1.0, "{}", plan.estimated_cost());
+
+        let count = r#"{"logic": "count_days", "condition": {"type": "dish_of_day", "days": {"type": "range", "start": 1, "end": 100000}, "dish_id": 200}, "at_least": 2600, "at_most": 2700}"#;
+        let (_, plan) = compile(count);
+        assert_eq!(plan.estimated_cost(), 100_000.0 * DISH_COST);
+        // 100000 / 39 ≈ 2564 expected hits with a deviation of about 50
+        let rate = plan.estimated_match_rate();
+        assert!(rate > 0.2 && rate < 0.3, "{}", rate);
+    }
+
+    #[test]
+    fn test_count_chance_is_exact_for_short_spans() {
+        assert!((binomial_between(3, 0.5, 1, 2) - 0.75).abs() < 1e-12);
+        assert!((binomial_between(1000, 0.6, 0, u64::MAX) - 1.0).abs() < 1e-9);
+        assert_eq!(binomial_between(10, 0.5, 11, u64::MAX), 0.0);
+        assert!((binomial_between(20_000, 0.5, 0, 10_000) - 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_condition_dependency() {
+        let weather = |start: i32, end: i32| FilterCondition::Weather {
+            days: DaySpec::Range { start, end },
+            weather_type: "rain".to_string(),
+        };
+        // Green rain only reads the full game ID in summer, and only in 1.6
+        assert_eq!(condition_dependency(&weather(1, 28), GameVersion::V1_6), SeedDependency::Quotient100);
+        assert_eq!(condition_dependency(&weather(20, 40), GameVersion::V1_6), SeedDependency::Full);
+        assert_eq!(condition_dependency(&weather(20, 40), GameVersion::V1_5), SeedDependency::Quotient100);
+
+        let cart = FilterCondition::CartItem {
+            days: DaySpec::Exact { day: 5 },
+            item_id: 266,
+            max_price: None,
+        };
+        assert_eq!(condition_dependency(&cart, GameVersion::V1_6), SeedDependency::Quotient2);
+        assert_eq!(condition_dependency(&cart, GameVersion::V1_5), SeedDependency::Full);
+    }
+}
@@ -0,0 +1,255 @@
+//! Seed recovery from in-game observations.
+//!
+//! Inverts the day mechanics: given things a player saw early in a save,
+//! find every game ID consistent with all of them. Observations are grouped
+//! by how much of the seed they depend on, so whole `seed / 100` and
+//! `seed / 2` runs are rejected with a single check.
+
+use serde::Deserialize;
+
+use super::blocks::quotient_blocks;
+use super::evaluate::{parse_geode_type, parse_night_event};
+use crate::mechanics;
+use crate::types::PlayerContext;
+use crate::version::GameVersion;
+
+/// Something the player saw in game.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type")]
+pub enum Observation {
+    /// Saloon dish of the day (quantity is optional)
+    #[serde(rename = "dish_of_day")]
+    DishOfDay {
+        day: i32,
+        dish_id: i32,
+        #[serde(default)]
+        quantity: Option<i32>,
+    },
+
+    /// Items seen in the traveling cart (any subset of its stock)
+    #[serde(rename = "cart")]
+    Cart { day: i32, item_ids: Vec<i32> },
+
+    /// Result of the Nth geode cracked
+    #[serde(rename = "geode")]
+    Geode {
+        geode_number: i32,
+        geode_type: String,
+        item_id: i32,
+    },
+
+    /// Night event on a day, or "none" for a quiet night
+    #[serde(rename = "night_event")]
+    NightEvent { day: i32, event_type: String },
+}
+
+/// How much of the seed an observation depends on, from least to most.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum SeedDependency {
+    /// Only `seed / 100` (day-start RNG)
+    Quotient100,
+    /// Only `seed / 2`
+    Quotient2,
+    /// Every bit of the seed
+    Full,
+}
+
+impl Observation {
+    pub(crate) fn dependency(&self, version: GameVersion) -> SeedDependency {
+        match self {
+            Observation::DishOfDay { .. } => SeedDependency::Quotient100,
+            Observation::Geode { .. } | Observation::NightEvent { .. } => SeedDependency::Quotient2,
+            // Before 1.6 the cart is seeded with the full game ID plus the day
+            Observation::Cart { .. } if version.has_new_cart_system() => SeedDependency::Quotient2,
+            Observation::Cart { .. } => SeedDependency::Full,
+        }
+    }
+
+    /// Whether `seed` reproduces this observation.
+    pub fn matches(&self, seed: i32, version: GameVersion, ctx: &PlayerContext) -> bool {
+        match self {
+            Observation::DishOfDay {
+                day,
+                dish_id,
+                quantity,
+            } => {
+                let (dish, qty) = mechanics::dish_of_the_day(seed, *day, ctx.steps);
+                dish == *dish_id && quantity.is_none_or(|q| q == qty)
+            }
+
+            Observation::Cart { day, item_ids } => {
+                let stock = mechanics::get_cart_for_day(seed, *day, ctx.legacy_random, version);
+                item_ids
+                    .iter()
+                    .all(|id| stock.iter().any(|item| item.item_id == *id))
+            }
+
+            Observation::Geode {
+                geode_number,
+                geode_type,
+                item_id,
+            } => {
+                let result = mechanics::next_geode_item(
+                    seed,
+                    *geode_number,
+                    parse_geode_type(geode_type),
+                    ctx.deepest_mine_level,
+                    ctx.has_coconut_hat,
+                    version,
+                );
+                result.item_id == *item_id
+            }
+
+            Observation::NightEvent { day, event_type } => {
+                let event = mechanics::night_event_for_farm(
+                    seed,
+                    *day,
+                    version,
+                    ctx.greenhouse_repaired,
+                    ctx.legacy_random,
+                );
+                if event_type.eq_ignore_ascii_case("none") {
+                    event.is_none()
+                } else {
+                    // Unknown names parse to None and never match an actual event
+                    event.is_some() && event == parse_night_event(event_type)
+                }
+            }
+        }
+    }
+}
+
+/// Find every seed in `[start_seed, end_seed]` consistent with all observations.
+///
+/// Stops after `max_results` matches. With no observations every seed matches.
+pub fn recover_seeds_in_range(
+    observations: &[Observation],
+    start_seed: i32,
+    end_seed: i32,
+    max_results: usize,
+    version: GameVersion,
+    ctx: &PlayerContext,
+) -> Vec<i32> {
+    let by_dependency = |dep: SeedDependency| -> Vec<&Observation> {
+        observations
+            .iter()
+            .filter(|o| o.dependency(version) == dep)
+            .collect()
+    };
+    let q100 = by_dependency(SeedDependency::Quotient100);
+    let q2 = by_dependency(SeedDependency::Quotient2);
+    let full = by_dependency(SeedDependency::Full);
+
+    let all_match = |checks: &[&Observation], seed: i32| checks.iter().all(|o| o.matches(seed, version, ctx));
+
+    let mut found = Vec::new();
+    if max_results == 0 {
+        return found;
+    }
+
+    for (block_start, block_end) in quotient_blocks(start_seed, end_seed, 100) {
+        if !all_match(&q100, block_start) {
+            continue;
+        }
+        for (pair_start, pair_end) in quotient_blocks(block_start, block_end, 2) {
+            if !all_match(&q2, pair_start) {
+                continue;
+            }
+            for seed in pair_start..=pair_end {
+                if all_match(&full, seed) {
+                    found.push(seed);
+                    if found.len() >= max_results {
+                        return found;
+                    }
+                }
+            }
+        }
+    }
+
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Observations a player of `seed` would have made.
+    fn observe(seed: i32, version: GameVersion) -> Vec<Observation> {
+        let ctx = PlayerContext::default();
+        let (dish_id, quantity) = mechanics::dish_of_the_day(seed, 1, ctx.steps);
+        let cart: Vec<i32> = mechanics::get_cart_for_day(seed, 5, false, version)
+            .iter()
+            .take(3)
+            .map(|item| item.item_id)
+            .collect();
+        let geode = mechanics::next_geode_item(seed, 1, mechanics::GeodeType::Geode, 120, false, version);
+        vec![
+            Observation::DishOfDay {
+                day: 1,
+                dish_id,
+                quantity: Some(quantity),
+            },
+            Observation::Cart {
+                day: 5,
+                item_ids: cart,
+            },
+            Observation::Geode {
+                geode_number: 1,
+                geode_type: "geode".to_string(),
+                item_id: geode.item_id,
+            },
+        ]
+    }
+
+    fn brute_force(observations: &[Observation], start: i32, end: i32, version: GameVersion) -> Vec<i32> {
+        let ctx = PlayerContext::default();
+        (start..=end)
+            .filter(|&seed| observations.iter().all(|o| o.matches(seed, version, &ctx)))
+            .collect()
+    }
+
+    #[test]
+    fn test_recovers_true_seed() {
+        for version in [GameVersion::V1_5, GameVersion::V1_6] {
+            let seed = 123_456;
+            let observations = observe(seed, version);
+            let found = recover_seeds_in_range(
+                &observations,
+                seed - 5000,
+                seed + 5000,
+                usize::MAX,
+                version,
+                &PlayerContext::default(),
+            );
+            assert!(found.contains(&seed), "{:?}: true seed not recovered", version);
+            assert_eq!(found, brute_force(&observations, seed - 5000, seed + 5000, version));
+        }
+    }
+
+    #[test]
+    fn test_block_pruning_across_zero() {
+        // Runs straddling zero are wider; pruning must still agree with brute force
+        let observations = observe(-37, GameVersion::V1_6);
+        let found = recover_seeds_in_range(
+            &observations,
+            -2000,
+            2000,
+            usize::MAX,
+            GameVersion::V1_6,
+            &PlayerContext::default(),
+        );
+        assert!(found.contains(&-37));
+        assert_eq!(found, brute_force(&observations, -2000, 2000, GameVersion::V1_6));
+    }
+
+    #[test]
+    fn test_observation_parsing() {
+        let json = r#"[
+            {"type": "dish_of_day", "day": 1, "dish_id": 210},
+            {"type": "night_event", "day": 3, "event_type": "none"}
+        ]"#;
+        let observations: Vec<Observation> = serde_json::from_str(json).unwrap();
+        assert_eq!(observations[0].dependency(GameVersion::V1_6), SeedDependency::Quotient100);
+        assert_eq!(observations[1].dependency(GameVersion::V1_6), SeedDependency::Quotient2);
+    }
+}
@@ -0,0 +1,298 @@
+//! Scored top-K search.
+//!
+//! Filters only say whether a seed passes. A score expression ranks seeds
+//! instead: a weighted sum of terms such as total luck over some days, the
+//! cheapest price of a cart item, or how many geodes hit a target. The search
+//! keeps the K best seeds of a range in a bounded heap.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+use serde::{Deserialize, Serialize};
+
+use super::evaluate::{is_cart_day, parse_geode_type, visit_days};
+use super::filter::{DaySpec, FilterCondition, FilterNode};
+use super::plan::FilterPlan;
+use crate::mechanics;
+use crate::types::PlayerContext;
+use crate::version::GameVersion;
+
+/// What to rank seeds by: the sum of `weight * value` over the terms.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScoreExpr {
+    pub terms: Vec<ScoreTerm>,
+    /// Only seeds that pass this filter are scored
+    #[serde(default)]
+    pub filter: Option<FilterNode>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScoreTerm {
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+    #[serde(flatten)]
+    pub value: ScoreValue,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+/// A number computed from a seed.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScoreValue {
+    /// Sum of daily luck over the days
+    LuckSum { days: DaySpec },
+
+    /// Cheapest price of an item in the cart on the days, or `missing` if it
+    /// never shows up. Use a negative weight to prefer cheap items, and a
+    /// `missing` above any real price so absent items rank last.
+    CartPrice {
+        days: DaySpec,
+        item_id: i32,
+        #[serde(default)]
+        missing: f64,
+    },
+
+    /// Number of days a condition matches (night events, weather, luck, ...).
+    /// Conditions without days, like geodes, count 0 or 1.
+    MatchingDays { condition: FilterCondition },
+
+    /// How many of `count` geodes from `geode_number` on give a target item
+    GeodeHits {
+        geode_type: String,
+        #[serde(default = "default_geode_number")]
+        geode_number: i32,
+        count: i32,
+        target_items: Vec<i32>,
+    },
+}
+
+fn default_geode_number() -> i32 {
+    1
+}
+
+/// A seed and its score.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ScoredSeed {
+    pub seed: i32,
+    pub score: f64,
+}
+
+impl ScoreValue {
+    pub fn evaluate(&self, seed: i32, version: GameVersion, ctx: &PlayerContext) -> f64 {
+        match self {
+            ScoreValue::LuckSum { days } => days
+                .days()
+                .map(|day| mechanics::daily_luck(seed, day, ctx.steps, ctx.friendship_rolls()))
+                .sum(),
+
+            ScoreValue::CartPrice {
+                days,
+                item_id,
+                missing,
+            } => days
+                .days()
+                .filter(|&day| is_cart_day(day))
+                .flat_map(|day| mechanics::get_cart_for_day(seed, day, ctx.legacy_random, version))
+                .filter(|item| item.item_id == *item_id)
+                .map(|item| item.price)
+                .min()
+                .map_or(*missing, f64::from),
+
+            ScoreValue::MatchingDays { condition } => {
+                let mut hits = 0u32;
+                visit_days(seed, condition, version, ctx, |hit| {
+                    hits += hit as u32;
+                    true
+                });
+                hits as f64
+            }
+
+            ScoreValue::GeodeHits {
+                geode_type,
+                geode_number,
+                count,
+                target_items,
+            } => mechanics::predict_geode_sequence(
+                seed,
+                *geode_number,
+                *count,
+                parse_geode_type(geode_type),
+                ctx.deepest_mine_level,
+                ctx.has_coconut_hat,
+                version,
+            )
+            .iter()
+            .filter(|geode| target_items.contains(&geode.item_id))
+            .count() as f64,
+        }
+    }
+}
+
+impl ScoreExpr {
+    pub fn evaluate(&self, seed: i32, version: GameVersion, ctx: &PlayerContext) -> f64 {
+        self.terms
+            .iter()
+            .filter(|term| term.weight != 0.0)
+            .map(|term| term.weight * term.value.evaluate(seed, version, ctx))
+            .sum()
+    }
+}
+
+/// Heap order: higher score is better, then lower seed.
+struct Ranked(ScoredSeed);
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .score
+            .total_cmp(&other.0.score)
+            .then_with(|| other.0.seed.cmp(&self.0.seed))
+    }
+}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ranked {}
+
+/// The `k` highest-scoring seeds in `[start_seed, end_seed]`, best first.
+///
+/// Ties go to the lower seed. NaN scores rank above everything, so a broken
+/// expression shows up instead of silently ranking last.
+pub fn top_k_in_range(
+    expr: &ScoreExpr,
+    start_seed: i32,
+    end_seed: i32,
+    k: usize,
+    version: GameVersion,
+    ctx: &PlayerContext,
+) -> Vec<ScoredSeed> {
+    if k == 0 {
+        return Vec::new();
+    }
+    let mut plan = expr.filter.as_ref().map(|filter| FilterPlan::compile(filter, version));
+    // Min-heap of the best k so far: the root is the one to beat. It never holds
+    // more than k seeds, nor more than the range has, whatever k the caller asks for
+    let range_len = (i64::from(end_seed) - i64::from(start_seed) + 1).max(0) as usize;
+    let mut best: BinaryHeap<Reverse<Ranked>> = BinaryHeap::with_capacity(k.min(range_len));
+
+    for seed in start_seed..=end_seed {
+        if let Some(plan) = plan.as_mut() {
+            if !plan.matches(seed, ctx) {
+                continue;
+            }
+        }
+        let ranked = Ranked(ScoredSeed {
+            seed,
+            score: expr.evaluate(seed, version, ctx),
+        });
+        if best.len() < k {
+            best.push(Reverse(ranked));
+        } else if best.peek().is_some_and(|Reverse(worst)| ranked > *worst) {
+            best.pop();
+            best.push(Reverse(ranked));
+        }
+    }
+
+    // Ascending Reverse order is best first
+    best.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(Ranked(scored))| scored)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute_force(expr: &ScoreExpr, start: i32, end: i32, k: usize) -> Vec<ScoredSeed> {
+        let ctx = PlayerContext::default();
+        let mut all: Vec<ScoredSeed> = (start..=end)
+            .map(|seed| ScoredSeed {
+                seed,
+                score: expr.evaluate(seed, GameVersion::V1_6, &ctx),
+            })
+            .collect();
+        all.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.seed.cmp(&b.seed)));
+        all.truncate(k);
+        all
+    }
+
+    #[test]
+    fn test_top_k_matches_brute_force() {
+        let json = r#"{
+            "terms": [
+                {"type": "luck_sum", "days": {"type": "range", "start": 1, "end": 7}, "weight": 10},
+                {"type": "matching_days", "condition": {"type": "night_event", "days": {"type": "range", "start": 1, "end": 28}, "event_type": "any"}},
+                {"type": "geode_hits", "geode_type": "geode", "count": 5, "target_items": [535, 378], "weight": 0.5}
+            ]
+        }"#;
+        let expr: ScoreExpr = serde_json::from_str(json).unwrap();
+        let top = top_k_in_range(&expr, -500, 1500, 10, GameVersion::V1_6, &PlayerContext::default());
+        assert_eq!(top.len(), 10);
+        assert_eq!(top, brute_force(&expr, -500, 1500, 10));
+        assert!(top.windows(2).all(|w| w[0].score >= w[1].score));
+    }
+
+    #[test]
+    fn test_cart_price_prefers_cheap_items() {
+        // Cheapest Red Cabbage in the first two weeks; seeds without one rank last
+        let json = r#"{
+            "terms": [
+                {"type": "cart_price", "days": {"type": "range", "start": 1, "end": 14}, "item_id": 266,
+                 "missing": 100000, "weight": -1}
+            ]
+        }"#;
+        let expr: ScoreExpr = serde_json::from_str(json).unwrap();
+        let ctx = PlayerContext::default();
+        let top = top_k_in_range(&expr, 0, 300, 3, GameVersion::V1_6, &ctx);
+        assert_eq!(top, brute_force(&expr, 0, 300, 3));
+        for scored in &top {
+            let stock: Vec<_> = [5, 7, 12, 14]
+                .iter()
+                .flat_map(|&day| mechanics::get_cart_for_day(scored.seed, day, false, GameVersion::V1_6))
+                .filter(|item| item.item_id == 266)
+                .collect();
+            assert!(!stock.is_empty(), "seed {} has no Red Cabbage", scored.seed);
+            assert_eq!(-scored.score, stock.iter().map(|item| item.price).min().unwrap() as f64);
+        }
+    }
+
+    #[test]
+    fn test_filter_gates_scoring() {
+        let json = r#"{
+            "terms": [{"type": "luck_sum", "days": {"type": "exact", "day": 1}}],
+            "filter": {"logic": "condition", "type": "dish_of_day", "days": {"type": "exact", "day": 1}, "dish_id": 200}
+        }"#;
+        let expr: ScoreExpr = serde_json::from_str(json).unwrap();
+        let ctx = PlayerContext::default();
+        let top = top_k_in_range(&expr, 0, 20_000, 5, GameVersion::V1_6, &ctx);
+        assert!(!top.is_empty());
+        for scored in &top {
+            assert_eq!(mechanics::dish_of_the_day(scored.seed, 1, 0).0, 200);
+        }
+        assert!(top_k_in_range(&expr, 0, 20_000, 0, GameVersion::V1_6, &ctx).is_empty());
+    }
+
+    #[test]
+    fn test_huge_k_returns_whole_range() {
+        let expr: ScoreExpr =
+            serde_json::from_str(r#"{"terms": [{"type": "luck_sum", "days": {"type": "exact", "day": 1}}]}"#).unwrap();
+        let ctx = PlayerContext::default();
+        let top = top_k_in_range(&expr, 0, 99, usize::MAX, GameVersion::V1_6, &ctx);
+        assert_eq!(top, brute_force(&expr, 0, 99, 100));
+        assert!(top_k_in_range(&expr, 10, 0, usize::MAX, GameVersion::V1_6, &ctx).is_empty());
+    }
+}
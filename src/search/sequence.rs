@@ -0,0 +1,312 @@
+//! Sequence conditions: events related in time.
+//!
+//! A sequence is checked from every day of its window, and those starts
+//! revisit the same later days, so each step's event and each partial
+//! match is computed at most once per day and cached. The caches only grow
+//! as far as the days actually looked at, since most seeds fail early.
+
+use super::evaluate::{check_cart_has_item, is_cart_day, parse_night_event, parse_weather};
+use super::filter::{DayEvent, DaySpec, SequenceStep};
+use crate::mechanics::{self, Weather};
+use crate::types::PlayerContext;
+use crate::version::GameVersion;
+
+/// Longest gap between steps, and most start days, a sequence can have (ten years).
+pub(super) const MAX_SEQUENCE_DAYS: i32 = 1120;
+
+/// Lazily evaluated sequence steps for one seed.
+pub(super) struct Timeline<'a> {
+    seed: i32,
+    steps: &'a [SequenceStep],
+    version: GameVersion,
+    ctx: &'a PlayerContext,
+    /// Days from the window's first day to the furthest a sequence can reach
+    first_day: i32,
+    last_day: i32,
+    /// events[step][day - first_day]: whether the step's event happens that day
+    /// (rows grow on demand)
+    events: Vec<Vec<Option<bool>>>,
+    /// chains[step][day - first_day]: whether steps `step..` match from that day
+    chains: Vec<Vec<Option<bool>>>,
+    /// First day each step's event happens, once looked up
+    first_events: Vec<Option<Option<i32>>>,
+    /// Chained forecast over the whole range, once a weather step needs it
+    weather: Option<Vec<Weather>>,
+}
+
+impl<'a> Timeline<'a> {
+    pub(super) fn new(
+        seed: i32,
+        days: &DaySpec,
+        steps: &'a [SequenceStep],
+        version: GameVersion,
+        ctx: &'a PlayerContext,
+    ) -> Self {
+        let (first_day, last_start) = days.bounds();
+        let last_day = last_start.saturating_add(sequence_reach(steps));
+
+        Self {
+            seed,
+            steps,
+            version,
+            ctx,
+            first_day,
+            last_day,
+            events: vec![Vec::new(); steps.len()],
+            chains: vec![Vec::new(); steps.len()],
+            first_events: vec![None; steps.len()],
+            weather: None,
+        }
+    }
+
+    /// Whether the whole sequence matches with its first step on `day`.
+    pub(super) fn starts_on(&mut self, day: i32) -> bool {
+        self.steps.is_empty() || self.chain_from(0, day)
+    }
+
+    /// The day each step lands on when the sequence matches starting on `day`.
+    ///
+    /// Where a gap allows several days, the earliest that completes the chain is used.
+    pub(super) fn chain_days(&mut self, day: i32) -> Option<Vec<i32>> {
+        if !self.starts_on(day) {
+            return None;
+        }
+        let steps = self.steps;
+        let mut chain = Vec::with_capacity(steps.len());
+        let mut current = day;
+        for (step, next) in steps.iter().enumerate() {
+            if step > 0 {
+                let (min, max) = next.after();
+                current = (min.max(0)..=max)
+                    .map(|gap| current.saturating_add(gap))
+                    .find(|&next_day| self.chain_from(step, next_day))?;
+            }
+            chain.push(current);
+        }
+        Some(chain)
+    }
+
+    /// Whether steps `step..` match with `step` on `day`.
+    fn chain_from(&mut self, step: usize, day: i32) -> bool {
+        let Some(index) = self.index(day) else {
+            return false;
+        };
+        if let Some(&Some(cached)) = self.chains[step].get(index) {
+            return cached;
+        }
+
+        let steps = self.steps;
+        let result = self.step_on(step, day)
+            && match steps.get(step + 1) {
+                None => true,
+                Some(next) => {
+                    let (min, max) = next.after();
+                    (min.max(0)..=max).any(|gap| self.chain_from(step + 1, day.saturating_add(gap)))
+                }
+            };
+        *cache_slot(&mut self.chains[step], index) = Some(result);
+        result
+    }
+
+    /// Whether a single step holds on `day`, including its `first` constraint.
+    pub(super) fn step_on(&mut self, step: usize, day: i32) -> bool {
+        if self.steps[step].first {
+            self.first_event(step) == Some(day)
+        } else {
+            self.event_on(step, day)
+        }
+    }
+
+    fn first_event(&mut self, step: usize) -> Option<i32> {
+        if let Some(cached) = self.first_events[step] {
+            return cached;
+        }
+        let first = (self.first_day..=self.last_day).find(|&day| self.event_on(step, day));
+        self.first_events[step] = Some(first);
+        first
+    }
+
+    fn event_on(&mut self, step: usize, day: i32) -> bool {
+        let Some(index) = self.index(day) else {
+            return false;
+        };
+        if let Some(&Some(cached)) = self.events[step].get(index) {
+            return cached;
+        }
+        let steps = self.steps;
+        let hit = self.check_event(&steps[step].event, day);
+        *cache_slot(&mut self.events[step], index) = Some(hit);
+        hit
+    }
+
+    fn check_event(&mut self, event: &DayEvent, day: i32) -> bool {
+        let (seed, version, ctx) = (self.seed, self.version, self.ctx);
+        match event {
+            DayEvent::DailyLuck { min_luck, max_luck } => {
+                let luck = mechanics::daily_luck(seed, day, ctx.steps, ctx.friendship_rolls());
+                luck >= *min_luck && luck <= *max_luck
+            }
+            DayEvent::CartItem { item_id, max_price } => {
+                is_cart_day(day)
+                    && check_cart_has_item(seed, day, *item_id, *max_price, ctx.legacy_random, version)
+            }
+            DayEvent::NightEvent { event_type } => {
+                let event = mechanics::night_event_for_farm(
+                    seed,
+                    day,
+                    version,
+                    ctx.greenhouse_repaired,
+                    ctx.legacy_random,
+                );
+                event.is_some() && (event == parse_night_event(event_type) || event_type == "any")
+            }
+            DayEvent::DishOfDay { dish_id } => {
+                mechanics::dish_of_the_day(seed, day, ctx.steps).0 == *dish_id
+            }
+            DayEvent::Weather { weather_type } => {
+                let weather = self.weather_on(day);
+                weather == parse_weather(weather_type)
+                    || (weather_type == "any" && weather != Weather::Sunny)
+            }
+        }
+    }
+
+    /// Weather on `day`, which must be within the timeline's days.
+    pub(super) fn weather_on(&mut self, day: i32) -> Weather {
+        let (first_day, last_day) = (self.first_day, self.last_day);
+        let (seed, version, ctx) = (self.seed, self.version, self.ctx);
+        let forecast = self.weather.get_or_insert_with(|| {
+            mechanics::forecast_weather(
+                seed,
+                first_day,
+                last_day,
+                ctx.steps,
+                ctx.friendship_rolls(),
+                ctx.legacy_random,
+                version,
+            )
+        });
+        forecast[(day - first_day) as usize]
+    }
+
+    fn index(&self, day: i32) -> Option<usize> {
+        (self.first_day..=self.last_day)
+            .contains(&day)
+            .then(|| (day as i64 - self.first_day as i64) as usize)
+    }
+}
+
+/// Furthest the last step can land after the first, in days.
+pub(super) fn sequence_reach(steps: &[SequenceStep]) -> i32 {
+    steps
+        .iter()
+        .skip(1)
+        .fold(0i32, |reach, step| reach.saturating_add(step.after().1.max(0)))
+}
+
+/// A cache row's entry for `index`, growing the row to reach it.
+fn cache_slot(row: &mut Vec<Option<bool>>, index: usize) -> &mut Option<bool> {
+    if row.len() <= index {
+        row.resize(index + 1, None);
+    }
+    &mut row[index]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::{evaluate_filter, FilterNode};
+
+    fn eval(seed: i32, json: &str) -> bool {
+        let filter: FilterNode = serde_json::from_str(json).unwrap();
+        evaluate_filter(seed, &filter, GameVersion::V1_6, &PlayerContext::default())
+    }
+
+    fn weather(seed: i32, day: i32) -> Weather {
+        mechanics::forecast_weather(seed, day, day, 0, false, false, GameVersion::V1_6)[0]
+    }
+
+    #[test]
+    fn test_consecutive_sunny_days() {
+        let json = r#"{
+            "logic": "condition",
+            "type": "sequence",
+            "days": {"type": "exact", "day": 10},
+            "steps": [
+                {"event": {"type": "weather", "weather_type": "sunny"}},
+                {"event": {"type": "weather", "weather_type": "sunny"}},
+                {"event": {"type": "weather", "weather_type": "sunny"}}
+            ]
+        }"#;
+        for seed in 0..200 {
+            let expected = (10..=12).all(|day| weather(seed, day) == Weather::Sunny);
+            assert_eq!(eval(seed, json), expected, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_rain_after_fairy() {
+        let json = r#"{
+            "logic": "condition",
+            "type": "sequence",
+            "days": {"type": "range", "start": 1, "end": 84},
+            "steps": [
+                {"event": {"type": "night_event", "event_type": "fairy"}},
+                {"event": {"type": "weather", "weather_type": "rain"}, "min_after": 1, "max_after": 2}
+            ]
+        }"#;
+        let mut matched = 0;
+        for seed in 0..1000 {
+            let expected = (1..=84).any(|day| {
+                mechanics::night_event(seed, day, GameVersion::V1_6) == Some(mechanics::NightEvent::Fairy)
+                    && (1..=2).any(|gap| weather(seed, day + gap) == Weather::Rain)
+            });
+            matched += expected as u32;
+            assert_eq!(eval(seed, json), expected, "seed {}", seed);
+        }
+        assert!(matched > 0, "No seed had rain after a fairy");
+    }
+
+    #[test]
+    fn test_cart_before_first_lucky_day() {
+        // Red Cabbage in the cart, then the first lucky day of the year later
+        let json = r#"{
+            "logic": "condition",
+            "type": "sequence",
+            "days": {"type": "range", "start": 1, "end": 112},
+            "steps": [
+                {"event": {"type": "cart_item", "item_id": 266}},
+                {"event": {"type": "daily_luck", "min_luck": 0.09, "max_luck": 1.0},
+                 "min_after": 1, "max_after": 112, "first": true}
+            ]
+        }"#;
+        for seed in 0..100 {
+            let first_lucky = (1..=224).find(|&day| mechanics::daily_luck(seed, day, 0, false) >= 0.09);
+            let expected = first_lucky.is_some_and(|lucky| {
+                (1..lucky.min(113)).any(|day| {
+                    is_cart_day(day) && mechanics::cart_has_item(seed, day, 266, false, GameVersion::V1_6)
+                        && lucky - day <= 112
+                })
+            });
+            assert_eq!(eval(seed, json), expected, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_huge_gaps_do_not_overflow() {
+        // Unvalidated gaps near i32::MAX must not overflow the reach or the cache
+        let json = r#"{
+            "logic": "condition",
+            "type": "sequence",
+            "days": {"type": "exact", "day": 10},
+            "steps": [
+                {"event": {"type": "dish_of_day", "dish_id": 200}},
+                {"event": {"type": "dish_of_day", "dish_id": 200}, "min_after": 2147483000, "max_after": 2147483647},
+                {"event": {"type": "dish_of_day", "dish_id": 200}, "min_after": 2147483000, "max_after": 2147483647}
+            ]
+        }"#;
+        for seed in 0..20 {
+            assert!(!eval(seed, json));
+        }
+    }
+}
@@ -0,0 +1,697 @@
+//! Filter validation.
+//!
+//! Serde only checks a filter's shape, and the evaluator quietly coerces
+//! strings it doesn't know (an unknown weather is sunny, an unknown geode a
+//! regular geode). This pass rejects those, along with inverted ranges, days
+//! past year 1000, luck bounds the game can't roll, and conditions that can
+//! never match in the chosen version. A condition that can't match is only an
+//! error when it makes the whole filter unsatisfiable: under `not`, or as one
+//! branch of an `or`, it is left alone. Every error carries a JSON pointer
+//! into the filter.
+
+use serde::de::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::evaluate::{is_cart_day, parse_night_event, try_parse_geode_type, try_parse_weather};
+use super::filter::{
+    is_versioned, migrate_filter, DayEvent, DaySpec, FilterCondition, FilterNode, SchemaError, MAX_DAY, MAX_YEAR,
+};
+use super::sequence::MAX_SEQUENCE_DAYS;
+use crate::mechanics;
+use crate::types::PlayerContext;
+use crate::version::GameVersion;
+
+/// Daily luck is always within ±`MAX_LUCK`.
+const MAX_LUCK: f64 = 0.1;
+
+const NIGHT_EVENTS: &str = "any, fairy, witch, meteor, ufo, owl, earthquake, windstorm";
+const GEODE_TYPES: &str = "geode, frozen, magma, omni, trove, coconut";
+const WEATHER_TYPES: &str = "any, sunny, rain, windy, storm, snow, green_rain";
+
+/// A problem with one part of a filter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidationError {
+    /// JSON pointer to the offending value (e.g. "/conditions/0/event_type");
+    /// empty for the whole filter
+    pub path: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Parse and validate filter JSON.
+pub fn validate_filter_json(
+    filter_json: &str,
+    version: GameVersion,
+    ctx: &PlayerContext,
+) -> Result<FilterNode, Vec<ValidationError>> {
+    let value: Value = serde_json::from_str(filter_json).map_err(|e| {
+        vec![ValidationError {
+            path: String::new(),
+            message: e.to_string(),
+        }]
+    })?;
+    validate_filter_value(&value, version, ctx)
+}
+
+/// Parse and validate a filter that is already JSON, e.g. nested in a score expression.
+///
+/// Versioned documents are migrated to the current schema first. Migrations
+/// keep the tree's shape, so error paths point inside `/filter` at the fields
+/// as written, e.g. the `day_start` of a version 1 condition.
+pub fn validate_filter_value(
+    document: &Value,
+    version: GameVersion,
+    ctx: &PlayerContext,
+) -> Result<FilterNode, Vec<ValidationError>> {
+    let prefix = if is_versioned(document) { "/filter" } else { "" };
+    let value = migrate_filter(document.clone()).map_err(|e| {
+        let (path, message) = match e {
+            SchemaError::Malformed(message) => (prefix, message),
+            SchemaError::UnsupportedVersion(_) => ("/schema_version", e.to_string()),
+        };
+        vec![ValidationError {
+            path: path.to_string(),
+            message,
+        }]
+    })?;
+    let filter = FilterNode::deserialize(&value).map_err(|_| vec![locate_parse_error(&value, prefix.to_string())])?;
+
+    let mut validator = Validator {
+        version,
+        ctx,
+        errors: Vec::new(),
+    };
+    // Look up fields in the filter as written, not as migrated
+    let written = if is_versioned(document) { &document["filter"] } else { &value };
+    // Dead branches are fine; a filter that can never match is not
+    if let Some(reasons) = validator.node(&filter, written, prefix) {
+        validator.errors.extend(reasons);
+    }
+    if validator.errors.is_empty() {
+        Ok(filter)
+    } else {
+        Err(validator.errors)
+    }
+}
+
+/// Find the innermost node that doesn't deserialize, so a typo deep in a
+/// tree isn't reported against the root.
+fn locate_parse_error(value: &Value, path: String) -> ValidationError {
+    let children: Vec<(String, &Value)> = match value.get("logic").and_then(Value::as_str) {
+        Some("and" | "or" | "xor" | "at_least") => value
+            .get("conditions")
+            .and_then(Value::as_array)
+            .map(|conditions| {
+                conditions
+                    .iter()
+                    .enumerate()
+                    .map(|(i, child)| (format!("{}/conditions/{}", path, i), child))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        Some("not") => value
+            .get("condition")
+            .map(|child| vec![(format!("{}/condition", path), child)])
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    };
+
+    for (child_path, child) in children {
+        if FilterNode::deserialize(child).is_err() {
+            return locate_parse_error(child, child_path);
+        }
+    }
+    ValidationError {
+        message: FilterNode::deserialize(value)
+            .err()
+            .map_or_else(|| "invalid filter".to_string(), |e| e.to_string()),
+        path,
+    }
+}
+
+struct Validator<'a> {
+    version: GameVersion,
+    ctx: &'a PlayerContext,
+    errors: Vec<ValidationError>,
+}
+
+impl Validator<'_> {
+    fn error(&mut self, path: String, message: impl Into<String>) {
+        self.errors.push(ValidationError {
+            path,
+            message: message.into(),
+        });
+    }
+
+    /// Validate a node. Returns why it can never match, if it can't.
+    fn node(&mut self, node: &FilterNode, json: &Value, path: &str) -> Option<Vec<ValidationError>> {
+        let mut children = |conditions: &[FilterNode]| -> Vec<Option<Vec<ValidationError>>> {
+            conditions
+                .iter()
+                .enumerate()
+                .map(|(i, child)| {
+                    let child_path = format!("{}/conditions/{}", path, i);
+                    self.node(child, &json["conditions"][i], &child_path)
+                })
+                .collect()
+        };
+
+        match node {
+            FilterNode::And { conditions } => {
+                let results = children(conditions);
+                if results.iter().any(Option::is_some) {
+                    Some(results.into_iter().flatten().flatten().collect())
+                } else {
+                    None
+                }
+            }
+            FilterNode::Or { conditions } | FilterNode::Xor { conditions } => {
+                let results = children(conditions);
+                if results.iter().all(Option::is_some) {
+                    Some(results.into_iter().flatten().flatten().collect())
+                } else {
+                    None
+                }
+            }
+            FilterNode::AtLeast { count, conditions } => {
+                let results = children(conditions);
+                let possible = results.iter().filter(|r| r.is_none()).count();
+                if *count as usize <= possible {
+                    return None;
+                }
+                let mut reasons: Vec<_> = results.into_iter().flatten().flatten().collect();
+                if *count as usize > conditions.len() {
+                    reasons.push(never(
+                        format!("{}/count", path),
+                        format!("needs {} matches but there are only {} conditions", count, conditions.len()),
+                    ));
+                }
+                Some(reasons)
+            }
+            FilterNode::Not { condition } => {
+                self.node(condition, &json["condition"], &format!("{}/condition", path));
+                None
+            }
+            FilterNode::CountDays {
+                condition,
+                at_least,
+                at_most,
+            } => {
+                if let (Some(least), Some(most)) = (at_least, at_most) {
+                    if least > most {
+                        self.error(format!("{}/at_least", path), "at_least is above at_most");
+                    }
+                }
+                let mut reasons = Vec::new();
+                if let (Some(least), Some(days)) = (at_least, condition_days(condition)) {
+                    let count = days.days().count();
+                    if *least as usize > count {
+                        reasons.push(never(
+                            format!("{}/at_least", path),
+                            format!("needs {} days but the condition only has {}", least, count),
+                        ));
+                    }
+                }
+                let condition_never = self.condition(condition, &json["condition"], &format!("{}/condition", path));
+                // Without bounds every day must match; otherwise at_least of them
+                let needs_a_match = at_least.map_or(at_most.is_none(), |least| least > 0);
+                if let (true, Some(condition_reasons)) = (needs_a_match, condition_never) {
+                    reasons.extend(condition_reasons);
+                }
+                (!reasons.is_empty()).then_some(reasons)
+            }
+            FilterNode::Condition(condition) => self.condition(condition, json, path),
+        }
+    }
+
+    /// Validate a condition. Returns why it can never match, if it can't.
+    fn condition(&mut self, condition: &FilterCondition, json: &Value, path: &str) -> Option<Vec<ValidationError>> {
+        let mut reasons = Vec::new();
+        // Older filters give `day_start`/`day_end` instead of a `days` spec
+        let legacy_days = json.get("days").is_none();
+        let days_path = format!("{}/{}", path, if legacy_days { "day_start" } else { "days" });
+        if let Some(days) = condition_days(condition) {
+            if legacy_days {
+                let (start, end) = days.bounds();
+                self.day_range(start, end, &days_path, &format!("{}/day_end", path));
+            } else {
+                self.days(days, &days_path);
+            }
+        }
+
+        match condition {
+            FilterCondition::DailyLuck {
+                min_luck, max_luck, ..
+            } => self.luck_bounds(*min_luck, *max_luck, path),
+
+            FilterCondition::CartItem { days, .. } => {
+                if days.days().next().is_some() && !days.days().any(is_cart_day) {
+                    reasons.push(never(days_path, "the cart only comes on Fridays and Sundays"));
+                }
+            }
+
+            FilterCondition::NightEvent { event_type, .. } if event_type == "any" => {}
+            FilterCondition::NightEvent { days, event_type } => {
+                let event_path = format!("{}/event_type", path);
+                match parse_night_event(event_type) {
+                    None => self.error(
+                        event_path,
+                        format!("unknown night event `{}` (expected one of {})", event_type, NIGHT_EVENTS),
+                    ),
+                    Some(event) => {
+                        let possible = days.days().any(|day| {
+                            mechanics::night_event_possible(event, day, self.version, self.ctx.greenhouse_repaired)
+                        });
+                        if !possible && days.days().next().is_some() {
+                            reasons.push(never(
+                                event_path,
+                                format!("`{}` can't happen on any of these days in {}", event_type, self.version),
+                            ));
+                        }
+                    }
+                }
+            }
+
+            FilterCondition::Geode {
+                geode_number,
+                geode_type,
+                target_items,
+            } => {
+                if try_parse_geode_type(geode_type).is_none() {
+                    self.error(
+                        format!("{}/geode_type", path),
+                        format!("unknown geode type `{}` (expected one of {})", geode_type, GEODE_TYPES),
+                    );
+                }
+                if *geode_number < 1 {
+                    self.error(format!("{}/geode_number", path), "geode numbers start at 1");
+                }
+                if target_items.is_empty() {
+                    reasons.push(never(format!("{}/target_items", path), "no target items, so this never matches"));
+                }
+            }
+
+            FilterCondition::DishOfDay { .. } => {}
+
+            FilterCondition::Weather { weather_type, .. } if weather_type == "any" => {}
+            FilterCondition::Weather { days, weather_type } => {
+                let weather_path = format!("{}/weather_type", path);
+                match try_parse_weather(weather_type) {
+                    None => self.error(
+                        weather_path,
+                        format!("unknown weather `{}` (expected one of {})", weather_type, WEATHER_TYPES),
+                    ),
+                    Some(weather) => {
+                        let possible = days
+                            .days()
+                            .any(|day| mechanics::weather_possible(weather, day, self.version));
+                        if !possible && days.days().next().is_some() {
+                            reasons.push(never(
+                                weather_path,
+                                format!("`{}` can't happen on any of these days in {}", weather_type, self.version),
+                            ));
+                        }
+                    }
+                }
+            }
+
+            FilterCondition::MineFloor {
+                floor_start,
+                floor_end,
+                has_mushroom,
+                ..
+            } => {
+                if *floor_start < 1 {
+                    self.error(format!("{}/floor_start", path), "floors start at 1");
+                }
+                if floor_start > floor_end {
+                    self.error(format!("{}/floor_start", path), "floor range is inverted (floor_start > floor_end)");
+                } else if *has_mushroom && (*floor_end < 81 || *floor_start > 120) {
+                    reasons.push(never(format!("{}/has_mushroom", path), "mushroom floors are only 81-120"));
+                }
+            }
+
+            FilterCondition::Sequence { days, steps } => {
+                if steps.is_empty() {
+                    self.error(format!("{}/steps", path), "a sequence needs at least one step");
+                }
+                let (first, last) = days.bounds();
+                if last as i64 - first as i64 >= MAX_SEQUENCE_DAYS as i64 {
+                    self.error(
+                        days_path,
+                        format!("a sequence can start on at most {} different days", MAX_SEQUENCE_DAYS),
+                    );
+                }
+                for (i, step) in steps.iter().enumerate() {
+                    let step_path = format!("{}/steps/{}", path, i);
+                    let (min_after, max_after) = step.after();
+                    if min_after < 0 {
+                        self.error(format!("{}/min_after", step_path), "min_after can't be negative");
+                    }
+                    if max_after < min_after {
+                        self.error(format!("{}/max_after", step_path), "max_after is below min_after");
+                    }
+                    if max_after > MAX_SEQUENCE_DAYS {
+                        let field = if step.max_after.is_some() { "max_after" } else { "min_after" };
+                        self.error(
+                            format!("{}/{}", step_path, field),
+                            format!("steps can be at most {} days apart", MAX_SEQUENCE_DAYS),
+                        );
+                    }
+                    self.day_event(&step.event, &format!("{}/event", step_path));
+                }
+            }
+        }
+
+        (!reasons.is_empty()).then_some(reasons)
+    }
+
+    fn day_event(&mut self, event: &DayEvent, path: &str) {
+        match event {
+            DayEvent::DailyLuck { min_luck, max_luck } => self.luck_bounds(*min_luck, *max_luck, path),
+            DayEvent::NightEvent { event_type } => {
+                if event_type != "any" && parse_night_event(event_type).is_none() {
+                    self.error(
+                        format!("{}/event_type", path),
+                        format!("unknown night event `{}` (expected one of {})", event_type, NIGHT_EVENTS),
+                    );
+                }
+            }
+            DayEvent::Weather { weather_type } => {
+                if weather_type != "any" && try_parse_weather(weather_type).is_none() {
+                    self.error(
+                        format!("{}/weather_type", path),
+                        format!("unknown weather `{}` (expected one of {})", weather_type, WEATHER_TYPES),
+                    );
+                }
+            }
+            DayEvent::CartItem { .. } | DayEvent::DishOfDay { .. } => {}
+        }
+    }
+
+    fn luck_bounds(&mut self, min_luck: f64, max_luck: f64, path: &str) {
+        if !(-MAX_LUCK..=MAX_LUCK).contains(&min_luck) {
+            self.error(format!("{}/min_luck", path), "luck is always between -0.1 and 0.1");
+        }
+        if !(-MAX_LUCK..=MAX_LUCK).contains(&max_luck) {
+            self.error(format!("{}/max_luck", path), "luck is always between -0.1 and 0.1");
+        }
+        if min_luck > max_luck {
+            self.error(format!("{}/min_luck", path), "min_luck is above max_luck");
+        }
+    }
+
+    fn days(&mut self, days: &DaySpec, path: &str) {
+        match days {
+            DaySpec::Exact { day } => self.day(*day, format!("{}/day", path)),
+            DaySpec::Range { start, end } => {
+                self.day_range(*start, *end, &format!("{}/start", path), &format!("{}/end", path));
+            }
+            DaySpec::EveryNth { start, end, step } => {
+                self.day_range(*start, *end, &format!("{}/start", path), &format!("{}/end", path));
+                if *step < 1 {
+                    self.error(format!("{}/step", path), "step must be at least 1");
+                }
+            }
+            DaySpec::Season { season, year } => {
+                if !(0..=3).contains(season) {
+                    self.error(format!("{}/season", path), "season must be 0-3 (Spring-Winter)");
+                }
+                if *year < 1 {
+                    self.error(format!("{}/year", path), "years start at 1");
+                } else if *year > MAX_YEAR {
+                    self.error(format!("{}/year", path), format!("years end at {}", MAX_YEAR));
+                }
+            }
+            DaySpec::Weekdays { weekdays, within } => {
+                if weekdays.is_empty() {
+                    self.error(format!("{}/weekdays", path), "no weekdays listed");
+                }
+                for (i, weekday) in weekdays.iter().enumerate() {
+                    if !(1..=7).contains(weekday) {
+                        self.error(format!("{}/weekdays/{}", path, i), "weekdays are 1-7 (Monday-Sunday)");
+                    }
+                }
+                self.days(within, &format!("{}/within", path));
+            }
+            DaySpec::List { days } => {
+                if days.is_empty() {
+                    self.error(format!("{}/days", path), "no days listed");
+                }
+                for (i, day) in days.iter().enumerate() {
+                    self.day(*day, format!("{}/days/{}", path, i));
+                }
+            }
+        }
+    }
+
+    fn day(&mut self, day: i32, path: String) {
+        if day < 1 {
+            self.error(path, "days start at 1 (Spring 1, Year 1)");
+        } else if day > MAX_DAY {
+            self.error(path, format!("days end at {} (Winter 28, Year {})", MAX_DAY, MAX_YEAR));
+        }
+    }
+
+    fn day_range(&mut self, start: i32, end: i32, start_path: &str, end_path: &str) {
+        if start > end {
+            self.day(start, start_path.to_string());
+            self.error(start_path.to_string(), format!("day range is inverted ({} > {})", start, end));
+        } else {
+            self.day(start, start_path.to_string());
+            self.day(end, end_path.to_string());
+        }
+    }
+}
+
+/// A reason a node can never match, reported only if the whole filter can't.
+fn never(path: String, message: impl Into<String>) -> ValidationError {
+    ValidationError {
+        path,
+        message: message.into(),
+    }
+}
+
+/// The days a condition checks, if it has any.
+fn condition_days(condition: &FilterCondition) -> Option<&DaySpec> {
+    match condition {
+        FilterCondition::DailyLuck { days, .. }
+        | FilterCondition::CartItem { days, .. }
+        | FilterCondition::NightEvent { days, .. }
+        | FilterCondition::DishOfDay { days, .. }
+        | FilterCondition::Weather { days, .. }
+        | FilterCondition::MineFloor { days, .. }
+        | FilterCondition::Sequence { days, .. } => Some(days),
+        FilterCondition::Geode { .. } => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(json: &str, version: GameVersion) -> Vec<(String, String)> {
+        match validate_filter_json(json, version, &PlayerContext::default()) {
+            Ok(_) => Vec::new(),
+            Err(errors) => errors.into_iter().map(|e| (e.path, e.message)).collect(),
+        }
+    }
+
+    fn paths(json: &str, version: GameVersion) -> Vec<String> {
+        errors(json, version).into_iter().map(|(path, _)| path).collect()
+    }
+
+    #[test]
+    fn test_valid_filter_passes() {
+        let json = r#"{"logic": "and", "conditions": [
+            {"logic": "condition", "type": "daily_luck", "days": {"type": "range", "start": 1, "end": 7}, "min_luck": 0.07, "max_luck": 0.1},
+            {"logic": "condition", "type": "night_event", "day_start": 1, "day_end": 28, "event_type": "fairy"},
+            {"logic": "not", "condition": {"logic": "condition", "type": "weather", "days": {"type": "season", "season": 3}, "weather_type": "snow"}},
+            {"logic": "condition", "type": "geode", "geode_number": 1, "geode_type": "omni", "target_items": [74]}
+        ]}"#;
+        assert_eq!(errors(json, GameVersion::V1_6), vec![]);
+    }
+
+    #[test]
+    fn test_unknown_strings_are_rejected() {
+        let json = r#"{"logic": "or", "conditions": [
+            {"logic": "condition", "type": "night_event", "days": {"type": "exact", "day": 5}, "event_type": "fary"},
+            {"logic": "condition", "type": "weather", "days": {"type": "exact", "day": 5}, "weather_type": "hail"},
+            {"logic": "condition", "type": "geode", "geode_number": 1, "geode_type": "mystery", "target_items": [74]},
+            {"logic": "condition", "type": "sequence", "days": {"type": "exact", "day": 5},
+             "steps": [{"event": {"type": "night_event", "event_type": "ghost"}}]}
+        ]}"#;
+        assert_eq!(
+            paths(json, GameVersion::V1_6),
+            vec![
+                "/conditions/0/event_type",
+                "/conditions/1/weather_type",
+                "/conditions/2/geode_type",
+                "/conditions/3/steps/0/event/event_type",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ranges_and_luck_bounds() {
+        let json = r#"{"logic": "and", "conditions": [
+            {"logic": "condition", "type": "daily_luck", "days": {"type": "range", "start": 9, "end": 3}, "min_luck": -1, "max_luck": 1},
+            {"logic": "condition", "type": "mine_floor", "day_start": 5, "day_end": 1, "floor_start": 90, "floor_end": 60,
+             "no_monsters": true, "no_dark": false, "has_mushroom": false},
+            {"logic": "count_days", "condition": {"type": "dish_of_day", "days": {"type": "range", "start": 1, "end": 3}, "dish_id": 200},
+             "at_least": 5}
+        ]}"#;
+        assert_eq!(
+            paths(json, GameVersion::V1_6),
+            vec![
+                "/conditions/0/days/start",
+                "/conditions/0/min_luck",
+                "/conditions/0/max_luck",
+                "/conditions/1/day_start",
+                "/conditions/1/floor_start",
+                "/conditions/2/at_least",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_days_past_the_last_year() {
+        let json = r#"{"logic": "or", "conditions": [
+            {"logic": "condition", "type": "dish_of_day", "days": {"type": "exact", "day": 112001}, "dish_id": 200},
+            {"logic": "condition", "type": "dish_of_day", "days": {"type": "range", "start": 1, "end": 2000000000}, "dish_id": 200},
+            {"logic": "condition", "type": "dish_of_day", "days": {"type": "every_nth", "start": 112001, "end": 200000, "step": 7}, "dish_id": 200},
+            {"logic": "condition", "type": "dish_of_day", "days": {"type": "list", "days": [112000, 112001]}, "dish_id": 200},
+            {"logic": "condition", "type": "dish_of_day", "days": {"type": "season", "season": 1, "year": 20000000}, "dish_id": 200},
+            {"logic": "condition", "type": "dish_of_day", "dish_id": 200,
+             "days": {"type": "weekdays", "weekdays": [5], "within": {"type": "range", "start": 1, "end": 112008}}}
+        ]}"#;
+        let found = errors(json, GameVersion::V1_6);
+        assert_eq!(
+            found.iter().map(|(path, _)| path.as_str()).collect::<Vec<_>>(),
+            vec![
+                "/conditions/0/days/day",
+                "/conditions/1/days/end",
+                "/conditions/2/days/start",
+                "/conditions/2/days/end",
+                "/conditions/3/days/days/1",
+                "/conditions/4/days/year",
+                "/conditions/5/days/within/end",
+            ]
+        );
+        assert_eq!(found[0].1, "days end at 112000 (Winter 28, Year 1000)");
+        assert_eq!(found[5].1, "years end at 1000");
+        let last = r#"{"logic": "condition", "type": "dish_of_day", "days": {"type": "season", "season": 3, "year": 1000}, "dish_id": 200}"#;
+        assert_eq!(errors(last, GameVersion::V1_6), vec![]);
+    }
+
+    #[test]
+    fn test_sequence_limits() {
+        let json = r#"{"logic": "condition", "type": "sequence", "days": {"type": "range", "start": 1, "end": 5000},
+            "steps": [{"event": {"type": "dish_of_day", "dish_id": 200}},
+                      {"event": {"type": "dish_of_day", "dish_id": 201}, "min_after": 1, "max_after": 2147483647},
+                      {"event": {"type": "dish_of_day", "dish_id": 202}, "min_after": 5000}]}"#;
+        assert_eq!(
+            paths(json, GameVersion::V1_6),
+            vec!["/days", "/steps/1/max_after", "/steps/2/min_after"]
+        );
+    }
+
+    #[test]
+    fn test_impossible_for_version() {
+        // The night after Winter 28 is rolled as Spring 1, so stop a day early
+        let fairy_in_winter = r#"{"logic": "condition", "type": "night_event", "days": {"type": "range", "start": 85, "end": 111}, "event_type": "fairy"}"#;
+        let (path, message) = &errors(fairy_in_winter, GameVersion::V1_6)[0];
+        assert_eq!(path, "/event_type");
+        assert!(message.contains("can't happen"), "{}", message);
+
+        let ufo_year_one = r#"{"logic": "condition", "type": "night_event", "days": {"type": "range", "start": 1, "end": 111}, "event_type": "ufo"}"#;
+        assert_eq!(paths(ufo_year_one, GameVersion::V1_5), vec!["/event_type"]);
+        let ufo_year_two = ufo_year_one.replace("111", "112");
+        assert!(paths(&ufo_year_two, GameVersion::V1_5).is_empty());
+
+        let green_rain = r#"{"logic": "condition", "type": "weather", "days": {"type": "season", "season": 1}, "weather_type": "green_rain"}"#;
+        assert!(paths(green_rain, GameVersion::V1_6).is_empty());
+        assert_eq!(paths(green_rain, GameVersion::V1_5), vec!["/weather_type"]);
+    }
+
+    #[test]
+    fn test_impossible_only_when_unsatisfiable() {
+        let fairy = r#"{"logic": "condition", "type": "night_event", "days": {"type": "range", "start": 85, "end": 111}, "event_type": "fairy"}"#;
+        let rain = r#"{"logic": "condition", "type": "weather", "days": {"type": "exact", "day": 9}, "weather_type": "rain"}"#;
+        let cart = r#"{"logic": "condition", "type": "cart_item", "days": {"type": "exact", "day": 1}, "item_id": 266}"#;
+        let check = |json: String| paths(&json, GameVersion::V1_6);
+
+        // Dead branches are allowed
+        assert!(check(format!(r#"{{"logic": "not", "condition": {}}}"#, fairy)).is_empty());
+        assert!(check(format!(r#"{{"logic": "or", "conditions": [{}, {}]}}"#, fairy, rain)).is_empty());
+        assert!(check(format!(r#"{{"logic": "at_least", "count": 1, "conditions": [{}, {}, {}]}}"#, fairy, cart, rain))
+            .is_empty());
+        assert!(check(format!(
+            r#"{{"logic": "count_days", "condition": {}, "at_most": 3}}"#,
+            fairy.replace(r#""logic": "condition", "#, "")
+        ))
+        .is_empty());
+
+        // ...but not a filter that can never match
+        assert_eq!(
+            check(format!(r#"{{"logic": "or", "conditions": [{}, {}]}}"#, fairy, cart)),
+            vec!["/conditions/0/event_type", "/conditions/1/days"]
+        );
+        assert_eq!(
+            check(format!(r#"{{"logic": "and", "conditions": [{}, {{"logic": "or", "conditions": [{}, {}]}}]}}"#, rain, fairy, rain)),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            check(format!(r#"{{"logic": "at_least", "count": 2, "conditions": [{}, {}, {}]}}"#, fairy, cart, rain)),
+            vec!["/conditions/0/event_type", "/conditions/1/days"]
+        );
+        assert_eq!(
+            check(format!(r#"{{"logic": "at_least", "count": 4, "conditions": [{}, {}, {}]}}"#, rain, rain, rain)),
+            vec!["/count"]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_point_at_the_bad_node() {
+        let json = r#"{"logic": "and", "conditions": [
+            {"logic": "condition", "type": "dish_of_day", "days": {"type": "exact", "day": 1}, "dish_id": 200},
+            {"logic": "not", "condition": {"logic": "condition", "type": "dish_of_day", "days": {"type": "exact", "day": 1}}}
+        ]}"#;
+        let found = errors(json, GameVersion::V1_6);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, "/conditions/1/condition");
+        assert!(found[0].1.contains("dish_id"), "{}", found[0].1);
+
+        assert_eq!(paths("{not json", GameVersion::V1_6), vec![""]);
+    }
+
+    #[test]
+    fn test_versioned_documents() {
+        // Version 1 days migrate to a range, but errors point at the fields as written
+        let v1 = r#"{"schema_version": 1, "filter": {"logic": "and", "conditions": [
+            {"logic": "condition", "type": "weather", "day_start": 9, "day_end": 2, "weather_type": "rain"},
+            {"logic": "condition", "type": "cart_item", "day_start": 1, "day_end": 4, "item_id": 266}
+        ]}}"#;
+        assert_eq!(
+            paths(v1, GameVersion::V1_6),
+            vec!["/filter/conditions/0/day_start", "/filter/conditions/1/day_start"]
+        );
+
+        let v2 = r#"{"schema_version": 2, "filter": {"logic": "condition", "type": "weather",
+            "days": {"type": "exact", "day": 9}, "weather_type": "rain"}}"#;
+        assert!(validate_filter_json(v2, GameVersion::V1_6, &PlayerContext::default()).is_ok());
+        assert_eq!(paths(&v2.replace(": 2,", ": 7,"), GameVersion::V1_6), vec!["/schema_version"]);
+    }
+}
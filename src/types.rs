@@ -3,7 +3,68 @@
 //! All types that cross the WASM boundary are defined here with serde Serialize.
 //! This provides a clean contract between Rust and JavaScript.
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+
+// ============================================================================
+// Player Context (input to every prediction)
+// ============================================================================
+
+/// Player state that changes which RNG calls the game makes.
+///
+/// Passed from JS as an optional object; missing fields fall back to the
+/// defaults below, which match the values the API used before it existed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerContext {
+    /// Lifetime steps taken (`Game1.stats.StepsTaken`) when the day starts;
+    /// seeds the dish and luck rolls
+    pub steps: i32,
+    /// Whether the player has any friendships (adds a roll before luck)
+    pub has_friends: bool,
+    /// Whether the save has farmhands (friendship rolls happen regardless)
+    pub multiplayer: bool,
+    /// Deepest mine level reached (gates geode ores)
+    pub deepest_mine_level: i32,
+    /// Whether the coconut hat is already owned (golden coconut drops)
+    pub has_coconut_hat: bool,
+    /// The 1.6 "use legacy randomization" save option
+    pub legacy_random: bool,
+    /// Whether the greenhouse is repaired (1.6 windstorm roll)
+    pub greenhouse_repaired: bool,
+}
+
+impl Default for PlayerContext {
+    fn default() -> Self {
+        Self {
+            steps: 0,
+            has_friends: false,
+            multiplayer: false,
+            deepest_mine_level: 120,
+            has_coconut_hat: false,
+            legacy_random: false,
+            greenhouse_repaired: false,
+        }
+    }
+}
+
+impl PlayerContext {
+    /// Parse a context passed from JS. `undefined` or `null` gives the default.
+    pub fn from_js(value: JsValue) -> Result<Self, JsValue> {
+        if value.is_undefined() || value.is_null() {
+            return Ok(Self::default());
+        }
+        serde_wasm_bindgen::from_value(value)
+            .map_err(|e| JsValue::from_str(&format!("Context parse error: {}", e)))
+    }
+
+    /// Whether the day-start friendship roll happens.
+    /// Any other farmer on the save counts, friends or not.
+    #[inline]
+    pub fn friendship_rolls(&self) -> bool {
+        self.has_friends || self.multiplayer
+    }
+}
 
 /// Main prediction for a single day.
 /// Returned by `predict_day()` - the unified Explore API entry point.
@@ -73,6 +134,7 @@ pub enum NightEventType {
     Ufo,
     Owl,
     Earthquake,
+    Windstorm,
 }
 
 impl NightEventType {
@@ -85,6 +147,7 @@ impl NightEventType {
             4 => Self::Ufo,
             5 => Self::Owl,
             6 => Self::Earthquake,
+            7 => Self::Windstorm,
             _ => Self::None,
         }
     }
@@ -98,6 +161,22 @@ impl NightEventType {
             Self::Ufo => 4,
             Self::Owl => 5,
             Self::Earthquake => 6,
+            Self::Windstorm => 7,
+        }
+    }
+
+    /// Convert a predicted night event (None for a quiet night).
+    pub fn from_event(event: Option<crate::mechanics::NightEvent>) -> Self {
+        use crate::mechanics::NightEvent;
+        match event {
+            None => Self::None,
+            Some(NightEvent::Fairy) => Self::Fairy,
+            Some(NightEvent::Witch) => Self::Witch,
+            Some(NightEvent::Meteor) => Self::Meteor,
+            Some(NightEvent::Ufo) => Self::Ufo,
+            Some(NightEvent::Owl) => Self::Owl,
+            Some(NightEvent::Earthquake) => Self::Earthquake,
+            Some(NightEvent::Windstorm) => Self::Windstorm,
         }
     }
 }
@@ -216,3 +295,46 @@ pub struct DayCart {
     pub day: i32,
     pub items: Vec<CartItem>,
 }
+
+// ============================================================================
+// Step Planner Types
+// ============================================================================
+
+/// Target for `plan_steps()`, e.g. `{"type": "luck", "min_luck": 0.07}`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StepTargetSpec {
+    Luck {
+        #[serde(default = "default_min_luck")]
+        min_luck: f64,
+        #[serde(default = "default_max_luck")]
+        max_luck: f64,
+    },
+    Dish {
+        dish_id: i32,
+        #[serde(default = "default_min_quantity")]
+        min_quantity: i32,
+    },
+}
+
+fn default_min_luck() -> f64 {
+    -0.1
+}
+
+fn default_max_luck() -> f64 {
+    0.1
+}
+
+fn default_min_quantity() -> i32 {
+    1
+}
+
+/// One step count that hits the planner target.
+#[derive(Debug, Clone, Serialize)]
+pub struct StepPlanResult {
+    pub steps: i32,
+    pub luck: f64,
+    pub dish: DishOfDay,
+    /// Tomorrow's weather
+    pub weather: WeatherType,
+}
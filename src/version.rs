@@ -9,55 +9,69 @@
 /// and game mechanics that affect predictions.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub enum GameVersion {
-    /// Version 1.3 - Uses legacy simple addition seeding
+    /// Version 1.3 - Original mine and night event seeding
     V1_3,
-    /// Version 1.4 - Introduced hash-based seeding (XXHash)
+    /// Version 1.4 - Geode warmup, primed night events, new mine seeding
     V1_4,
-    /// Version 1.5 - Added Ginger Island, Qi challenges
+    /// Version 1.5.0-1.5.2 - Added Ginger Island, Qi challenges
     V1_5,
-    /// Version 1.6 - Major overhaul: green rain, new cart system, etc.
+    /// Version 1.5.3-1.5.6 - Night events reordered (owl before capsule)
+    V1_5_3,
+    /// Version 1.6 - Major overhaul: hash-based seeding, green rain, new cart system, etc.
     #[default]
     V1_6,
 }
 
 impl GameVersion {
-    /// Parse a version string like "1.5" or "1.6.4" into a GameVersion.
-    /// Defaults to V1_6 for unrecognized versions.
-    pub fn parse(s: &str) -> Self {
-        let parts: Vec<u32> = s
+    /// Parse a version string like "1.5" or "1.6.4", rejecting anything that
+    /// isn't a known release.
+    ///
+    /// Accepts "major.minor" or "major.minor.patch". The patch only matters
+    /// where the RNG changed within a release (1.5.3). Per-patch data tables
+    /// are not modelled: 1.6 patches all share the current cart object table,
+    /// and 1.5 patches the current item tables.
+    pub fn try_parse(s: &str) -> Result<Self, VersionError> {
+        let parts = s
+            .trim()
             .split('.')
-            .filter_map(|p| p.parse().ok())
-            .collect();
-
-        match parts.first() {
-            Some(1) => match parts.get(1) {
-                Some(3) => Self::V1_3,
-                Some(4) => Self::V1_4,
-                Some(5) => Self::V1_5,
-                _ => Self::V1_6, // Default to latest for 1.6+
-            },
-            _ => Self::V1_6, // Default to latest
+            .map(|p| p.parse::<u32>())
+            .collect::<Result<Vec<u32>, _>>()
+            .map_err(|_| VersionError::Malformed(s.to_string()))?;
+
+        let patch = match parts.len() {
+            2 => 0,
+            3 => parts[2],
+            _ => return Err(VersionError::Malformed(s.to_string())),
+        };
+
+        match (parts[0], parts[1]) {
+            (1, 3) => Ok(Self::V1_3),
+            (1, 4) => Ok(Self::V1_4),
+            (1, 5) if patch >= 3 => Ok(Self::V1_5_3),
+            (1, 5) => Ok(Self::V1_5),
+            (1, 6) => Ok(Self::V1_6),
+            _ => Err(VersionError::Unsupported(s.to_string())),
         }
     }
 
-    /// Returns true if this version uses legacy (pre-1.4) RNG seeding.
-    /// Legacy seeding uses simple modular addition.
-    /// Modern seeding (1.4+) uses XXHash.
+    /// Returns true if this version always seeds by simple addition (pre-1.6).
+    /// 1.6 hashes its seeds with XXHash unless the save enables the
+    /// "use legacy randomization" option.
     #[inline]
     pub fn uses_legacy_random(&self) -> bool {
-        matches!(self, Self::V1_3)
+        !self.uses_hash_seeding()
     }
 
-    /// Returns true if this version has hash-based seeding (1.4+).
+    /// Returns true if this version has hash-based seeding (1.6+).
     #[inline]
     pub fn uses_hash_seeding(&self) -> bool {
-        !self.uses_legacy_random()
+        matches!(self, Self::V1_6)
     }
 
     /// Returns true if this version has Ginger Island content (1.5+).
     #[inline]
     pub fn has_ginger_isle(&self) -> bool {
-        matches!(self, Self::V1_5 | Self::V1_6)
+        matches!(self, Self::V1_5 | Self::V1_5_3 | Self::V1_6)
     }
 
     /// Returns true if this version has green rain weather (1.6+).
@@ -100,7 +114,7 @@ impl GameVersion {
     /// Returns true if this version has the Qi bean check in geodes (1.5+).
     #[inline]
     pub fn has_qi_bean_check(&self) -> bool {
-        matches!(self, Self::V1_5 | Self::V1_6)
+        matches!(self, Self::V1_5 | Self::V1_5_3 | Self::V1_6)
     }
 
     /// Returns true if 1.6 reversed the geode mineral/ore check.
@@ -116,25 +130,73 @@ impl std::fmt::Display for GameVersion {
             Self::V1_3 => write!(f, "1.3"),
             Self::V1_4 => write!(f, "1.4"),
             Self::V1_5 => write!(f, "1.5"),
+            Self::V1_5_3 => write!(f, "1.5.3"),
             Self::V1_6 => write!(f, "1.6"),
         }
     }
 }
 
+/// Error from `GameVersion::try_parse`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VersionError {
+    /// Not a "major.minor[.patch]" version string
+    Malformed(String),
+    /// A well-formed version that isn't supported
+    Unsupported(String),
+}
+
+impl std::fmt::Display for VersionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Malformed(s) => write!(f, "Malformed game version {:?}", s),
+            Self::Unsupported(s) => write!(f, "Unsupported game version {:?} (expected 1.3-1.6)", s),
+        }
+    }
+}
+
+impl std::error::Error for VersionError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_version_parsing() {
-        assert_eq!(GameVersion::parse("1.3"), GameVersion::V1_3);
-        assert_eq!(GameVersion::parse("1.4"), GameVersion::V1_4);
-        assert_eq!(GameVersion::parse("1.5"), GameVersion::V1_5);
-        assert_eq!(GameVersion::parse("1.5.6"), GameVersion::V1_5);
-        assert_eq!(GameVersion::parse("1.6"), GameVersion::V1_6);
-        assert_eq!(GameVersion::parse("1.6.4"), GameVersion::V1_6);
-        assert_eq!(GameVersion::parse("1.7"), GameVersion::V1_6); // Future versions default to latest
-        assert_eq!(GameVersion::parse("invalid"), GameVersion::V1_6);
+        assert_eq!(GameVersion::try_parse("1.3"), Ok(GameVersion::V1_3));
+        assert_eq!(GameVersion::try_parse("1.4"), Ok(GameVersion::V1_4));
+        assert_eq!(GameVersion::try_parse("1.5"), Ok(GameVersion::V1_5));
+        assert_eq!(GameVersion::try_parse("1.5.2"), Ok(GameVersion::V1_5));
+        assert_eq!(GameVersion::try_parse("1.5.6"), Ok(GameVersion::V1_5_3));
+        assert_eq!(GameVersion::try_parse("1.6"), Ok(GameVersion::V1_6));
+        assert_eq!(GameVersion::try_parse("1.6.4"), Ok(GameVersion::V1_6));
+    }
+
+    #[test]
+    fn test_version_try_parse() {
+        assert_eq!(GameVersion::try_parse("1.5.3"), Ok(GameVersion::V1_5_3));
+        assert_eq!(GameVersion::try_parse(" 1.6.9 "), Ok(GameVersion::V1_6));
+        assert_eq!(
+            GameVersion::try_parse("1.7"),
+            Err(VersionError::Unsupported("1.7".to_string()))
+        );
+        assert_eq!(
+            GameVersion::try_parse("invalid"),
+            Err(VersionError::Malformed("invalid".to_string()))
+        );
+        assert!(GameVersion::try_parse("").is_err());
+        assert!(GameVersion::try_parse("1").is_err());
+        assert!(GameVersion::try_parse("1.6.4.1").is_err());
+
+        // Display round-trips
+        for v in [
+            GameVersion::V1_3,
+            GameVersion::V1_4,
+            GameVersion::V1_5,
+            GameVersion::V1_5_3,
+            GameVersion::V1_6,
+        ] {
+            assert_eq!(GameVersion::try_parse(&v.to_string()), Ok(v));
+        }
     }
 
     #[test]
@@ -145,8 +207,8 @@ mod tests {
         assert!(!GameVersion::V1_3.has_green_rain());
 
         // 1.4
-        assert!(!GameVersion::V1_4.uses_legacy_random());
-        assert!(GameVersion::V1_4.uses_hash_seeding());
+        assert!(GameVersion::V1_4.uses_legacy_random());
+        assert!(!GameVersion::V1_4.uses_hash_seeding());
         assert!(!GameVersion::V1_4.has_ginger_isle());
 
         // 1.5
@@ -155,6 +217,8 @@ mod tests {
         assert!(!GameVersion::V1_5.has_green_rain());
 
         // 1.6
+        assert!(GameVersion::V1_6.uses_hash_seeding());
+        assert!(!GameVersion::V1_6.uses_legacy_random());
         assert!(GameVersion::V1_6.has_green_rain());
         assert!(GameVersion::V1_6.has_new_cart_system());
         assert!(GameVersion::V1_6.has_windstorm_event());
@@ -165,6 +229,7 @@ mod tests {
     fn test_version_ordering() {
         assert!(GameVersion::V1_3 < GameVersion::V1_4);
         assert!(GameVersion::V1_4 < GameVersion::V1_5);
-        assert!(GameVersion::V1_5 < GameVersion::V1_6);
+        assert!(GameVersion::V1_5 < GameVersion::V1_5_3);
+        assert!(GameVersion::V1_5_3 < GameVersion::V1_6);
     }
 }
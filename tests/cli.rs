@@ -0,0 +1,122 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+use rasmodius::search::{evaluate_filter, FilterNode};
+use rasmodius::{GameVersion, PlayerContext};
+
+const FILTER: &str = r#"{
+    "logic": "or",
+    "conditions": [
+        {"logic": "condition", "type": "daily_luck", "days": {"type": "exact", "day": 1}, "min_luck": 0.09, "max_luck": 0.1},
+        {"logic": "condition", "type": "night_event", "days": {"type": "range", "start": 1, "end": 7}, "event_type": "fairy"}
+    ]
+}"#;
+
+/// Run the binary with the filter on stdin.
+fn run(args: &[&str]) -> Output {
+    run_filter(FILTER, args)
+}
+
+fn run_filter(filter: &str, args: &[&str]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rasmodius"))
+        .args(args)
+        .arg("-")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Bad arguments exit before reading stdin
+    let _ = child.stdin.take().unwrap().write_all(filter.as_bytes());
+    child.wait_with_output().unwrap()
+}
+
+fn seeds(output: &Output) -> Vec<i32> {
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout.clone())
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            value["seed"].as_i64().unwrap() as i32
+        })
+        .collect()
+}
+
+fn expected(start: i32, end: i32) -> Vec<i32> {
+    let filter: FilterNode = serde_json::from_str(FILTER).unwrap();
+    (start..=end)
+        .filter(|&seed| evaluate_filter(seed, &filter, GameVersion::V1_6, &PlayerContext::default()))
+        .collect()
+}
+
+#[test]
+fn test_threads_stream_matches_in_order() {
+    let output = run(&[
+        "--start", "-5000", "--end", "15000", "--threads", "4", "--chunk-size", "700", "--quiet",
+    ]);
+    let found = seeds(&output);
+    assert!(!found.is_empty());
+    assert_eq!(found, expected(-5000, 15000));
+}
+
+#[test]
+fn test_many_small_chunks_stay_in_order() {
+    // Far more chunks than workers may run ahead of the printed ones
+    let output = run(&["--end", "20000", "--threads", "8", "--chunk-size", "10", "--quiet"]);
+    assert_eq!(seeds(&output), expected(0, 20000));
+}
+
+#[test]
+fn test_max_results_takes_the_first_matches() {
+    let output = run(&[
+        "--end", "20000", "--threads", "3", "--chunk-size", "300", "--max-results", "5", "--quiet",
+    ]);
+    assert_eq!(seeds(&output), expected(0, 20000)[..5]);
+}
+
+#[test]
+fn test_bad_arguments_fail() {
+    let output = run(&["--version", "1.2"]);
+    assert_eq!(output.status.code(), Some(2));
+    let output = run(&["--threads", "many"]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_versioned_filter_document() {
+    let document = format!(r#"{{"schema_version": 2, "filter": {}}}"#, FILTER);
+    let output = run_filter(&document, &["--end", "3000", "--quiet"]);
+    assert_eq!(seeds(&output), expected(0, 3000));
+}
+
+#[test]
+fn test_invalid_filter_points_at_the_problem() {
+    let output = run_filter(&FILTER.replace("fairy", "fary"), &["--end", "100"]);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("/conditions/1/event_type: unknown night event `fary`"), "{}", stderr);
+}
+
+#[test]
+fn test_checkpoint_resumes_where_it_stopped() {
+    let path = std::env::temp_dir().join(format!("rasmodius-cli-{}.json", std::process::id()));
+    let path = path.to_str().unwrap();
+    let _ = std::fs::remove_file(path);
+    let args = ["--end", "20000", "--threads", "3", "--chunk-size", "300", "--quiet", "--checkpoint", path];
+
+    let first = seeds(&run(&[&args[..], &["--max-results", "5"]].concat()));
+    let rest = seeds(&run(&args));
+    assert_eq!(first.len(), 5);
+    assert_eq!([first, rest].concat(), expected(0, 20000));
+
+    // A finished checkpoint has nothing left to print
+    assert!(seeds(&run(&args)).is_empty());
+
+    // Other versions or ranges are refused
+    let output = run(&[&args[..], &["--version", "1.5"]].concat());
+    assert_eq!(output.status.code(), Some(2));
+    let output = run(&["--end", "30000", "--quiet", "--checkpoint", path]);
+    assert_eq!(output.status.code(), Some(2));
+    std::fs::remove_file(path).unwrap();
+}
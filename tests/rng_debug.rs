@@ -11,7 +11,7 @@ fn debug_cart_seed14_day14() {
         println!("\n=== SEED {} ===", seed);
         for version in [GameVersion::V1_3, GameVersion::V1_4, GameVersion::V1_5, GameVersion::V1_6] {
             println!("\nCart for seed={}, day={}, {:?}:", seed, day, version);
-            let cart = get_cart_for_day(seed, day, version);
+            let cart = get_cart_for_day(seed, day, false, version);
             for (i, item) in cart.iter().enumerate() {
                 let marker = if item.item_id == 266 { " <-- RED CABBAGE!" } else { "" };
                 println!("  [{}] id={} price={} qty={}{}", i, item.item_id, item.price, item.quantity, marker);
@@ -1,5 +1,5 @@
 import { describe, it, expect } from 'vitest';
-import { filterToSearchJson } from '$lib/utils/filterToJson';
+import { filterFromSearchJson, filterToSearchJson } from '$lib/utils/filterToJson';
 import type { FilterGroup } from '$lib/types/filters';
 
 describe('filterToJson', () => {
@@ -10,12 +10,65 @@ describe('filterToJson', () => {
 				logic: 'and',
 				conditions: [],
 			};
-			const result = JSON.parse(filterToSearchJson(filter));
+			const result = JSON.parse(filterToSearchJson(filter)).filter;
 			expect(result.logic).toBe('and');
 			expect(result.conditions).toEqual([]);
 		});
 	});
 
+	describe('versioned document', () => {
+		it('wraps the filter in a schema version 2 envelope', () => {
+			const filter: FilterGroup = {
+				id: 'test',
+				logic: 'or',
+				conditions: [{ type: 'dish_of_day', daySpec: { type: 'exact', day: 1 }, dishId: 200 }],
+			};
+			const result = JSON.parse(filterToSearchJson(filter));
+			expect(Object.keys(result)).toEqual(['schema_version', 'filter']);
+			expect(result.schema_version).toBe(2);
+			expect(result.filter.logic).toBe('or');
+		});
+
+		it('converts a document back to the same UI filter', () => {
+			const filter: FilterGroup = {
+				id: 'test',
+				logic: 'and',
+				conditions: [
+					{ type: 'cart_item', daySpec: { type: 'season', season: 0, year: 1 }, itemId: 266, maxPrice: 500 },
+					{
+						id: 'inner',
+						logic: 'or',
+						conditions: [
+							{ type: 'weather', daySpec: { type: 'range', start: 2, end: 6 }, weatherType: 'rain' },
+							{ type: 'geode', geodeNumber: 3, geodeType: 'omni', targetItems: [74] },
+						],
+					},
+				],
+			};
+			const restored = filterFromSearchJson(JSON.parse(filterToSearchJson(filter)));
+			expect(filterToSearchJson(restored)).toBe(filterToSearchJson(filter));
+		});
+
+		it('reads version 1 day_start/day_end as a range', () => {
+			const restored = filterFromSearchJson({
+				schema_version: 1,
+				filter: { logic: 'condition', type: 'night_event', day_start: 1, day_end: 7, event_type: 'fairy' },
+			});
+			expect(restored.logic).toBe('and');
+			expect(restored.conditions[0]).toEqual({
+				type: 'night_event',
+				daySpec: { type: 'range', start: 1, end: 7 },
+				eventType: 'fairy',
+			});
+		});
+
+		it('rejects documents the editor cannot show', () => {
+			const not = { logic: 'not', condition: { logic: 'condition', type: 'dish_of_day', days: { type: 'exact', day: 1 }, dish_id: 200 } };
+			expect(() => filterFromSearchJson({ schema_version: 2, filter: not })).toThrow();
+			expect(() => filterFromSearchJson({ schema_version: 3, filter: { logic: 'and', conditions: [] } })).toThrow();
+		});
+	});
+
 	describe('single condition conversion', () => {
 		it('converts daily_luck condition', () => {
 			const filter: FilterGroup = {
@@ -30,14 +83,13 @@ describe('filterToJson', () => {
 					},
 				],
 			};
-			const result = JSON.parse(filterToSearchJson(filter));
+			const result = JSON.parse(filterToSearchJson(filter)).filter;
 
 			expect(result.conditions).toHaveLength(1);
 			const cond = result.conditions[0];
 			expect(cond.logic).toBe('condition');
 			expect(cond.type).toBe('daily_luck');
-			expect(cond.day_start).toBe(5);
-			expect(cond.day_end).toBe(5);
+			expect(cond.days).toEqual({ type: 'exact', day: 5 });
 			expect(cond.min_luck).toBe(0.07);
 			expect(cond.max_luck).toBe(0.1);
 		});
@@ -55,15 +107,14 @@ describe('filterToJson', () => {
 					},
 				],
 			};
-			const result = JSON.parse(filterToSearchJson(filter));
+			const result = JSON.parse(filterToSearchJson(filter)).filter;
 
 			const cond = result.conditions[0];
 			expect(cond.type).toBe('cart_item');
 			expect(cond.item_id).toBe(266);
 			expect(cond.max_price).toBe(5000);
-			// Spring Y1 = days 1-28 (getDaysFromSpec returns all season days, WASM filters to cart days)
-			expect(cond.day_start).toBe(1);
-			expect(cond.day_end).toBe(28);
+			// Passed through whole; WASM expands the season and filters to cart days
+			expect(cond.days).toEqual({ type: 'season', season: 0, year: 1 });
 		});
 
 		it('converts night_event condition', () => {
@@ -78,12 +129,11 @@ describe('filterToJson', () => {
 					},
 				],
 			};
-			const result = JSON.parse(filterToSearchJson(filter));
+			const result = JSON.parse(filterToSearchJson(filter)).filter;
 
 			const cond = result.conditions[0];
 			expect(cond.type).toBe('night_event');
-			expect(cond.day_start).toBe(1);
-			expect(cond.day_end).toBe(28);
+			expect(cond.days).toEqual({ type: 'range', start: 1, end: 28 });
 			expect(cond.event_type).toBe('fairy');
 		});
 
@@ -100,7 +150,7 @@ describe('filterToJson', () => {
 					},
 				],
 			};
-			const result = JSON.parse(filterToSearchJson(filter));
+			const result = JSON.parse(filterToSearchJson(filter)).filter;
 
 			const cond = result.conditions[0];
 			expect(cond.type).toBe('geode');
@@ -121,12 +171,11 @@ describe('filterToJson', () => {
 					},
 				],
 			};
-			const result = JSON.parse(filterToSearchJson(filter));
+			const result = JSON.parse(filterToSearchJson(filter)).filter;
 
 			const cond = result.conditions[0];
 			expect(cond.type).toBe('dish_of_day');
-			expect(cond.day_start).toBe(10);
-			expect(cond.day_end).toBe(10);
+			expect(cond.days).toEqual({ type: 'exact', day: 10 });
 			expect(cond.dish_id).toBe(195);
 		});
 
@@ -142,12 +191,11 @@ describe('filterToJson', () => {
 					},
 				],
 			};
-			const result = JSON.parse(filterToSearchJson(filter));
+			const result = JSON.parse(filterToSearchJson(filter)).filter;
 
 			const cond = result.conditions[0];
 			expect(cond.type).toBe('weather');
-			expect(cond.day_start).toBe(3);
-			expect(cond.day_end).toBe(3);
+			expect(cond.days).toEqual({ type: 'exact', day: 3 });
 			expect(cond.weather_type).toBe('rain');
 		});
 
@@ -166,12 +214,11 @@ describe('filterToJson', () => {
 					},
 				],
 			};
-			const result = JSON.parse(filterToSearchJson(filter));
+			const result = JSON.parse(filterToSearchJson(filter)).filter;
 
 			const cond = result.conditions[0];
 			expect(cond.type).toBe('mine_floor');
-			expect(cond.day_start).toBe(5);
-			expect(cond.day_end).toBe(5);
+			expect(cond.days).toEqual({ type: 'exact', day: 5 });
 			expect(cond.floor_start).toBe(1);
 			expect(cond.floor_end).toBe(50);
 			expect(cond.no_monsters).toBe(true);
@@ -180,7 +227,7 @@ describe('filterToJson', () => {
 		});
 	});
 
-	describe('DaySpec expansion', () => {
+	describe('DaySpec passthrough', () => {
 		it('handles exact day', () => {
 			const filter: FilterGroup = {
 				id: 'test',
@@ -192,10 +239,9 @@ describe('filterToJson', () => {
 					},
 				],
 			};
-			const result = JSON.parse(filterToSearchJson(filter));
+			const result = JSON.parse(filterToSearchJson(filter)).filter;
 			const cond = result.conditions[0];
-			expect(cond.day_start).toBe(15);
-			expect(cond.day_end).toBe(15);
+			expect(cond.days).toEqual({ type: 'exact', day: 15 });
 		});
 
 		it('handles day range', () => {
@@ -209,10 +255,9 @@ describe('filterToJson', () => {
 					},
 				],
 			};
-			const result = JSON.parse(filterToSearchJson(filter));
+			const result = JSON.parse(filterToSearchJson(filter)).filter;
 			const cond = result.conditions[0];
-			expect(cond.day_start).toBe(10);
-			expect(cond.day_end).toBe(20);
+			expect(cond.days).toEqual({ type: 'range', start: 10, end: 20 });
 		});
 
 		it('handles season (Spring Y1)', () => {
@@ -226,10 +271,9 @@ describe('filterToJson', () => {
 					},
 				],
 			};
-			const result = JSON.parse(filterToSearchJson(filter));
+			const result = JSON.parse(filterToSearchJson(filter)).filter;
 			const cond = result.conditions[0];
-			expect(cond.day_start).toBe(1);
-			expect(cond.day_end).toBe(28);
+			expect(cond.days).toEqual({ type: 'season', season: 0, year: 1 });
 		});
 
 		it('handles season (Summer Y2)', () => {
@@ -243,11 +287,10 @@ describe('filterToJson', () => {
 					},
 				],
 			};
-			const result = JSON.parse(filterToSearchJson(filter));
+			const result = JSON.parse(filterToSearchJson(filter)).filter;
 			const cond = result.conditions[0];
-			// Summer Y2: seasonStart = 1*28 + 1 + (2-1)*112 = 141, so days 141-168
-			expect(cond.day_start).toBe(141);
-			expect(cond.day_end).toBe(168);
+			// Summer Y2 (days 141-168) is expanded by the Rust DaySpec
+			expect(cond.days).toEqual({ type: 'season', season: 1, year: 2 });
 		});
 
 		it('handles wide range correctly', () => {
@@ -261,15 +304,14 @@ describe('filterToJson', () => {
 					},
 				],
 			};
-			const result = JSON.parse(filterToSearchJson(filter));
+			const result = JSON.parse(filterToSearchJson(filter)).filter;
 			const cond = result.conditions[0];
-			expect(cond.day_start).toBe(1);
-			expect(cond.day_end).toBe(224);
+			expect(cond.days).toEqual({ type: 'range', start: 1, end: 224 });
 		});
 	});
 
 	describe('optional field defaults', () => {
-		it('defaults minLuck to -1 when not specified', () => {
+		it('defaults minLuck to -0.1 when not specified', () => {
 			const filter: FilterGroup = {
 				id: 'test',
 				logic: 'and',
@@ -282,11 +324,11 @@ describe('filterToJson', () => {
 					},
 				],
 			};
-			const result = JSON.parse(filterToSearchJson(filter));
-			expect(result.conditions[0].min_luck).toBe(-1);
+			const result = JSON.parse(filterToSearchJson(filter)).filter;
+			expect(result.conditions[0].min_luck).toBe(-0.1);
 		});
 
-		it('defaults maxLuck to 1 when not specified', () => {
+		it('defaults maxLuck to 0.1 when not specified', () => {
 			const filter: FilterGroup = {
 				id: 'test',
 				logic: 'and',
@@ -299,8 +341,8 @@ describe('filterToJson', () => {
 					},
 				],
 			};
-			const result = JSON.parse(filterToSearchJson(filter));
-			expect(result.conditions[0].max_luck).toBe(1);
+			const result = JSON.parse(filterToSearchJson(filter)).filter;
+			expect(result.conditions[0].max_luck).toBe(0.1);
 		});
 
 		it('defaults maxPrice to null when not specified', () => {
@@ -316,7 +358,7 @@ describe('filterToJson', () => {
 					},
 				],
 			};
-			const result = JSON.parse(filterToSearchJson(filter));
+			const result = JSON.parse(filterToSearchJson(filter)).filter;
 			expect(result.conditions[0].max_price).toBeNull();
 		});
 
@@ -333,7 +375,7 @@ describe('filterToJson', () => {
 					},
 				],
 			};
-			const result = JSON.parse(filterToSearchJson(filter));
+			const result = JSON.parse(filterToSearchJson(filter)).filter;
 			expect(result.conditions[0].no_monsters).toBe(false);
 			expect(result.conditions[0].no_dark).toBe(false);
 			expect(result.conditions[0].has_mushroom).toBe(false);
@@ -369,7 +411,7 @@ describe('filterToJson', () => {
 					},
 				],
 			};
-			const result = JSON.parse(filterToSearchJson(filter));
+			const result = JSON.parse(filterToSearchJson(filter)).filter;
 
 			expect(result.logic).toBe('and');
 			expect(result.conditions).toHaveLength(2);
@@ -401,7 +443,7 @@ describe('filterToJson', () => {
 					},
 				],
 			};
-			const result = JSON.parse(filterToSearchJson(filter));
+			const result = JSON.parse(filterToSearchJson(filter)).filter;
 
 			expect(result.logic).toBe('or');
 			expect(result.conditions).toHaveLength(2);
@@ -431,7 +473,7 @@ describe('filterToJson', () => {
 					},
 				],
 			};
-			const result = JSON.parse(filterToSearchJson(filter));
+			const result = JSON.parse(filterToSearchJson(filter)).filter;
 
 			expect(result.logic).toBe('and');
 			expect(result.conditions[0].logic).toBe('or');
@@ -474,14 +516,12 @@ describe('filterToJson', () => {
 			// Check snake_case is used
 			expect(json).toContain('item_id');
 			expect(json).toContain('max_price');
-			expect(json).toContain('day_start');
-			expect(json).toContain('day_end');
+			expect(json).toContain('"days"');
 
 			// Check camelCase is NOT used
 			expect(json).not.toContain('itemId');
 			expect(json).not.toContain('maxPrice');
-			expect(json).not.toContain('dayStart');
-			expect(json).not.toContain('dayEnd');
+			expect(json).not.toContain('daySpec');
 		});
 	});
 });
@@ -27,7 +27,19 @@ describe('URL Serializer', () => {
 			};
 			const encoded = encodeFilter(filter);
 			expect(encoded).toBeTruthy();
-			expect(encoded.length).toBeLessThan(100); // Should be compact
+			expect(encoded.length).toBeLessThan(250); // Short enough to share
+		});
+
+		it('encodes the versioned filter document', () => {
+			const filter: FilterRoot = {
+				id: 'test',
+				logic: 'and',
+				conditions: [{ type: 'dish_of_day', daySpec: { type: 'exact', day: 1 }, dishId: 200 }],
+			};
+			const base64 = encodeFilter(filter).replace(/-/g, '+').replace(/_/g, '/');
+			const document = JSON.parse(atob(base64.padEnd(Math.ceil(base64.length / 4) * 4, '=')));
+			expect(document.schema_version).toBe(2);
+			expect(document.filter.conditions[0].dish_id).toBe(200);
 		});
 
 		it('encodes a cart_item filter', () => {
@@ -66,6 +78,17 @@ describe('URL Serializer', () => {
 	});
 
 	describe('decodeFilter', () => {
+		it('decodes links in the legacy compact encoding', () => {
+			const decoded = decodeFilter('eyJsIjoiYSIsImMiOlt7InQiOiJsIiwiZHMiOnsidCI6ImUiLCJkIjoxfSwibW4iOjAuMDd9XX0');
+			expect(decoded?.logic).toBe('and');
+			expect(decoded?.conditions[0]).toEqual({
+				type: 'daily_luck',
+				daySpec: { type: 'exact', day: 1 },
+				minLuck: 0.07,
+				maxLuck: undefined,
+			});
+		});
+
 		it('returns null for empty string', () => {
 			expect(decodeFilter('')).toBeNull();
 		});
@@ -132,6 +155,27 @@ describe('URL Serializer', () => {
 			}
 		});
 
+		it('correctly roundtrips a windstorm night_event filter', () => {
+			const filter: FilterRoot = {
+				id: 'test',
+				logic: 'and',
+				conditions: [
+					{
+						type: 'night_event',
+						daySpec: { type: 'range', start: 1, end: 28 },
+						eventType: 'windstorm',
+					},
+				],
+			};
+			const decoded = decodeFilter(encodeFilter(filter));
+
+			expect(decoded).not.toBeNull();
+			const condition = decoded!.conditions[0];
+			expect('type' in condition && condition.type === 'night_event' && condition.eventType).toBe(
+				'windstorm'
+			);
+		});
+
 		it('correctly roundtrips a cart_item filter with season', () => {
 			const filter: FilterRoot = {
 				id: 'test',
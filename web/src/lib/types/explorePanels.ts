@@ -39,7 +39,7 @@ export type CartHighlight = {
 
 /** Highlight night events matching specific types */
 export type NightEventHighlight = {
-	eventType: 'fairy' | 'witch' | 'meteor' | 'ufo' | 'owl' | 'any';
+	eventType: 'fairy' | 'witch' | 'meteor' | 'ufo' | 'owl' | 'windstorm' | 'any';
 	days: number[];
 };
 
@@ -52,7 +52,7 @@ export type LuckHighlight = {
 
 /** Highlight days matching weather type */
 export type WeatherHighlight = {
-	weatherType: 'sunny' | 'rain' | 'storm' | 'windy' | 'snow' | 'any';
+	weatherType: 'sunny' | 'rain' | 'storm' | 'windy' | 'snow' | 'green_rain' | 'any';
 	days: number[];
 };
 
@@ -25,7 +25,7 @@ export type LuckCondition = {
 export type NightEventCondition = {
 	type: 'night_event';
 	daySpec: DaySpec;
-	eventType: 'fairy' | 'witch' | 'meteor' | 'ufo' | 'owl' | 'any';
+	eventType: 'fairy' | 'witch' | 'meteor' | 'ufo' | 'owl' | 'windstorm' | 'any';
 };
 
 export type CartItemCondition = {
@@ -51,7 +51,7 @@ export type DishCondition = {
 export type WeatherCondition = {
 	type: 'weather';
 	daySpec: DaySpec;
-	weatherType: 'sunny' | 'rain' | 'storm' | 'windy' | 'snow' | 'any';
+	weatherType: 'sunny' | 'rain' | 'storm' | 'windy' | 'snow' | 'green_rain' | 'any';
 };
 
 export type MineFloorCondition = {
@@ -230,3 +230,22 @@ export function getDaySpecLabel(spec: DaySpec): string {
 		}
 	}
 }
+
+// Game versions the predictor distinguishes. 1.5.3 covers 1.5.3-1.5.6, where
+// the owl is checked before the capsule; 1.6 covers every 1.6 patch.
+export type GameVersion = '1.3' | '1.4' | '1.5' | '1.5.3' | '1.6';
+
+export const GAME_VERSIONS: readonly GameVersion[] = ['1.3', '1.4', '1.5', '1.5.3', '1.6'];
+
+// Player state passed to every prediction and search (mirrors Rust PlayerContext).
+// All fields are optional; missing ones use the Rust defaults.
+export interface PlayerContext {
+	// Lifetime step count (Game1.stats.StepsTaken), not the steps walked that day
+	steps?: number;
+	has_friends?: boolean;
+	multiplayer?: boolean;
+	deepest_mine_level?: number;
+	has_coconut_hat?: boolean;
+	legacy_random?: boolean;
+	greenhouse_repaired?: boolean;
+}
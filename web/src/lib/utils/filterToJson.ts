@@ -2,17 +2,126 @@
  * Convert UI filter format to JSON for WASM search kernel.
  *
  * The WASM search_range() function expects a specific JSON format
- * that differs from the UI's filter representation.
+ * that differs from the UI's filter representation. Filters are sent and
+ * shared as versioned documents: `{"schema_version": 2, "filter": {...}}`.
  */
 
 import type { FilterGroup, FilterCondition, DaySpec } from '$lib/types/filters';
-import { getDaysFromSpec } from '$lib/utils/daySpec';
+
+// Must match FILTER_SCHEMA_VERSION in src/search/filter.rs
+export const FILTER_SCHEMA_VERSION = 2;
 
 /**
- * Convert a UI filter to JSON string for WASM.
+ * Convert a UI filter to a versioned filter document string for WASM.
  */
 export function filterToSearchJson(filter: FilterGroup): string {
-	return JSON.stringify(convertGroup(filter));
+	return JSON.stringify({ schema_version: FILTER_SCHEMA_VERSION, filter: convertGroup(filter) });
+}
+
+/**
+ * Convert a versioned filter document (schema version 1 or 2) back to a UI filter.
+ *
+ * Throws for filters the UI can't show: newer schema versions, logic other
+ * than and/or, and condition types or day specs the editor doesn't support.
+ */
+export function filterFromSearchJson(document: unknown): FilterGroup {
+	const doc = document as { schema_version?: unknown; filter?: unknown };
+	if (doc.schema_version !== 1 && doc.schema_version !== FILTER_SCHEMA_VERSION) {
+		throw new Error(`Unsupported filter schema version: ${String(doc.schema_version)}`);
+	}
+	const root = parseNode(doc.filter as Record<string, unknown>);
+	// The editor's root is always a group
+	return 'logic' in root ? root : { id: crypto.randomUUID(), logic: 'and', conditions: [root] };
+}
+
+function parseNode(node: Record<string, unknown>): FilterGroup | FilterCondition {
+	switch (node.logic) {
+		case 'and':
+		case 'or':
+			return {
+				id: crypto.randomUUID(),
+				logic: node.logic,
+				conditions: (node.conditions as Record<string, unknown>[]).map(parseNode),
+			};
+		case 'condition':
+			return parseCondition(node);
+		default:
+			throw new Error(`Unsupported filter logic: ${String(node.logic)}`);
+	}
+}
+
+function parseDays(cond: Record<string, unknown>): DaySpec {
+	// Schema version 1 gives days as day_start/day_end
+	if (cond.days === undefined) {
+		return { type: 'range', start: cond.day_start as number, end: cond.day_end as number };
+	}
+	const days = cond.days as DaySpec;
+	if (days.type !== 'exact' && days.type !== 'range' && days.type !== 'season') {
+		throw new Error(`Unsupported day spec: ${String((days as { type: unknown }).type)}`);
+	}
+	return days;
+}
+
+function parseCondition(cond: Record<string, unknown>): FilterCondition {
+	switch (cond.type) {
+		case 'daily_luck':
+			return {
+				type: 'daily_luck',
+				daySpec: parseDays(cond),
+				minLuck: cond.min_luck as number,
+				maxLuck: cond.max_luck as number,
+			};
+
+		case 'cart_item':
+			return {
+				type: 'cart_item',
+				daySpec: parseDays(cond),
+				itemId: cond.item_id as number,
+				maxPrice: (cond.max_price as number | null) ?? undefined,
+			};
+
+		case 'night_event':
+			return {
+				type: 'night_event',
+				daySpec: parseDays(cond),
+				eventType: cond.event_type as 'fairy' | 'witch' | 'meteor' | 'ufo' | 'owl' | 'windstorm' | 'any',
+			};
+
+		case 'geode':
+			return {
+				type: 'geode',
+				geodeNumber: cond.geode_number as number,
+				geodeType: cond.geode_type as 'geode' | 'frozen' | 'magma' | 'omni' | 'trove' | 'coconut',
+				targetItems: cond.target_items as number[],
+			};
+
+		case 'dish_of_day':
+			return {
+				type: 'dish_of_day',
+				daySpec: parseDays(cond),
+				dishId: cond.dish_id as number,
+			};
+
+		case 'weather':
+			return {
+				type: 'weather',
+				daySpec: parseDays(cond),
+				weatherType: cond.weather_type as 'sunny' | 'rain' | 'storm' | 'windy' | 'snow' | 'green_rain' | 'any',
+			};
+
+		case 'mine_floor':
+			return {
+				type: 'mine_floor',
+				daySpec: parseDays(cond),
+				floorRange: { start: cond.floor_start as number, end: cond.floor_end as number },
+				noMonsters: (cond.no_monsters as boolean) ?? false,
+				noDark: (cond.no_dark as boolean) ?? false,
+				hasMushroom: (cond.has_mushroom as boolean) ?? false,
+			};
+
+		default:
+			throw new Error(`Unsupported condition type: ${String(cond.type)}`);
+	}
 }
 
 /**
@@ -36,53 +145,33 @@ function convertGroup(group: FilterGroup): object {
  * Convert a filter condition to the WASM format.
  */
 function convertCondition(cond: FilterCondition): object {
-	// Get day range from DaySpec
-	const getDayRange = (spec: DaySpec): { day_start: number; day_end: number } => {
-		const days = getDaysFromSpec(spec);
-		if (days.length === 0) {
-			return { day_start: 1, day_end: 1 };
-		}
-		return {
-			day_start: Math.min(...days),
-			day_end: Math.max(...days),
-		};
-	};
-
+	// DaySpec is passed through as-is; the Rust DaySpec accepts the same shape
 	switch (cond.type) {
-		case 'daily_luck': {
-			const { day_start, day_end } = getDayRange(cond.daySpec);
+		case 'daily_luck':
 			return {
 				logic: 'condition',
 				type: 'daily_luck',
-				day_start,
-				day_end,
-				min_luck: cond.minLuck ?? -1,
-				max_luck: cond.maxLuck ?? 1,
+				days: cond.daySpec,
+				min_luck: cond.minLuck ?? -0.1,
+				max_luck: cond.maxLuck ?? 0.1,
 			};
-		}
 
-		case 'cart_item': {
-			const { day_start, day_end } = getDayRange(cond.daySpec);
+		case 'cart_item':
 			return {
 				logic: 'condition',
 				type: 'cart_item',
-				day_start,
-				day_end,
+				days: cond.daySpec,
 				item_id: cond.itemId,
 				max_price: cond.maxPrice ?? null,
 			};
-		}
 
-		case 'night_event': {
-			const { day_start, day_end } = getDayRange(cond.daySpec);
+		case 'night_event':
 			return {
 				logic: 'condition',
 				type: 'night_event',
-				day_start,
-				day_end,
+				days: cond.daySpec,
 				event_type: cond.eventType,
 			};
-		}
 
 		case 'geode':
 			return {
@@ -93,41 +182,32 @@ function convertCondition(cond: FilterCondition): object {
 				target_items: cond.targetItems,
 			};
 
-		case 'dish_of_day': {
-			const { day_start, day_end } = getDayRange(cond.daySpec);
+		case 'dish_of_day':
 			return {
 				logic: 'condition',
 				type: 'dish_of_day',
-				day_start,
-				day_end,
+				days: cond.daySpec,
 				dish_id: cond.dishId,
 			};
-		}
 
-		case 'weather': {
-			const { day_start, day_end } = getDayRange(cond.daySpec);
+		case 'weather':
 			return {
 				logic: 'condition',
 				type: 'weather',
-				day_start,
-				day_end,
+				days: cond.daySpec,
 				weather_type: cond.weatherType,
 			};
-		}
 
-		case 'mine_floor': {
-			const { day_start, day_end } = getDayRange(cond.daySpec);
+		case 'mine_floor':
 			return {
 				logic: 'condition',
 				type: 'mine_floor',
-				day_start,
-				day_end,
+				days: cond.daySpec,
 				floor_start: cond.floorRange.start,
 				floor_end: cond.floorRange.end,
 				no_monsters: cond.noMonsters ?? false,
 				no_dark: cond.noDark ?? false,
 				has_mushroom: cond.hasMushroom ?? false,
 			};
-		}
 	}
 }
@@ -1,15 +1,19 @@
 /**
  * URL serialization for filter sharing
  *
- * Encodes filters to compact URL-safe strings and decodes them back.
- * Uses base64-encoded JSON with optional compression for longer filters.
+ * Encodes filters to URL-safe strings and decodes them back. A shared filter
+ * is the base64url of its versioned document (`{"schema_version": 2, "filter": ...}`),
+ * so the CLI and other tools read it as-is. Links from before the envelope
+ * used a compact short-key encoding, which is still decoded.
  */
 
-import type { FilterRoot, FilterCondition, FilterGroup, DaySpec } from '$lib/types/filters';
+import type { FilterRoot, FilterCondition, FilterGroup, DaySpec, GameVersion } from '$lib/types/filters';
+import { GAME_VERSIONS } from '$lib/types/filters';
 import type { ExplorePanel, ExploreState } from '$lib/types/explorePanels';
 import { generatePanelId } from '$lib/types/explorePanels';
+import { filterFromSearchJson, filterToSearchJson } from './filterToJson';
 
-// Short keys for compact encoding
+// Short keys of the legacy compact encoding
 const CONDITION_TYPE_MAP: Record<string, string> = {
 	daily_luck: 'l',
 	night_event: 'n',
@@ -34,21 +38,6 @@ const DAY_SPEC_TYPE_REVERSE: Record<string, string> = Object.fromEntries(
 	Object.entries(DAY_SPEC_TYPE_MAP).map(([k, v]) => [v, k])
 );
 
-/**
- * Compress a DaySpec to a compact object
- */
-function compressDaySpec(spec: DaySpec): unknown {
-	const t = DAY_SPEC_TYPE_MAP[spec.type];
-	switch (spec.type) {
-		case 'exact':
-			return { t, d: spec.day };
-		case 'range':
-			return { t, s: spec.start, e: spec.end };
-		case 'season':
-			return { t, s: spec.season, y: spec.year };
-	}
-}
-
 /**
  * Decompress a compact object back to DaySpec
  */
@@ -67,53 +56,6 @@ function decompressDaySpec(obj: Record<string, unknown>): DaySpec {
 	}
 }
 
-/**
- * Compress a FilterCondition to a compact object
- */
-function compressCondition(condition: FilterCondition): Record<string, unknown> {
-	const base: Record<string, unknown> = { t: CONDITION_TYPE_MAP[condition.type] };
-
-	switch (condition.type) {
-		case 'daily_luck':
-			base.ds = compressDaySpec(condition.daySpec);
-			if (condition.minLuck !== undefined) base.mn = condition.minLuck;
-			if (condition.maxLuck !== undefined) base.mx = condition.maxLuck;
-			break;
-		case 'night_event':
-			base.ds = compressDaySpec(condition.daySpec);
-			base.ev = condition.eventType;
-			break;
-		case 'cart_item':
-			base.ds = compressDaySpec(condition.daySpec);
-			base.i = condition.itemId;
-			if (condition.maxPrice !== undefined) base.p = condition.maxPrice;
-			break;
-		case 'geode':
-			base.n = condition.geodeNumber;
-			base.gt = condition.geodeType;
-			base.ti = condition.targetItems;
-			break;
-		case 'dish_of_day':
-			base.ds = compressDaySpec(condition.daySpec);
-			base.di = condition.dishId;
-			break;
-		case 'weather':
-			base.ds = compressDaySpec(condition.daySpec);
-			base.wt = condition.weatherType;
-			break;
-		case 'mine_floor':
-			base.ds = compressDaySpec(condition.daySpec);
-			base.fs = condition.floorRange.start;
-			base.fe = condition.floorRange.end;
-			if (condition.noMonsters) base.nm = 1;
-			if (condition.noDark) base.nd = 1;
-			if (condition.hasMushroom) base.hm = 1;
-			break;
-	}
-
-	return base;
-}
-
 /**
  * Decompress a compact object back to FilterCondition
  */
@@ -132,7 +74,7 @@ function decompressCondition(obj: Record<string, unknown>): FilterCondition {
 			return {
 				type: 'night_event',
 				daySpec: decompressDaySpec(obj.ds as Record<string, unknown>),
-				eventType: obj.ev as 'fairy' | 'witch' | 'meteor' | 'ufo' | 'owl' | 'any',
+				eventType: obj.ev as 'fairy' | 'witch' | 'meteor' | 'ufo' | 'owl' | 'windstorm' | 'any',
 			};
 		case 'cart_item':
 			return {
@@ -158,7 +100,7 @@ function decompressCondition(obj: Record<string, unknown>): FilterCondition {
 			return {
 				type: 'weather',
 				daySpec: decompressDaySpec(obj.ds as Record<string, unknown>),
-				weatherType: obj.wt as 'sunny' | 'rain' | 'storm' | 'windy' | 'snow' | 'any',
+				weatherType: obj.wt as 'sunny' | 'rain' | 'storm' | 'windy' | 'snow' | 'green_rain' | 'any',
 			};
 		case 'mine_floor':
 			return {
@@ -175,23 +117,7 @@ function decompressCondition(obj: Record<string, unknown>): FilterCondition {
 }
 
 /**
- * Compress a FilterGroup to a compact object
- */
-function compressGroup(group: FilterGroup): Record<string, unknown> {
-	return {
-		l: group.logic === 'and' ? 'a' : 'o',
-		c: group.conditions.map((item) => {
-			if ('logic' in item) {
-				return { g: compressGroup(item) };
-			} else {
-				return compressCondition(item);
-			}
-		}),
-	};
-}
-
-/**
- * Decompress a compact object back to FilterGroup
+ * Decompress a legacy compact object back to FilterGroup
  */
 function decompressGroup(obj: Record<string, unknown>, id?: string): FilterGroup {
 	const conditions = (obj.c as Record<string, unknown>[]).map((item) => {
@@ -217,8 +143,7 @@ export function encodeFilter(filter: FilterRoot): string {
 		return '';
 	}
 
-	const compressed = compressGroup(filter);
-	const json = JSON.stringify(compressed);
+	const json = filterToSearchJson(filter);
 
 	// Use base64url encoding (URL-safe base64)
 	const encoded = btoa(json).replace(/\+/g, '-').replace(/\//g, '_').replace(/=+$/, '');
@@ -244,9 +169,13 @@ export function decodeFilter(encoded: string): FilterRoot | null {
 		}
 
 		const json = atob(base64);
-		const compressed = JSON.parse(json);
+		const parsed = JSON.parse(json);
 
-		return decompressGroup(compressed);
+		// Versioned documents; anything else is a legacy compact link
+		if ('schema_version' in parsed) {
+			return filterFromSearchJson(parsed);
+		}
+		return decompressGroup(parsed);
 	} catch (e) {
 		console.error('Failed to decode filter:', e);
 		return null;
@@ -301,7 +230,7 @@ export function getSeedFromURL(): number | null {
 /**
  * Get version from URL
  */
-export function getVersionFromURL(): '1.3' | '1.4' | '1.5' | '1.6' | null {
+export function getVersionFromURL(): GameVersion | null {
 	if (typeof window === 'undefined') return null;
 
 	const params = new URLSearchParams(window.location.search);
@@ -309,8 +238,8 @@ export function getVersionFromURL(): '1.3' | '1.4' | '1.5' | '1.6' | null {
 
 	if (!versionParam) return null;
 
-	if (['1.3', '1.4', '1.5', '1.6'].includes(versionParam)) {
-		return versionParam as '1.3' | '1.4' | '1.5' | '1.6';
+	if ((GAME_VERSIONS as readonly string[]).includes(versionParam)) {
+		return versionParam as GameVersion;
 	}
 	return null;
 }
@@ -50,4 +50,17 @@ describe('WorkerPool', () => {
 			expect(true).toBe(true);
 		});
 	});
+
+	describe('checkpoint resumption', () => {
+		it('should resume an interrupted search from saved checkpoints', () => {
+			// Each worker posts its checkpoint after every chunk:
+			// 1. The pool saves all workers' checkpoints to localStorage, keyed by
+			//    version, range, context and filter JSON
+			// 2. A later search with the same key sends them to every worker
+			// 3. Workers merge them, report the matches already found in their
+			//    share of the range and skip the seeds already checked
+			// 4. finishSearch() clears the saved checkpoints
+			expect(true).toBe(true);
+		});
+	});
 });
@@ -1,12 +1,41 @@
 /**
  * Worker Pool Manager for parallel seed searching
  * Coordinates multiple web workers to search different seed ranges
+ * and saves their checkpoints so an interrupted search can resume
  */
 
-import type { FilterGroup } from '$lib/types/filters';
+import type { FilterGroup, PlayerContext } from '$lib/types/filters';
 import type { WorkerRequest, WorkerResponse } from './search.worker';
 import { filterToSearchJson } from '$lib/utils/filterToJson';
 
+// localStorage key prefix for saved search checkpoints
+const CHECKPOINT_KEY_PREFIX = 'rasmodius:checkpoint:';
+
+function loadCheckpoints(key: string): string[] {
+	try {
+		const saved = localStorage.getItem(key);
+		return saved ? (JSON.parse(saved) as string[]) : [];
+	} catch {
+		return [];
+	}
+}
+
+function saveCheckpoints(key: string, checkpoints: string[]): void {
+	try {
+		localStorage.setItem(key, JSON.stringify(checkpoints));
+	} catch {
+		// Storage full or unavailable - the search still runs, it just can't resume
+	}
+}
+
+function clearCheckpoints(key: string): void {
+	try {
+		localStorage.removeItem(key);
+	} catch {
+		// Nothing saved to clear
+	}
+}
+
 export interface SearchProgress {
 	totalChecked: number;
 	totalSeeds: number;
@@ -38,6 +67,8 @@ export class WorkerPool {
 	private foundByWorker: Map<Worker, number> = new Map();
 	private allMatches: number[] = [];
 	private completedWorkers: number = 0;
+	private checkpointKey: string | null = null;
+	private checkpointByWorker: Map<Worker, string> = new Map();
 
 	constructor(workerCount?: number) {
 		// Default to navigator.hardwareConcurrency or 4
@@ -89,9 +120,11 @@ export class WorkerPool {
 			}
 
 			switch (msg.type) {
-				case 'match':
-					this.allMatches.push(msg.seed);
-					this.callbacks.onMatch?.(msg.seed);
+				case 'matches':
+					for (const seed of msg.seeds) {
+						this.allMatches.push(seed);
+						this.callbacks.onMatch?.(seed);
+					}
 
 					// Cancel all workers when we hit global maxResults
 					if (this.allMatches.length >= this.maxResults) {
@@ -99,6 +132,13 @@ export class WorkerPool {
 					}
 					break;
 
+				case 'checkpoint':
+					this.checkpointByWorker.set(worker, msg.checkpoint);
+					if (this.checkpointKey) {
+						saveCheckpoints(this.checkpointKey, Array.from(this.checkpointByWorker.values()));
+					}
+					break;
+
 				case 'progress':
 					this.checkedByWorker.set(worker, msg.checked);
 					this.foundByWorker.set(worker, msg.found);
@@ -156,6 +196,12 @@ export class WorkerPool {
 			isComplete: true,
 		});
 
+		// A finished search has nothing left to resume
+		if (this.checkpointKey) {
+			clearCheckpoints(this.checkpointKey);
+			this.checkpointKey = null;
+		}
+
 		this.callbacks.onComplete?.(this.allMatches);
 		this.searchId = null;
 	}
@@ -175,7 +221,8 @@ export class WorkerPool {
 		endSeed: number,
 		maxResults: number,
 		callbacks: SearchCallbacks,
-		version: string = '1.6'
+		version: string = '1.6',
+		context?: PlayerContext
 	): Promise<void> {
 		// Cancel any existing search before starting a new one
 		if (this.searchId) {
@@ -195,10 +242,15 @@ export class WorkerPool {
 		this.foundByWorker.clear();
 		this.allMatches = [];
 		this.completedWorkers = 0;
+		this.checkpointByWorker.clear();
 
 		// Convert filter to JSON string for WASM
 		const filterJson = filterToSearchJson(filter);
 
+		// Resume from what an earlier, unfinished run of the same search saved
+		this.checkpointKey = `${CHECKPOINT_KEY_PREFIX}${version}:${startSeed}:${endSeed}:${JSON.stringify(context ?? null)}:${filterJson}`;
+		const checkpoints = loadCheckpoints(this.checkpointKey);
+
 		// Divide work among workers
 		const seedsPerWorker = Math.ceil(this.totalSeeds / this.workers.length);
 		// Each worker gets a proportional share of maxResults
@@ -222,6 +274,10 @@ export class WorkerPool {
 					endSeed: workerEnd,
 					maxResults: maxResultsPerWorker,
 					version,
+					context,
+					rangeStart: startSeed,
+					rangeEnd: endSeed,
+					checkpoints,
 				} as WorkerRequest);
 			} else {
 				// No work for this worker
@@ -244,6 +300,7 @@ export class WorkerPool {
 		});
 
 		this.searchId = null;
+		this.checkpointKey = null;
 		this.callbacks = {};
 	}
 
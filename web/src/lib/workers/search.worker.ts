@@ -1,12 +1,16 @@
 /**
  * Web Worker for parallel seed searching.
  *
- * This is a thin wrapper around the WASM search_range() function.
+ * This is a thin wrapper around the WASM search_range_into_checkpointed() function.
  * All filter evaluation happens in Rust for maximum performance.
  *
  * Search is processed in chunks to allow cancellation between WASM calls.
+ * After each chunk the worker posts its checkpoint, so the pool can save
+ * progress and a later search of the same filter and range resumes from it.
  */
 
+import type { PlayerContext } from '$lib/types/filters';
+
 // Chunk size for processing - smaller chunks = more responsive cancellation
 // and more frequent progress updates.
 const CHUNK_SIZE = 10_000;
@@ -22,16 +26,36 @@ export type WorkerRequest =
 			endSeed: number;
 			maxResults: number;
 			version: string;
+			context?: PlayerContext;
+			// Whole search range, shared by every worker's checkpoint
+			rangeStart: number;
+			rangeEnd: number;
+			// Saved checkpoints to resume from (empty for a fresh search)
+			checkpoints: string[];
 	  }
 	| { type: 'cancel'; id: string };
 
 export type WorkerResponse =
 	| { type: 'ready' }
 	| { type: 'progress'; id: string; checked: number; found: number }
-	| { type: 'match'; id: string; seed: number }
+	| { type: 'matches'; id: string; seeds: number[] }
+	| { type: 'checkpoint'; id: string; checkpoint: string }
 	| { type: 'complete'; id: string }
 	| { type: 'error'; id: string; message: string };
 
+// The parts of a checkpoint the worker reads
+interface CheckpointState {
+	completed: [number, number][];
+	matches: number[];
+}
+
+// What search_range_into_checkpointed() returns
+interface CheckpointedBatch {
+	checkpoint: string;
+	count: number;
+	stopped_early: boolean;
+}
+
 // WASM module reference
 let wasm: typeof import('rasmodius') | null = null;
 let cancelled = false;
@@ -53,6 +77,17 @@ async function init() {
 	}
 }
 
+// Seeds of [start, end] the checkpoint has already checked
+function checkedWithin(state: CheckpointState, start: number, end: number): number {
+	let checked = 0;
+	for (const [from, to] of state.completed) {
+		const lo = Math.max(from, start);
+		const hi = Math.min(to, end);
+		if (lo <= hi) checked += hi - lo + 1;
+	}
+	return checked;
+}
+
 // Yield to event loop - allows cancel messages to be processed between chunks
 function yieldToEventLoop(): Promise<void> {
 	return new Promise((resolve) => setTimeout(resolve, 0));
@@ -94,38 +129,61 @@ self.onmessage = async (e: MessageEvent<WorkerRequest>) => {
 					found: 0,
 				} as WorkerResponse);
 
+				// Start from the saved checkpoints, or a fresh one over the whole range
+				let checkpoint =
+					msg.checkpoints.length > 0
+						? msg.checkpoints.reduce((a, b) => wasm!.merge_checkpoints(a, b))
+						: wasm.new_checkpoint(
+								msg.filterJson,
+								msg.rangeStart,
+								msg.rangeEnd,
+								msg.version,
+								msg.context
+							);
+
+				// Report what earlier runs already found in this worker's range
+				let state = JSON.parse(checkpoint) as CheckpointState;
+				const resumed = state.matches
+					.filter((seed) => seed >= msg.startSeed && seed <= msg.endSeed)
+					.slice(0, workerSoftLimit);
+				if (resumed.length > 0) {
+					self.postMessage({ type: 'matches', id: msg.id, seeds: resumed } as WorkerResponse);
+				}
+				totalChecked = checkedWithin(state, msg.startSeed, msg.endSeed);
+				totalMatches = resumed.length;
+
 				// Process range in chunks to allow cancellation between WASM calls
 				let chunkStart = msg.startSeed;
+				// A chunk can't match more seeds than it has
+				const buffer = new Int32Array(Math.min(workerSoftLimit, CHUNK_SIZE));
 
 				while (chunkStart <= msg.endSeed && !cancelled && totalMatches < workerSoftLimit) {
 					const chunkEnd = Math.min(chunkStart + CHUNK_SIZE - 1, msg.endSeed);
 					const remainingResults = workerSoftLimit - totalMatches;
+					const out = buffer.subarray(0, Math.min(remainingResults, buffer.length));
 
-					// Track matches in this chunk
-					let chunkMatches = 0;
-
-					// Call WASM for this chunk - no progress callback, we report between chunks
-					wasm.search_range(
+					// Call WASM for this chunk - seeds the checkpoint covers are skipped
+					const batch = wasm.search_range_into_checkpointed(
 						msg.filterJson,
+						checkpoint,
 						chunkStart,
 						chunkEnd,
-						remainingResults,
 						msg.version,
-						// on_match callback
-						(seed: number): boolean => {
-							chunkMatches++;
-							self.postMessage({
-								type: 'match',
-								id: msg.id,
-								seed,
-							} as WorkerResponse);
-							return chunkMatches < remainingResults;
-						}
-					);
-
-					// Update totals
-					totalChecked += chunkEnd - chunkStart + 1;
-					totalMatches += chunkMatches;
+						out,
+						msg.context
+					) as CheckpointedBatch;
+					checkpoint = batch.checkpoint;
+					const found = Array.from(out.subarray(0, batch.count));
+
+					if (found.length > 0) {
+						self.postMessage({ type: 'matches', id: msg.id, seeds: found } as WorkerResponse);
+					}
+					self.postMessage({ type: 'checkpoint', id: msg.id, checkpoint } as WorkerResponse);
+
+					// Update totals from the checkpoint - a full result limit stops the chunk early
+					state = JSON.parse(checkpoint) as CheckpointState;
+					totalChecked = checkedWithin(state, msg.startSeed, msg.endSeed);
+					totalMatches += found.length;
 
 					// Report progress between chunks
 					self.postMessage({
@@ -135,8 +193,10 @@ self.onmessage = async (e: MessageEvent<WorkerRequest>) => {
 						found: totalMatches,
 					} as WorkerResponse);
 
-					// Move to next chunk
-					chunkStart = chunkEnd + 1;
+					// Move to next chunk - a full buffer resumes this one from its checkpoint
+					if (!batch.stopped_early) {
+						chunkStart = chunkEnd + 1;
+					}
 
 					// Yield to event loop between chunks - this allows cancel messages to be processed
 					if (chunkStart <= msg.endSeed && !cancelled && totalMatches < workerSoftLimit) {